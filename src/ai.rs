@@ -0,0 +1,41 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use crate::strategy::Bot;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Strength {
+    Random,
+    Greedy,
+    Lookahead,
+}
+
+impl Strength {
+    pub const ALL: [Strength; 3] =
+        [Strength::Random, Strength::Greedy, Strength::Lookahead];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strength::Random => "Random",
+            Strength::Greedy => "Greedy",
+            Strength::Lookahead => "Lookahead",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Strength> {
+        Strength::ALL
+            .iter()
+            .find(|strength| strength.name() == name)
+            .copied()
+    }
+
+    // Returns the bot that plays the computer at this strength; its moves
+    // can take a while so should be chosen off the UI thread
+    pub fn bot(&self) -> Bot {
+        match self {
+            Strength::Random => Bot::Random,
+            Strength::Greedy => Bot::Greedy,
+            Strength::Lookahead => Bot::Lookahead,
+        }
+    }
+}
@@ -0,0 +1,150 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use crate::engine::{Path, Tile};
+use crate::util::Pos;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+pub const FRAME_SECS: f64 = 1.0 / 60.0;
+const MS_PER_CELL: u64 = 70;
+const MS_MIN: u64 = 150;
+const MS_MAX: u64 = 600; // so that big boards settle quickly
+const VANISH_MS: u64 = 500;
+const FLASH: f64 = 0.3; // the fraction of the vanishing spent flashing
+const FLASHES: f64 = 3.0;
+
+// Every moving tile slides along its path at the same time, easing in and
+// out; how long it takes depends only on the longest path, never on the
+// delay
+pub struct Slide {
+    paths: Vec<Path>,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Slide {
+    pub fn new(paths: Vec<Path>) -> Self {
+        let cells = paths.iter().map(|path| path.len() - 1).max();
+        let ms = (cells.unwrap_or_default() as u64 * MS_PER_CELL)
+            .clamp(MS_MIN, MS_MAX);
+        Self {
+            paths,
+            started: Instant::now(),
+            duration: Duration::from_millis(ms),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    // Carries on from where it was before being paused
+    pub fn postpone(&mut self, paused: Duration) {
+        self.started += paused;
+    }
+
+    // Returns where each moving tile (keyed by where it ends up) should be
+    // drawn now as a fractional (column, row)
+    pub fn positions(&self) -> HashMap<Pos, (f64, f64)> {
+        let t = ease(
+            (self.started.elapsed().as_secs_f64()
+                / self.duration.as_secs_f64())
+            .min(1.0),
+        );
+        let mut positions = HashMap::with_capacity(self.paths.len());
+        for path in &self.paths {
+            let steps = path.len() - 1;
+            let along = t * steps as f64;
+            let i = (along.floor() as usize).min(steps - 1);
+            let fraction = along - i as f64;
+            let (a, b) = (path[i], path[i + 1]);
+            positions.insert(
+                path[steps],
+                (
+                    a.x as f64 + (b.x - a.x) as f64 * fraction,
+                    a.y as f64 + (b.y - a.y) as f64 * fraction,
+                ),
+            );
+        }
+        positions
+    }
+}
+
+// Cubic ease in and out
+fn ease(t: f64) -> f64 {
+    if t < 0.5 {
+        4.0 * t * t * t
+    } else {
+        1.0 - (2.0 - 2.0 * t).powi(3) / 2.0
+    }
+}
+
+// The tiles just removed flash and then shrink and fade away while the
+// points they scored float up from their middle
+pub struct Vanish {
+    pub tiles: Vec<(Pos, Tile)>,
+    pub points: u16,
+    middle: (f64, f64), // fractional (column, row)
+    started: Instant,
+    duration: Duration,
+}
+
+impl Vanish {
+    pub fn new(tiles: Vec<(Pos, Tile)>, points: u16) -> Self {
+        let count = tiles.len().max(1) as f64;
+        let column = tiles.iter().map(|(pos, _)| pos.x as f64).sum::<f64>();
+        let row = tiles.iter().map(|(pos, _)| pos.y as f64).sum::<f64>();
+        Self {
+            tiles,
+            points,
+            middle: (column / count + 0.5, row / count + 0.5),
+            started: Instant::now(),
+            duration: Duration::from_millis(VANISH_MS),
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    // Carries on from where it was before being paused
+    pub fn postpone(&mut self, paused: Duration) {
+        self.started += paused;
+    }
+
+    // Makes the next frame the last
+    pub fn skip(&mut self) {
+        self.duration = Duration::ZERO;
+    }
+
+    fn progress(&self) -> f64 {
+        (self.started.elapsed().as_secs_f64() / self.duration.as_secs_f64())
+            .min(1.0)
+    }
+
+    // Returns true if the tiles should be drawn highlighted
+    pub fn is_lit(&self) -> bool {
+        let progress = self.progress();
+        progress < FLASH
+            && (progress / FLASH * FLASHES * 2.0) as i32 % 2 == 0
+    }
+
+    // Returns how much of each tile's width and height to draw (0.0-1.0)
+    pub fn scale(&self) -> f64 {
+        let progress = self.progress();
+        if progress < FLASH {
+            1.0
+        } else {
+            1.0 - ease((progress - FLASH) / (1.0 - FLASH))
+        }
+    }
+
+    // Returns where the points should be drawn as a fractional (column,
+    // row) and how faded they are (0.0-1.0)
+    pub fn label(&self) -> ((f64, f64), f64) {
+        let progress = self.progress();
+        let (column, row) = self.middle;
+        ((column, row - progress), ease(progress))
+    }
+}
@@ -3,22 +3,56 @@
 
 use super::CONFIG;
 use crate::board;
-use crate::fixed::{about_html, Action, Arrow, HELP_HTML, MESSAGE_DELAY};
+use crate::config;
+use crate::engine::{self, Players};
+use crate::fixed::{
+    about_html, help_html, Action, Variant, APPNAME, MESSAGE_DELAY, TICK,
+    TIME_ATTACK_SECS, TIME_PER_TILE,
+};
 use crate::html_form;
+use crate::i18n::{self, tr, trf};
+use crate::keys::Command;
+use crate::keys_form;
 use crate::mainwindow;
 use crate::options_form;
+use crate::profile::SavedGame;
+use crate::profiles_form;
+use crate::race::{Progress, Race, RACE_PORT};
+use crate::rating;
+use crate::solver;
+use crate::theme;
+use crate::util;
 use fltk::prelude::*;
-use thousands::Separable;
+use std::thread;
 
 pub struct Application {
     app: fltk::app::App,
     mainwindow: fltk::window::Window,
+    toolbuttons: mainwindow::ToolButtons,
     board: board::Board,
     statusbar: fltk::frame::Frame,
+    badgelabel: fltk::frame::Frame,
+    timelabel: fltk::frame::Frame,
     scorelabel: fltk::frame::Frame,
     helpform: Option<html_form::Form>,
+    raceform: Option<html_form::Form>, // shows every racer's progress
+    sender: fltk::app::Sender<Action>,
     receiver: fltk::app::Receiver<Action>,
     score: u16,
+    clock: Option<fltk::app::TimeoutHandle>,
+    time_left: f64,        // seconds
+    resume_clock: bool,    // the clock was running when paused
+    deferred: Vec<Action>, // the steps of a move that arrived while paused
+    race: Option<Race>,
+    search: Option<Search>, // looking for a solvable board
+    search_id: u32,
+}
+
+// A board waiting on its solvable seed
+struct Search {
+    id: u32,
+    setup: engine::Setup,
+    for_race: bool,
 }
 
 impl Application {
@@ -26,21 +60,40 @@ impl Application {
         let app =
             fltk::app::App::default().with_scheme(fltk::app::Scheme::Oxy);
         let (sender, receiver) = fltk::app::channel::<Action>();
-        let (mut mainwindow, board, statusbar, scorelabel) =
-            mainwindow::make(sender);
+        let (
+            mut mainwindow,
+            toolbuttons,
+            board,
+            statusbar,
+            badgelabel,
+            timelabel,
+            scorelabel,
+        ) = mainwindow::make(sender);
         mainwindow::add_event_handlers(&mut mainwindow, sender);
         mainwindow.show();
         let mut app = Self {
             app,
             mainwindow,
+            toolbuttons,
             board,
             statusbar,
+            badgelabel,
+            timelabel,
             scorelabel,
             helpform: None,
+            raceform: None,
+            sender,
             receiver,
             score: 0,
+            clock: None,
+            time_left: 0.0,
+            resume_clock: false,
+            deferred: vec![],
+            race: None,
+            search: None,
+            search_id: 0,
         };
-        app.on_new_game();
+        app.restore_or_new_game();
         app
     }
 
@@ -48,31 +101,35 @@ impl Application {
         while self.app.wait() {
             if let Some(action) = self.receiver.recv() {
                 match action {
+                    Action::DeleteAdjoining
+                    | Action::CloseUp
+                    | Action::Animate
+                    | Action::ComputerMove(..)
+                        if self.board.is_paused() =>
+                    {
+                        self.deferred.push(action)
+                    }
                     Action::New => self.on_new_game(),
                     Action::Options => self.on_options(),
+                    Action::KeyBindings => self.on_key_bindings(),
+                    Action::Profiles => self.on_profiles(),
                     Action::About => self.on_about(),
                     Action::Help => self.on_help(),
                     Action::Quit => self.on_quit(),
-                    Action::MoveUp => {
-                        self.clear_status();
-                        self.board.on_arrow(Arrow::Up);
-                    }
-                    Action::MoveDown => {
-                        self.clear_status();
-                        self.board.on_arrow(Arrow::Down);
-                    }
-                    Action::MoveLeft => {
-                        self.clear_status();
-                        self.board.on_arrow(Arrow::Left);
-                    }
-                    Action::MoveRight => {
-                        self.clear_status();
-                        self.board.on_arrow(Arrow::Right)
+                    Action::Pause => self.on_pause(),
+                    Action::AutoPause => self.on_auto_pause(),
+                    Action::Navigate(navigation) => {
+                        self.board.on_navigate(navigation);
+                        self.show_preview();
                     }
                     Action::ClickTile => {
                         self.clear_status();
                         self.board.on_click_tile();
                     }
+                    Action::Hover => {
+                        self.board.on_hover();
+                        self.show_preview();
+                    }
                     Action::PressTile => {
                         self.clear_status();
                         self.board.on_press_tile();
@@ -84,24 +141,172 @@ impl Application {
                     Action::UpdatedScore(score) => {
                         self.updated_score(score)
                     }
-                    Action::Redraw => self.board.redraw(),
+                    Action::Removed(count) => self.removed(count),
+                    Action::Animate => self.board.animate(),
                     Action::GameOver => self.game_over(),
+                    Action::Tick => self.on_tick(),
+                    Action::HighScores => self.on_highscores(),
+                    Action::ComputerMove(pos, game_id) => {
+                        self.board.on_computer_move(pos, game_id)
+                    }
+                    Action::Race => self.on_race(),
+                    Action::RaceUpdate => self.on_race_update(),
+                    Action::Rated(rating, game_id) => {
+                        if self.board.on_rated(rating, game_id) {
+                            self.update_badge();
+                        }
+                    }
+                    Action::SolvableSeed(seed, id) => {
+                        self.on_solvable_seed(seed, id)
+                    }
                 }
             }
         }
     }
 
     pub fn on_new_game(&mut self) {
+        self.leave_race();
         self.score = 0;
-        self.board.new_game();
-        self.set_status("New game! Click a tile…", Some(MESSAGE_DELAY));
+        self.resume_clock = false;
+        self.deferred.clear();
+        self.stop_clock();
+        let (variant, solvable) = {
+            let config = CONFIG.get().read().unwrap();
+            (config.board_variant, config.board_solvable)
+        };
+        if variant == Variant::HotSeat {
+            get_player_names();
+        }
+        let setup = board::new_setup();
+        if solvable {
+            self.find_solvable(setup, false);
+        } else {
+            self.start_new_game(setup);
+        }
+    }
+
+    fn start_new_game(&mut self, setup: engine::Setup) {
+        self.search = None;
+        let variant = CONFIG.get().read().unwrap().board_variant;
+        self.board.start(setup, variant);
+        self.update_badge();
+        if variant == Variant::TimeAttack {
+            self.time_left = TIME_ATTACK_SECS;
+            self.start_clock();
+        }
+        self.update_time_label();
+        let players = self.board.players();
+        if CONFIG.get().read().unwrap().board_solvable
+            && !self.board.setup().solvable
+        {
+            self.set_status(
+                tr("No solvable board was found in time so this one may \
+                not be clearable"),
+                Some(MESSAGE_DELAY),
+            );
+        } else if players.is_empty() {
+            self.set_status(
+                tr("New game! Click a tile…"),
+                Some(MESSAGE_DELAY),
+            );
+        } else {
+            self.set_status(
+                &trf("New game! {0}", &[&self.idle_status()]),
+                None,
+            );
+        }
+    }
+
+    // Looks for a solvable seed in another thread since it can take a
+    // couple of seconds; the board is cleared until it is found (or not)
+    // and then the game (or race) starts
+    fn find_solvable(&mut self, setup: engine::Setup, for_race: bool) {
+        self.board.wait();
+        self.search_id = self.search_id.wrapping_add(1);
+        let id = self.search_id;
+        self.search = Some(Search { id, setup, for_race });
+        #[allow(clippy::clone_on_copy)]
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let seed = solver::find_solvable_seed(&setup);
+            sender.send(Action::SolvableSeed(seed, id));
+        });
+        self.set_status(tr("Finding a solvable board…"), None);
+    }
+
+    fn on_solvable_seed(&mut self, seed: Option<u64>, id: u32) {
+        if self.search.as_ref().map(|search| search.id) != Some(id) {
+            return; // a search that was superseded or abandoned
+        }
+        let Some(Search { mut setup, for_race, .. }) = self.search.take()
+        else {
+            return;
+        };
+        if let Some(seed) = seed {
+            setup.seed = seed;
+            setup.solvable = true;
+        }
+        if !for_race {
+            self.start_new_game(setup);
+        } else if let Some(race) = &mut self.race {
+            race.start(setup);
+            self.start_race_game(setup);
+        }
+    }
+
+    // Carries on the active profile's saved game if it has one
+    fn restore_or_new_game(&mut self) {
+        let saved = CONFIG.get().write().unwrap().saved_game.take();
+        match saved {
+            Some(saved) => self.restore_game(&saved),
+            None => self.on_new_game(),
+        }
+    }
+
+    fn restore_game(&mut self, saved: &SavedGame) {
+        self.leave_race();
+        self.score = saved.game.score;
+        self.resume_clock = false;
+        self.deferred.clear();
+        self.stop_clock();
+        self.search = None;
+        self.board.restore(saved);
+        self.update_badge();
+        if saved.variant == Variant::TimeAttack {
+            self.time_left = saved.time_left;
+            self.start_clock();
+        }
+        self.update_time_label();
+        self.set_status(
+            tr("Carrying on where you left off…"),
+            Some(MESSAGE_DELAY),
+        );
+    }
+
+    // Keeps the one-player game in progress (if any) with the profile
+    fn save_game(&mut self) {
+        let saved = if self.race.is_none() {
+            self.board.snapshot().map(|saved| SavedGame {
+                time_left: self.time_left,
+                ..saved
+            })
+        } else {
+            None
+        };
+        CONFIG.get().write().unwrap().saved_game = saved;
     }
 
     fn on_options(&mut self) {
+        let running = self.clock.is_some();
+        self.stop_clock(); // The clock doesn't run while Options is open
         let form = options_form::Form::default();
+        if running {
+            self.start_clock();
+        }
         if *form.ok.borrow() {
+            self.retranslate(); // In case the language changed
             self.set_status(
-                "Start New Game for New Options",
+                tr("Start New Game for New Options"),
                 Some(MESSAGE_DELAY),
             );
         } else {
@@ -109,21 +314,140 @@ impl Application {
         }
     }
 
+    fn on_key_bindings(&mut self) {
+        let running = self.clock.is_some();
+        self.stop_clock();
+        let form = keys_form::Form::default();
+        if running {
+            self.start_clock();
+        }
+        if *form.ok.borrow() {
+            self.retranslate(); // New bindings apply at once
+        }
+    }
+
+    // Updates the texts that mention keys or are in the chosen language
+    fn retranslate(&mut self) {
+        let keys = CONFIG.get().read().unwrap().keys.clone();
+        mainwindow::update_tooltips(&mut self.toolbuttons, &keys);
+        self.timelabel.set_tooltip(tr("Time left"));
+        self.update_badge();
+        if let Some(helpform) = &mut self.helpform {
+            helpform.set_html(&help_html(&keys));
+        }
+    }
+
+    fn on_profiles(&mut self) {
+        let running = self.clock.is_some();
+        self.stop_clock();
+        let form = profiles_form::Form::default();
+        if running {
+            self.start_clock();
+        }
+        let Some(name) = form.name.borrow().clone() else {
+            return;
+        };
+        if name == CONFIG.get().read().unwrap().profile {
+            return;
+        }
+        self.save_game();
+        CONFIG.get().write().unwrap().switch_profile(&name);
+        config::report_problems();
+        theme::reload();
+        self.mainwindow.set_label(&mainwindow::title());
+        self.retranslate();
+        self.restore_or_new_game();
+    }
+
+    fn on_pause(&mut self) {
+        if self.board.is_paused() {
+            self.board.resume();
+            if self.resume_clock {
+                self.start_clock();
+            }
+            for action in self.deferred.drain(..) {
+                self.sender.send(action);
+            }
+            self.clear_status();
+        } else if self.board.pause() {
+            self.resume_clock = self.clock.is_some();
+            self.stop_clock();
+            self.clear_status();
+        }
+    }
+
+    // Pauses if the main window is minimized or another application has
+    // the focus (but not for this application's own dialogs)
+    fn on_auto_pause(&mut self) {
+        if !self.board.is_paused()
+            && (!self.mainwindow.visible() || fltk::app::focus().is_none())
+        {
+            self.on_pause();
+        }
+    }
+
     fn on_about(&mut self) {
-        html_form::Form::new("About", &about_html(), true, 480, 300, false);
+        let running = self.clock.is_some();
+        self.stop_clock();
+        html_form::Form::new(
+            tr("About"),
+            &about_html(),
+            true,
+            480,
+            300,
+            false,
+        );
+        if running {
+            self.start_clock();
+        }
+    }
+
+    fn on_highscores(&mut self) {
+        let running = self.clock.is_some();
+        self.stop_clock();
+        let html = {
+            let config = CONFIG.get().read().unwrap();
+            let mut html = config.highscores.to_html();
+            let end = html.rfind("</body>").unwrap_or(html.len());
+            html.insert_str(
+                end,
+                &(config.endless_records.to_html()
+                    + &config.statistics.to_html()),
+            );
+            html
+        };
+        let profile = CONFIG.get().read().unwrap().profile.clone();
+        html_form::Form::new(
+            &trf("High Scores — {0}", &[&profile]),
+            &html,
+            true,
+            360,
+            420,
+            true,
+        );
+        if running {
+            self.start_clock();
+        }
     }
 
     fn on_help(&mut self) {
         if let Some(helpform) = &mut self.helpform {
             helpform.show();
         } else {
+            let html = help_html(&CONFIG.get().read().unwrap().keys);
             self.helpform = Some(html_form::Form::new(
-                "Help", HELP_HTML, false, 480, 420, true,
+                tr("Help"),
+                &html,
+                false,
+                480,
+                420,
+                true,
             ));
         }
     }
 
     fn on_quit(&mut self) {
+        self.save_game();
         let config = CONFIG.get().read().unwrap();
         config.save(
             self.mainwindow.x(),
@@ -136,17 +460,320 @@ impl Application {
 
     fn updated_score(&mut self, score: u16) {
         self.score = score;
+        self.report_progress();
+        let players = self.board.players();
+        if !players.is_empty() {
+            self.updated_players(&players);
+            return;
+        }
+        let category = self.board.category();
         let config = CONFIG.get().read().unwrap();
+        self.scorelabel.set_tooltip(tr("Score • High score"));
         self.scorelabel.set_label(&format!(
             "{} • {}",
-            score.separate_with_commas(),
-            config.board_highscore.separate_with_commas()
+            i18n::number(score),
+            i18n::number(config.highscores.best(&category))
         ));
         fltk::app::redraw(); // redraws the world
     }
 
+    fn updated_players(&mut self, players: &Players) {
+        let scores: Vec<String> = players
+            .scores
+            .iter()
+            .map(|&score| i18n::number(score))
+            .collect();
+        self.scorelabel.set_label(&scores.join(" • "));
+        self.scorelabel.set_tooltip(&players.names.join(" • "));
+        if self.board.is_playing() {
+            self.statusbar.set_label(&self.idle_status());
+        }
+        fltk::app::redraw(); // redraws the world
+    }
+
+    fn removed(&mut self, count: u16) {
+        if self.clock.is_some() {
+            self.time_left += count as f64 * TIME_PER_TILE;
+            self.update_time_label();
+        }
+    }
+
     fn game_over(&mut self) {
-        self.set_status("Click New or press n to play…", None);
+        self.stop_clock();
+        if self.race.as_ref().is_some_and(|race| race.is_started()) {
+            self.set_status(tr("Waiting for the other racers…"), None);
+        } else {
+            let keys = key_names(Command::NewGame);
+            self.set_status(
+                &trf("Click New or press {0} to play…", &[&keys]),
+                None,
+            );
+        }
+    }
+
+    // Host or join a race if not in one; if hosting start a new race;
+    // otherwise offer to leave
+    fn on_race(&mut self) {
+        match &self.race {
+            None => self.host_or_join(),
+            Some(race) if race.is_host() => {
+                let setup = board::new_setup();
+                if CONFIG.get().read().unwrap().board_solvable {
+                    self.find_solvable(setup, true);
+                } else {
+                    if let Some(race) = &mut self.race {
+                        race.start(setup);
+                    }
+                    self.start_race_game(setup);
+                }
+            }
+            Some(_) => {
+                fltk::dialog::message_title(&trf(
+                    "Race — {0}",
+                    &[&APPNAME],
+                ));
+                if fltk::dialog::choice2_default(
+                    tr("Leave the race?"),
+                    tr("&No"),
+                    tr("&Yes"),
+                    "",
+                ) == Some(1)
+                {
+                    self.leave_race();
+                    self.set_status(
+                        tr("Left the race"),
+                        Some(MESSAGE_DELAY),
+                    );
+                }
+            }
+        }
+    }
+
+    fn host_or_join(&mut self) {
+        let name = CONFIG.get().read().unwrap().player_names[0].clone();
+        fltk::dialog::message_title(&trf("Race — {0}", &[&APPNAME]));
+        let race = match fltk::dialog::choice2_default(
+            tr("Race on the local network"),
+            tr("&Cancel"),
+            tr("&Host"),
+            tr("&Join"),
+        ) {
+            Some(1) => {
+                Race::host(&name, RACE_PORT, self.sender).map_err(|err| {
+                    trf(
+                        "Failed to host a race on port {0}:\n{1}",
+                        &[&RACE_PORT, &err],
+                    )
+                })
+            }
+            Some(2) => {
+                fltk::dialog::message_title(&trf(
+                    "Race — {0}",
+                    &[&APPNAME],
+                ));
+                let Some(address) = fltk::dialog::input_default(
+                    tr("Host's address:"),
+                    &format!("localhost:{RACE_PORT}"),
+                ) else {
+                    return;
+                };
+                let mut address = address.trim().to_string();
+                if !address.contains(':') {
+                    address = format!("{address}:{RACE_PORT}");
+                }
+                Race::join(&address, &name, self.sender).map_err(|err| {
+                    trf(
+                        "Failed to join the race at {0}:\n{1}",
+                        &[&address, &err],
+                    )
+                })
+            }
+            _ => return,
+        };
+        match race {
+            Ok(race) => {
+                let message = if race.is_host() {
+                    trf(
+                        "Hosting a race on port {0}: press {1} to start \
+                        once everyone has joined",
+                        &[&race.port(), &key_names(Command::Race)],
+                    )
+                } else {
+                    tr("Joined the race: waiting for the host to start…")
+                        .to_string()
+                };
+                self.race = Some(race);
+                self.show_racers();
+                self.set_status(&message, None);
+            }
+            Err(err) => util::warning(&err),
+        }
+    }
+
+    fn start_race_game(&mut self, setup: engine::Setup) {
+        self.score = 0;
+        self.resume_clock = false;
+        self.deferred.clear();
+        self.stop_clock();
+        self.search = None;
+        self.board.start(setup, Variant::Classic);
+        self.update_badge();
+        self.update_time_label();
+        if let Some(raceform) = &mut self.raceform {
+            raceform.show();
+        }
+        self.set_status(
+            tr("The race is on! Click a tile…"),
+            Some(MESSAGE_DELAY),
+        );
+    }
+
+    fn leave_race(&mut self) {
+        self.race = None; // dropping the race disconnects
+        self.raceform = None;
+        self.search = None;
+    }
+
+    fn on_race_update(&mut self) {
+        let Some(race) = &mut self.race else {
+            return; // a late update from a race that has been left
+        };
+        let errors = race.take_errors();
+        let setup = race.take_setup();
+        let connected = race.is_connected();
+        for error in errors {
+            util::warning(&error);
+        }
+        if !connected {
+            self.leave_race();
+            return;
+        }
+        if let Some(setup) = setup {
+            self.start_race_game(setup);
+        }
+        self.show_racers();
+        if let Some(race) = &mut self.race {
+            if race.is_started() && !race.over {
+                if let Some(winner) = race.winner() {
+                    race.over = true;
+                    self.board.stop();
+                    let message = if race.is_host() {
+                        trf(
+                            "{0} wins! Press {1} to race again…",
+                            &[&winner, &key_names(Command::Race)],
+                        )
+                    } else {
+                        trf("{0} wins!", &[&winner])
+                    };
+                    self.set_status(&message, None);
+                }
+            }
+        }
+    }
+
+    fn show_racers(&mut self) {
+        if let Some(race) = &self.race {
+            let html = race.to_html();
+            if let Some(raceform) = &mut self.raceform {
+                raceform.set_html(&html);
+            } else {
+                self.raceform = Some(html_form::Form::new(
+                    tr("Race"),
+                    &html,
+                    false,
+                    360,
+                    240,
+                    true,
+                ));
+            }
+        }
+    }
+
+    // Tells the other racers how this racer is doing
+    fn report_progress(&mut self) {
+        if let Some(race) = &self.race {
+            if race.is_started() && !race.over {
+                let progress = if self.board.is_playing() {
+                    Progress::Playing
+                } else if self.board.has_won() {
+                    Progress::Cleared
+                } else {
+                    Progress::Stuck
+                };
+                race.report(self.score, self.board.remaining(), progress);
+                if race.is_host() {
+                    self.on_race_update(); // joiners' reports do this
+                }
+            }
+        }
+    }
+
+    fn start_clock(&mut self) {
+        #[allow(clippy::clone_on_copy)] // The clone is needed
+        let sender = self.sender.clone();
+        self.clock = Some(fltk::app::add_timeout3(TICK, move |handle| {
+            sender.send(Action::Tick);
+            fltk::app::repeat_timeout3(TICK, handle);
+        }));
+    }
+
+    fn stop_clock(&mut self) {
+        if let Some(clock) = self.clock.take() {
+            fltk::app::remove_timeout3(clock);
+        }
+    }
+
+    fn on_tick(&mut self) {
+        if self.clock.is_none()
+            || self.helpform.as_ref().is_some_and(|form| form.shown())
+        {
+            return; // The clock doesn't run while Help is shown
+        }
+        self.time_left -= TICK;
+        if self.time_left <= 0.0 {
+            self.time_left = 0.0;
+            self.stop_clock();
+            self.board.time_up();
+        }
+        self.update_time_label();
+    }
+
+    fn update_time_label(&mut self) {
+        if self.board.variant() == Variant::TimeAttack {
+            let seconds = self.time_left.ceil() as u32;
+            self.timelabel.set_label(&format!(
+                "{}:{:02}",
+                seconds / 60,
+                seconds % 60
+            ));
+        } else {
+            self.timelabel.set_label("");
+        }
+        fltk::app::redraw(); // redraws the world
+    }
+
+    // Shows what is known about the board: its difficulty and whether it
+    // can be cleared
+    fn update_badge(&mut self) {
+        let rating = self.board.rating();
+        let (mut label, mut tooltip) = if rating == 0 {
+            ("…".to_string(), tr("Rating the board…").to_string())
+        } else {
+            (
+                rating::stars(rating),
+                trf(
+                    "Difficulty: {0} of {1}",
+                    &[&rating, &rating::STARS_MAX],
+                ),
+            )
+        };
+        if self.board.setup().solvable {
+            label.push_str(" ✓");
+            tooltip.push('\n');
+            tooltip.push_str(tr("This board can be cleared"));
+        }
+        self.badgelabel.set_label(&label);
+        self.badgelabel.set_tooltip(&tooltip);
     }
 
     fn set_status(&mut self, message: &str, timeout: Option<f64>) {
@@ -163,8 +790,58 @@ impl Application {
         }
     }
 
+    // Shows what clicking the hovered or focused tile would do
+    fn show_preview(&mut self) {
+        match self.board.preview_status() {
+            Some(message) => self.set_status(&message, None),
+            None => self.clear_status(),
+        }
+    }
+
     fn clear_status(&mut self) {
-        self.statusbar.set_label("");
+        self.statusbar.set_label(&self.idle_status());
         fltk::app::redraw(); // redraws the world
     }
+
+    // Returns the message to show when there is no other: how to resume
+    // when paused or whose turn it is when taking turns
+    fn idle_status(&self) -> String {
+        if self.board.is_paused() {
+            let keys = key_names(Command::Pause);
+            return if keys.is_empty() {
+                tr("Paused: click Pause to resume").to_string()
+            } else {
+                trf("Paused: click Pause or press {0} to resume", &[&keys])
+            };
+        }
+        let players = self.board.players();
+        if players.is_empty() || !self.board.is_playing() {
+            String::new()
+        } else if players.is_computers_turn() {
+            trf("{0} is thinking…", &[&players.current_name()])
+        } else {
+            trf("{0} to play", &[&players.current_name()])
+        }
+    }
+}
+
+// Returns the keys bound to the command, e.g., "F1 or h"
+fn key_names(command: Command) -> String {
+    CONFIG.get().read().unwrap().keys.describe(command)
+}
+
+fn get_player_names() {
+    let mut names = CONFIG.get().read().unwrap().player_names.clone();
+    for (i, name) in names.iter_mut().enumerate() {
+        fltk::dialog::message_title(&trf("Hot Seat — {0}", &[&APPNAME]));
+        if let Some(new_name) = fltk::dialog::input_default(
+            &trf("Player {0}'s name:", &[&(i + 1)]),
+            name,
+        ) {
+            if !new_name.trim().is_empty() {
+                *name = new_name.trim().to_string();
+            }
+        }
+    }
+    CONFIG.get().write().unwrap().player_names = names;
 }
@@ -2,33 +2,57 @@
 // License: GPLv3
 
 use super::CONFIG;
-use crate::board_util::{self, Mode, Size, Tiles};
-use crate::fixed::{
-    Action, Arrow, APRICOT, BEIGE, BROWN, COLORS, GREEN, LAVENDER, LIME,
-    MINT, OLIVE, PINK, TEAL, TINY_DELAY, WHITE,
+use crate::ai::Strength;
+use crate::animation::{self, Slide, Vanish};
+use crate::board_util::{self, Mode};
+use crate::engine::{
+    Game, Kind, Navigation, Players, PosSet, Setup, Size, Specials, Tile,
 };
-use crate::util::Pos;
+use crate::fixed::{Action, Variant, TINY_DELAY};
+use crate::i18n::{self, tr, trf};
+use crate::palette::{self, Palette};
+use crate::preset::{self, Preset};
+use crate::profile::SavedGame;
+use crate::rating;
+use crate::theme::{Theme, THEME};
+use crate::tileset::{self, TileSet};
+use crate::util::{self, Pos};
 use fltk::enums::Color;
 use fltk::prelude::*;
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
+use rand::SeedableRng;
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
 use std::ops::{Deref, DerefMut};
 use std::rc::Rc;
-
-type PosSet = HashSet<Pos>;
-type PosForPos = HashMap<Pos, Pos>;
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub struct Board {
     widget: fltk::widget::Widget,
     mode: Rc<RefCell<Mode>>,
+    variant: Rc<RefCell<Variant>>,
     selected: Rc<RefCell<Option<Pos>>>,
-    tiles: Rc<RefCell<Tiles>>,
-    size: Rc<RefCell<Size>>,
-    maxcolors: Rc<RefCell<u8>>,
+    hovered: Rc<RefCell<Option<Pos>>>, // the tile whose group is outlined
+    wrap: Rc<RefCell<bool>>,           // the focus wraps round at the edges
+    game: Rc<RefCell<Game>>,
+    setup: Rc<RefCell<Setup>>, // the game's, to save and restore it
+    rating: Rc<RefCell<u8>>,   // the board's difficulty in stars
+    players: Rc<RefCell<Players>>, // empty unless taking turns
+    colors: Rc<RefCell<Vec<Color>>>,
+    symbols: Rc<RefCell<bool>>, // draw a symbol on each color
+    tileset: Rc<RefCell<Option<TileSet>>>, // None means colored boxes
     delay_ms: Rc<RefCell<u16>>,
-    score: Rc<RefCell<u16>>,
+    reduced_motion: Rc<RefCell<bool>>,
+    slide: Rc<RefCell<Option<Slide>>>, // while tiles are moving
+    vanish: Rc<RefCell<Option<Vanish>>>, // while tiles are being removed
+    timer: Rc<RefCell<Option<Timer>>>, // the next step of the move
+    paused: Rc<RefCell<Option<Instant>>>, // when it was paused
     adjoining: Rc<RefCell<PosSet>>,
+    removed: Rc<RefCell<usize>>, // the number removed by the last move
+    started: Rc<RefCell<Instant>>,
+    strength: Rc<RefCell<Strength>>,
+    game_id: Rc<RefCell<u32>>, // so that stale computer moves are ignored
     sender: fltk::app::Sender<Action>,
 }
 
@@ -37,13 +61,28 @@ impl Board {
         let mut board = Board {
             widget: fltk::widget::Widget::default(),
             mode: Rc::new(RefCell::new(Mode::GameOver)),
+            variant: Rc::new(RefCell::new(Variant::Classic)),
             selected: Rc::default(),
-            tiles: Rc::default(),
-            size: Rc::default(),
-            maxcolors: Rc::default(),
+            hovered: Rc::default(),
+            wrap: Rc::default(),
+            game: Rc::default(),
+            setup: Rc::default(),
+            rating: Rc::default(),
+            players: Rc::default(),
+            colors: Rc::default(),
+            symbols: Rc::default(),
+            tileset: Rc::default(),
             delay_ms: Rc::default(),
-            score: Rc::default(),
+            reduced_motion: Rc::default(),
+            slide: Rc::default(),
+            vanish: Rc::default(),
+            timer: Rc::default(),
+            paused: Rc::default(),
             adjoining: Rc::default(),
+            removed: Rc::default(),
+            started: Rc::new(RefCell::new(Instant::now())),
+            strength: Rc::new(RefCell::new(Strength::Greedy)),
+            game_id: Rc::default(),
             sender,
         };
         add_event_handler(&mut board, sender);
@@ -51,101 +90,312 @@ impl Board {
         board
     }
 
-    pub fn new_game(&mut self) {
+    // Starts a game whose board (tiles and colors) depends only on the
+    // setup so that every racer gets the same board
+    pub fn start(&mut self, setup: Setup, variant: Variant) {
+        self.start_rated(setup, variant, 0);
+    }
+
+    // Starts the game with the given rating or, if that is 0 (unknown),
+    // rates the board in another thread since that takes a while
+    fn start_rated(&mut self, setup: Setup, variant: Variant, rating: u8) {
         *self.mode.borrow_mut() = Mode::Playing;
         *self.selected.borrow_mut() = None;
-        *self.score.borrow_mut() = 0;
+        *self.hovered.borrow_mut() = None;
+        *self.slide.borrow_mut() = None;
+        *self.vanish.borrow_mut() = None;
+        *self.paused.borrow_mut() = None;
+        self.cancel_timer();
+        self.adjoining.borrow_mut().clear();
         let config = CONFIG.get().read().unwrap();
-        *self.size.borrow_mut() = Size::new(
-            config.board_columns as i32,
-            config.board_rows as i32,
-        );
-        *self.maxcolors.borrow_mut() = config.board_maxcolors;
+        *self.variant.borrow_mut() = variant;
         *self.delay_ms.borrow_mut() = config.board_delay_ms;
-        *self.tiles.borrow_mut() = self.get_tiles();
-        self.sender.send(Action::UpdatedScore(*self.score.borrow()));
+        *self.reduced_motion.borrow_mut() = config.board_reduced_motion;
+        *self.wrap.borrow_mut() = config.board_wrap;
+        *self.symbols.borrow_mut() = config.board_symbols;
+        *self.strength.borrow_mut() = config.board_ai_strength;
+        let game_id = self.game_id.borrow().wrapping_add(1);
+        *self.game_id.borrow_mut() = game_id;
+        *self.setup.borrow_mut() = setup;
+        *self.rating.borrow_mut() = rating;
+        if rating == 0 {
+            #[allow(clippy::clone_on_copy)] // The clone is needed
+            let sender = self.sender.clone();
+            thread::spawn(move || {
+                sender.send(Action::Rated(rating::rate(&setup), game_id));
+            });
+        }
+        let mut rng = StdRng::seed_from_u64(setup.seed);
+        *self.game.borrow_mut() = setup.new_game(&mut rng);
+        *self.players.borrow_mut() = match variant {
+            Variant::HotSeat => Players::new(&config.player_names),
+            Variant::Versus => {
+                let names = [
+                    config.player_names[0].clone(),
+                    tr("Computer").to_string(),
+                ];
+                let mut players = Players::new(&names);
+                players.computer = Some(1);
+                players
+            }
+            _ => Players::default(),
+        };
+        *self.colors.borrow_mut() =
+            self.get_colors(config.board_palette, &mut rng);
+        let tileset_name = config.board_tileset.clone();
+        drop(config);
+        self.update_tileset(&tileset_name);
+        *self.started.borrow_mut() = Instant::now();
+        self.sender.send(Action::UpdatedScore(0));
         self.widget.redraw();
     }
 
-    fn get_tiles(&self) -> Tiles {
-        let size = *self.size.borrow();
-        let colors = self.get_colors();
-        let mut rng = rand::thread_rng();
-        let mut tiles = Vec::with_capacity(size.columns as usize);
-        for column in 0..size.columns {
-            tiles.push(Vec::with_capacity(size.rows as usize));
-            for _ in 0..size.rows {
-                let color = colors.choose(&mut rng).copied();
-                tiles[column as usize].push(color);
+    // Carries on a saved game where it was left off
+    pub fn restore(&mut self, saved: &SavedGame) {
+        self.start_rated(saved.setup, saved.variant, saved.rating);
+        *self.game.borrow_mut() = saved.game.clone();
+        *self.started.borrow_mut() = Instant::now()
+            .checked_sub(Duration::from_secs(saved.elapsed_secs as u64))
+            .unwrap_or_else(Instant::now);
+        self.sender.send(Action::UpdatedScore(saved.game.score));
+        self.widget.redraw();
+    }
+
+    // Returns the one-player game in progress, if any, so that it can be
+    // carried on later
+    pub fn snapshot(&self) -> Option<SavedGame> {
+        if *self.mode.borrow() != Mode::Playing
+            || !self.players.borrow().is_empty()
+        {
+            return None;
+        }
+        let mut game = self.game.borrow().clone();
+        game.close_up(); // In case it is in the middle of a move
+        let now = self.paused.borrow().unwrap_or_else(Instant::now);
+        let elapsed = now.saturating_duration_since(*self.started.borrow());
+        Some(SavedGame {
+            variant: *self.variant.borrow(),
+            setup: *self.setup.borrow(),
+            game,
+            elapsed_secs: elapsed.as_secs() as u32,
+            time_left: 0.0, // the application keeps the clock
+            rating: *self.rating.borrow(),
+        })
+    }
+
+    // Clears the board until the next game starts, e.g., while a solvable
+    // board is being looked for; anything still to come from the game
+    // that was being played is ignored
+    pub fn wait(&mut self) {
+        *self.mode.borrow_mut() = Mode::Waiting;
+        *self.paused.borrow_mut() = None;
+        *self.selected.borrow_mut() = None;
+        *self.hovered.borrow_mut() = None;
+        *self.slide.borrow_mut() = None;
+        *self.vanish.borrow_mut() = None;
+        self.cancel_timer();
+        self.adjoining.borrow_mut().clear();
+        let game_id = self.game_id.borrow().wrapping_add(1);
+        *self.game_id.borrow_mut() = game_id;
+        self.widget.redraw();
+    }
+
+    // Ends the game early, e.g., when someone else has won a race
+    pub fn stop(&mut self) {
+        if *self.mode.borrow() == Mode::Playing {
+            *self.mode.borrow_mut() = Mode::GameOver;
+            *self.paused.borrow_mut() = None;
+            self.dim_remaining_tiles();
+            self.widget.redraw();
+        }
+    }
+
+    pub fn variant(&self) -> Variant {
+        *self.variant.borrow()
+    }
+
+    pub fn players(&self) -> Players {
+        self.players.borrow().clone()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        *self.mode.borrow() == Mode::Playing
+    }
+
+    pub fn has_won(&self) -> bool {
+        matches!(*self.mode.borrow(), Mode::UserWon(_))
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.game.borrow().remaining()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.borrow().is_some()
+    }
+
+    // Hides the tiles and stops the move in progress (if any) where it is;
+    // returns false if there's no game to pause
+    pub fn pause(&mut self) -> bool {
+        if *self.mode.borrow() != Mode::Playing || self.is_paused() {
+            return false;
+        }
+        let now = Instant::now();
+        *self.paused.borrow_mut() = Some(now);
+        if let Some(timer) = &mut *self.timer.borrow_mut() {
+            if fltk::app::has_timeout3(timer.handle) {
+                fltk::app::remove_timeout3(timer.handle);
+                timer.left = Some(timer.due.saturating_duration_since(now));
             }
         }
-        tiles
+        *self.hovered.borrow_mut() = None;
+        self.widget.redraw();
+        true
     }
 
-    fn get_colors(&self) -> Vec<Color> {
-        let mut rng = rand::thread_rng();
-        let all_colors = COLORS.get().read().unwrap();
-        let mut colors: Vec<Color>;
-        'color: loop {
-            colors = all_colors
-                .choose_multiple(
-                    &mut rng,
-                    (*self.maxcolors.borrow()).into(),
-                )
-                .cloned()
-                .collect();
-            for (a, b) in [
-                (BROWN, OLIVE),
-                (TEAL, GREEN),
-                (LIME, MINT),
-                (APRICOT, PINK),
-                (LAVENDER, PINK),
-                (BEIGE, WHITE),
-            ]
-            .iter()
-            {
-                if colors.contains(a) && colors.contains(b) {
-                    continue 'color; // disallow hard to see color pairs
+    // Carries on exactly where it was paused
+    pub fn resume(&mut self) {
+        let Some(paused) = self.paused.borrow_mut().take() else {
+            return;
+        };
+        let paused = paused.elapsed();
+        if let Some(slide) = &mut *self.slide.borrow_mut() {
+            slide.postpone(paused);
+        }
+        if let Some(vanish) = &mut *self.vanish.borrow_mut() {
+            vanish.postpone(paused);
+        }
+        *self.started.borrow_mut() += paused; // pauses don't count
+        let left = self.timer.borrow_mut().as_mut().and_then(|timer| {
+            timer.left.take().map(|left| (left, timer.action))
+        });
+        if let Some((left, action)) = left {
+            self.schedule(left.as_secs_f64(), action);
+        }
+        self.widget.redraw();
+    }
+
+    // Sends the action after the given time unless paused first
+    fn schedule(&self, secs: f64, action: Action) {
+        #[allow(clippy::clone_on_copy)] // The clone is needed
+        let sender = self.sender.clone();
+        let handle = fltk::app::add_timeout3(secs, move |_| {
+            sender.send(action);
+        });
+        *self.timer.borrow_mut() = Some(Timer {
+            handle,
+            action,
+            due: Instant::now() + Duration::from_secs_f64(secs),
+            left: None,
+        });
+    }
+
+    fn cancel_timer(&self) {
+        if let Some(timer) = self.timer.borrow_mut().take() {
+            if fltk::app::has_timeout3(timer.handle) {
+                fltk::app::remove_timeout3(timer.handle);
+            }
+        }
+    }
+
+    // Tile sets are only loaded when changed since loading and scaling
+    // images is slow
+    fn update_tileset(&mut self, name: &str) {
+        let current = self
+            .tileset
+            .borrow()
+            .as_ref()
+            .map_or(tileset::NONE.to_string(), |tileset| {
+                tileset.name.clone()
+            });
+        if current != name {
+            match tileset::load(name) {
+                Ok(tileset) => *self.tileset.borrow_mut() = tileset,
+                Err(err) => {
+                    *self.tileset.borrow_mut() = None;
+                    util::warning(&trf(
+                        "failed to load tile set: {0}",
+                        &[&err],
+                    ));
                 }
             }
-            break;
         }
+    }
+
+    // The colors are always the most distinguishable ones for the palette
+    // and count, shuffled so that they aren't always for the same tiles
+    fn get_colors(&self, palette: Palette, rng: &mut StdRng) -> Vec<Color> {
+        let mut colors =
+            palette::choose(palette, self.game.borrow().maxcolors.into());
+        colors.shuffle(rng);
         colors
     }
 
-    pub fn on_arrow(&mut self, arrow: Arrow) {
-        if *self.mode.borrow() != Mode::Playing {
+    pub fn on_navigate(&mut self, navigation: Navigation) {
+        if *self.mode.borrow() != Mode::Playing || self.is_paused() {
             return;
         }
-        let size = *self.size.borrow();
-        if self.selected.borrow().is_none() {
-            *self.selected.borrow_mut() =
-                Some(Pos::new(size.columns / 2, size.rows / 2));
-        } else {
-            let mut pos = self.selected.borrow().unwrap();
-            match arrow {
-                Arrow::Left => pos.x -= 1,
-                Arrow::Right => pos.x += 1,
-                Arrow::Up => pos.y -= 1,
-                Arrow::Down => pos.y += 1,
-            }
-            let tiles = &*self.tiles.borrow();
-            let x = pos.x;
-            let y = pos.y;
-            if 0 <= x
-                && x < size.columns
-                && 0 <= y
-                && y < size.rows
-                && tiles[x as usize][y as usize].is_some()
-            {
-                *self.selected.borrow_mut() = Some(pos);
-            }
+        let selected = *self.selected.borrow();
+        let pos = self.game.borrow().navigate(
+            selected,
+            navigation,
+            *self.wrap.borrow(),
+        );
+        if pos.is_some() {
+            *self.selected.borrow_mut() = pos;
         }
+        *self.hovered.borrow_mut() = *self.selected.borrow();
         self.widget.redraw();
     }
 
+    pub fn on_hover(&mut self) {
+        let (tile_width, tile_height) = self.get_tile_size();
+        if tile_width < 1 || tile_height < 1 || self.is_paused() {
+            return;
+        }
+        let x = fltk::app::event_x() - self.widget.x();
+        let y = fltk::app::event_y() - self.widget.y();
+        let pos =
+            Pos::new(x.div_euclid(tile_width), y.div_euclid(tile_height));
+        let pos = self.game.borrow().tile(pos).map(|_| pos);
+        if *self.hovered.borrow() != pos {
+            *self.hovered.borrow_mut() = pos;
+            self.widget.redraw();
+        }
+    }
+
+    // Returns what clicking the hovered tile would do, if anything
+    pub fn preview_status(&self) -> Option<String> {
+        if !is_idle(&self.mode, &self.adjoining, &self.slide, &self.vanish)
+            || self.players.borrow().is_computers_turn()
+            || self.is_paused()
+        {
+            return None;
+        }
+        let pos = (*self.hovered.borrow())?;
+        let game = self.game.borrow();
+        let tile = game.tile(pos)?;
+        let removal = game.removal(pos);
+        Some(if !removal.is_empty() {
+            trf(
+                "Remove {0} tiles for {1} points",
+                &[
+                    &i18n::number(removal.len()),
+                    &i18n::number(game.score_for(&removal)),
+                ],
+            )
+        } else if tile.kind == Kind::Stone {
+            tr("Not legal: stones can only be removed by bombs").to_string()
+        } else {
+            tr("Not legal: no adjoining tile of the same color").to_string()
+        })
+    }
+
     pub fn on_press_tile(&mut self) {
-        if *self.mode.borrow() != Mode::Playing {
+        if *self.mode.borrow() != Mode::Playing
+            || self.players.borrow().is_computers_turn()
+            || self.is_paused()
+        {
             return;
         }
         let pos = *self.selected.borrow();
@@ -155,7 +405,9 @@ impl Board {
     }
 
     pub fn on_click_tile(&mut self) {
-        if *self.mode.borrow() != Mode::Playing {
+        if *self.mode.borrow() != Mode::Playing
+            || self.players.borrow().is_computers_turn()
+        {
             return;
         }
         let (tile_width, tile_height) = self.get_tile_size();
@@ -166,7 +418,7 @@ impl Board {
     }
 
     fn get_tile_size(&self) -> (i32, i32) {
-        let size = *self.size.borrow();
+        let size = self.game.borrow().size;
         board_util::get_tile_size(
             size.columns,
             size.rows,
@@ -175,320 +427,283 @@ impl Board {
         )
     }
 
-    fn delete_tile(&mut self, pos: Pos) {
-        let color = self.tiles.borrow()[pos.x as usize][pos.y as usize];
-        if color.is_none() {
-            return;
+    // Returns true if the rating is for the current game
+    pub fn on_rated(&mut self, rating: u8, game_id: u32) -> bool {
+        let current = game_id == *self.game_id.borrow();
+        if current {
+            *self.rating.borrow_mut() = rating;
         }
-        let color = color.unwrap();
-        if !self.is_legal(&pos, color) {
-            return;
-        }
-        self.dim_adjoining(&pos, &color);
+        current
     }
 
-    fn is_legal(&self, pos: &Pos, color: Color) -> bool {
-        // A legal click is on a colored tile that is adjacent to another
-        // tile of the same color.
-        let tiles = &*self.tiles.borrow();
-        let size = *self.size.borrow();
-        let x = pos.x as usize;
-        let y = pos.y as usize;
-        let color = Some(color);
-        (x > 0 && tiles[x - 1][y] == color)
-            || (x + 1 < size.columns as usize && tiles[x + 1][y] == color)
-            || (y > 0 && tiles[x][y - 1] == color)
-            || (y + 1 < size.rows as usize && tiles[x][y + 1] == color)
+    pub fn on_computer_move(&mut self, pos: Pos, game_id: u32) {
+        if *self.mode.borrow() == Mode::Playing
+            && game_id == *self.game_id.borrow()
+            && self.players.borrow().is_computers_turn()
+        {
+            self.delete_tile(pos);
+        }
     }
 
-    fn dim_adjoining(&mut self, pos: &Pos, color: &Color) {
-        self.adjoining.borrow_mut().clear();
-        self.populate_adjoining(*pos, *color);
-        let count = self.adjoining.borrow().len() as u16;
-        self.update_score(count);
-        let tiles = &mut *self.tiles.borrow_mut();
-        for &adjoining_pos in self.adjoining.borrow().iter() {
-            let x = adjoining_pos.x as usize;
-            let y = adjoining_pos.y as usize;
-            tiles[x][y] = Some(tiles[x][y].unwrap().darker());
-        }
-        fltk::app::sleep(TINY_DELAY);
-        self.widget.redraw();
+    // The computer thinks in its own thread so as not to block the UI and
+    // takes at least the delay to "think" so that the player can follow
+    fn play_computer(&mut self) {
+        let game = self.game.borrow().clone();
+        let strength = *self.strength.borrow();
+        let delay = Duration::from_millis(*self.delay_ms.borrow() as u64);
+        let game_id = *self.game_id.borrow();
         #[allow(clippy::clone_on_copy)] // The clone is needed
         let sender = self.sender.clone();
-        fltk::app::add_timeout3(
-            *self.delay_ms.borrow() as f64 / 1000.0,
-            move |_| {
-                sender.send(Action::DeleteAdjoining);
-            },
-        );
-    }
-
-    fn update_score(&mut self, count: u16) {
-        let size = *self.size.borrow();
-        *self.score.borrow_mut() += (((size.columns * size.rows) as f64)
-            .sqrt() as u16)
-            + count.pow(*self.maxcolors.borrow() as u32 - 2);
+        thread::spawn(move || {
+            let started = Instant::now();
+            let pos = strength.bot().strategy(rand::random()).choose(&game);
+            if let Some(remaining) = delay.checked_sub(started.elapsed()) {
+                thread::sleep(remaining);
+            }
+            if let Some(pos) = pos {
+                sender.send(Action::ComputerMove(pos, game_id));
+            }
+        });
     }
 
-    fn populate_adjoining(&self, pos: Pos, color: Color) {
-        let size = *self.size.borrow();
-        let x = pos.x;
-        let y = pos.y;
-        if !(0 <= x && x < size.columns && 0 <= y && y < size.rows) {
-            return; // Fallen off an edge
+    fn delete_tile(&mut self, pos: Pos) {
+        if let Some(vanish) = &mut *self.vanish.borrow_mut() {
+            vanish.skip(); // A click or key press skips the removal
+            return;
         }
-        let tiles = &*self.tiles.borrow();
-        if self.adjoining.borrow().contains(&pos)
-            || tiles[x as usize][y as usize] != Some(color)
+        if !self.adjoining.borrow().is_empty()
+            || self.slide.borrow().is_some()
         {
-            return; // Color doesn't match or already done
+            return; // The last move hasn't finished
         }
-        self.adjoining.borrow_mut().insert(pos);
-        self.populate_adjoining(Pos::new(x - 1, y), color);
-        self.populate_adjoining(Pos::new(x + 1, y), color);
-        self.populate_adjoining(Pos::new(x, y - 1), color);
-        self.populate_adjoining(Pos::new(x, y + 1), color);
+        let removal = self.game.borrow().removal(pos);
+        if removal.is_empty() {
+            return; // Not a legal click
+        }
+        self.dim_adjoining(removal);
     }
 
-    pub fn delete_adjoining(&mut self) {
-        let tiles = &mut *self.tiles.borrow_mut();
-        for &pos in self.adjoining.borrow().iter() {
-            tiles[pos.x as usize][pos.y as usize] = None
-        }
-        self.adjoining.borrow_mut().clear();
+    fn dim_adjoining(&mut self, removal: PosSet) {
+        *self.adjoining.borrow_mut() = removal;
         fltk::app::sleep(TINY_DELAY);
         self.widget.redraw();
-        #[allow(clippy::clone_on_copy)] // The clone is needed
-        let sender = self.sender.clone();
-        fltk::app::add_timeout3(
+        self.schedule(
             *self.delay_ms.borrow() as f64 / 1000.0,
-            move |_| {
-                sender.send(Action::CloseUp);
-            },
+            Action::DeleteAdjoining,
         );
     }
 
-    pub fn close_up(&mut self) {
-        self.move_tiles();
-        let mut new_selected: Option<Pos> = None;
-        if let Some(selected) = *self.selected.borrow() {
-            let x = selected.x as usize;
-            let y = selected.y as usize;
-            let tiles = &*self.tiles.borrow();
-            if tiles[x][y].is_none() {
-                let size = *self.size.borrow();
-                new_selected =
-                    Some(Pos::new(size.rows / 2, size.columns / 2));
-            }
+    pub fn delete_adjoining(&mut self) {
+        if *self.mode.borrow() != Mode::Playing {
+            return; // The game ended, e.g., time ran out, while dimmed
         }
-        if new_selected.is_some() {
-            *self.selected.borrow_mut() = new_selected;
+        let tiles: Vec<(Pos, Tile)> = {
+            let game = self.game.borrow();
+            self.adjoining
+                .borrow()
+                .iter()
+                .filter_map(|&pos| game.tile(pos).map(|tile| (pos, tile)))
+                .collect()
+        };
+        let points =
+            self.game.borrow_mut().remove(&self.adjoining.borrow());
+        if !self.players.borrow().is_empty() {
+            self.players.borrow_mut().credit(points);
+        }
+        let count = self.adjoining.borrow().len();
+        *self.removed.borrow_mut() = count;
+        self.sender.send(Action::Removed(count as u16));
+        self.adjoining.borrow_mut().clear();
+        if *self.reduced_motion.borrow() {
+            fltk::app::sleep(TINY_DELAY);
+            self.widget.redraw();
+            self.schedule(
+                *self.delay_ms.borrow() as f64 / 1000.0,
+                Action::CloseUp,
+            );
+        } else {
+            *self.vanish.borrow_mut() = Some(Vanish::new(tiles, points));
+            self.animate();
         }
-        self.check_game_over();
     }
 
-    fn move_tiles(&mut self) {
-        let tiles = Rc::clone(&self.tiles);
-        let size = *self.size.borrow();
-        let mut moved = true;
-        let mut already_moved = PosForPos::new();
-        while moved {
-            moved = false;
-            for x in board_util::ripple(size.columns as usize) {
-                for y in board_util::ripple(size.rows as usize) {
-                    #[allow(clippy::collapsible_if)] // Clippy is wrong
-                    if tiles.borrow()[x][y].is_some() {
-                        if self.move_if_possible(
-                            Pos::new(x as i32, y as i32),
-                            &mut already_moved,
-                        ) {
-                            moved = true;
-                            break;
-                        }
-                    }
-                }
-            }
+    pub fn close_up(&mut self) {
+        if *self.mode.borrow() != Mode::Playing {
+            return;
+        }
+        let paths = self.game.borrow_mut().close_up();
+        if paths.is_empty() || *self.reduced_motion.borrow() {
+            self.settle();
+        } else {
+            *self.slide.borrow_mut() = Some(Slide::new(paths));
+            self.animate();
         }
     }
 
-    fn move_if_possible(
-        &mut self,
-        pos: Pos,
-        already_moved: &mut PosForPos,
-    ) -> bool {
-        let empties = self.get_empty_neighbours(pos);
-        if !empties.is_empty() {
-            let (do_move, new_pos) = self.nearest_to_middle(pos, &empties);
-            if let Some(value) = already_moved.get(&new_pos) {
-                if value == &pos {
-                    return false; // avoid endless loop back and forth
-                }
+    // Draws the next frame of the tiles vanishing or sliding into place
+    pub fn animate(&mut self) {
+        let vanished = self.vanish.borrow().as_ref().map(Vanish::is_done);
+        let slid = self.slide.borrow().as_ref().map(Slide::is_done);
+        match (vanished, slid) {
+            (Some(true), _) => {
+                *self.vanish.borrow_mut() = None;
+                self.widget.redraw();
+                self.close_up();
             }
-            if do_move {
-                let tiles = &mut *self.tiles.borrow_mut();
-                let x = pos.x as usize;
-                let y = pos.y as usize;
-                tiles[new_pos.x as usize][new_pos.y as usize] = tiles[x][y];
-                tiles[x][y] = None;
-                already_moved.insert(pos, new_pos);
-                let delay =
-                    0.2_f64.max(*self.delay_ms.borrow() as f64 / 7000.0);
-                #[allow(clippy::clone_on_copy)] // The clone is needed
-                let sender = self.sender.clone();
-                fltk::app::add_timeout3(delay, move |_| {
-                    sender.send(Action::Redraw);
-                });
-                return true;
+            (Some(false), _) | (None, Some(false)) => {
+                self.widget.redraw();
+                self.schedule(animation::FRAME_SECS, Action::Animate);
+            }
+            (None, Some(true)) => {
+                *self.slide.borrow_mut() = None;
+                if *self.mode.borrow() == Mode::Playing {
+                    self.settle();
+                } else {
+                    self.widget.redraw(); // e.g., time ran out
+                }
             }
+            (None, None) => (), // e.g., a new game was started
         }
-        false
-    }
-
-    pub fn redraw(&mut self) {
-        self.widget.redraw();
     }
 
-    fn get_empty_neighbours(&mut self, pos: Pos) -> PosSet {
-        let size = *self.size.borrow();
-        let x = pos.x;
-        let y = pos.y;
-        let mut neighbours = PosSet::new();
-        let tiles = Rc::clone(&self.tiles);
-        for new_pos in [
-            Pos::new(x - 1, y),
-            Pos::new(x + 1, y),
-            Pos::new(x, y - 1),
-            Pos::new(x, y + 1),
-        ]
-        .iter()
-        {
-            if 0 <= new_pos.x
-                && new_pos.x < size.columns
-                && 0 <= new_pos.y
-                && new_pos.y < size.rows
-                && tiles.borrow()[new_pos.x as usize][new_pos.y as usize]
-                    .is_none()
-            {
-                neighbours.insert(*new_pos);
-            }
+    // Finishes the move once the tiles are in place
+    fn settle(&mut self) {
+        if *self.variant.borrow() == Variant::Endless {
+            self.refill();
         }
-        neighbours
-    }
-
-    fn nearest_to_middle(&self, pos: Pos, empties: &PosSet) -> (bool, Pos) {
-        let x = pos.x;
-        let y = pos.y;
-        let tiles = Rc::clone(&self.tiles);
-        let color = tiles.borrow()[x as usize][y as usize].unwrap();
-        let size = *self.size.borrow();
-        let mid_x = size.columns / 2;
-        let mid_y = size.rows / 2;
-        let old_radius = ((mid_x - x) as f64).hypot((mid_y - y) as f64);
-        let mut shortest_radius = f64::NAN;
-        let mut radius_pos = Pos::default(); // invalid
-        for new_pos in empties.iter() {
-            let nx = new_pos.x;
-            let ny = new_pos.y;
-            if self.is_square(new_pos) {
-                let mut new_radius =
-                    ((mid_x - nx) as f64).hypot((mid_y - ny) as f64);
-                if self.is_legal(new_pos, color) {
-                    // Make same colors slightly attractive
-                    new_radius -= 0.1;
-                }
-                if !radius_pos.is_valid() || shortest_radius > new_radius {
-                    shortest_radius = new_radius;
-                    radius_pos = *new_pos;
-                }
-            }
-        }
-        if !shortest_radius.is_nan() && old_radius > shortest_radius {
-            (true, radius_pos)
-        } else {
-            (false, pos)
+        // If the focused tile went the focus goes to the nearest one
+        let selected = *self.selected.borrow();
+        if let Some(selected) = selected {
+            let nearest = self.game.borrow().nearest_tile(selected);
+            *self.selected.borrow_mut() = nearest;
         }
+        self.check_game_over();
     }
 
-    fn is_square(&self, pos: &Pos) -> bool {
-        let x = pos.x;
-        let y = pos.y;
-        let size = *self.size.borrow();
-        let tiles = &*self.tiles.borrow();
-        (x > 0 && tiles[x as usize - 1][y as usize].is_some())
-            || (x + 1 < size.columns
-                && tiles[x as usize + 1][y as usize].is_some())
-            || (y > 0 && tiles[x as usize][y as usize - 1].is_some())
-            || (y + 1 < size.rows
-                && tiles[x as usize][y as usize + 1].is_some())
+    // New tiles replace half of those removed, and more are added while
+    // there is room but no legal move
+    fn refill(&mut self) {
+        let mut rng = rand::thread_rng();
+        let game = &mut *self.game.borrow_mut();
+        let count = self.removed.borrow().div_ceil(2);
+        game.refill(count, &mut rng);
+        while !game.has_legal_move() && !game.is_full() {
+            if game.refill(1, &mut rng) == 0 {
+                break; // There's no room at the edges
+            }
+        }
     }
 
     pub fn check_game_over(&mut self) {
-        let highscore = {
-            let config = CONFIG.get().read().unwrap();
-            config.board_highscore
+        let variant = *self.variant.borrow();
+        let score = self.game.borrow().score;
+        let (user_won, can_move) = if variant == Variant::Endless {
+            (false, self.game.borrow().has_legal_move())
+        } else {
+            self.game.borrow().check_tiles()
         };
-        let score = *self.score.borrow();
-        let (user_won, can_move) = self.check_tiles();
-        *self.mode.borrow_mut() = if user_won {
-            let is_new_highscore = score > highscore;
-            if is_new_highscore {
-                let mut config = CONFIG.get().write().unwrap();
-                config.board_highscore = score;
-            }
-            Mode::UserWon(is_new_highscore)
-        } else if can_move {
+        *self.mode.borrow_mut() = if !user_won && can_move {
             Mode::Playing
+        } else if !self.players.borrow().is_empty() {
+            if !user_won {
+                self.dim_remaining_tiles();
+            }
+            Mode::PlayerWon(self.players.borrow().winner())
+        } else if user_won {
+            self.add_statistics(true);
+            Mode::UserWon(self.add_highscore(score))
         } else {
+            self.add_statistics(false);
+            match variant {
+                Variant::Classic | Variant::HotSeat | Variant::Versus => (),
+                Variant::TimeAttack => {
+                    self.add_highscore(score);
+                }
+                Variant::Endless => {
+                    self.add_highscore(score);
+                    self.add_endless_records();
+                }
+            }
             self.dim_remaining_tiles();
             Mode::GameOver
         };
         if *self.mode.borrow() != Mode::Playing {
             self.sender.send(Action::GameOver);
+        } else if self.players.borrow().is_computers_turn() {
+            self.play_computer();
         }
         self.sender.send(Action::UpdatedScore(score));
         self.widget.redraw();
     }
 
-    fn check_tiles(&mut self) -> (bool, bool) {
-        let mut count_for_color = HashMap::<Color, u32>::new();
-        let mut user_won = true;
-        let mut can_move = false;
-        let size = *self.size.borrow();
-        let tiles = &*self.tiles.borrow();
-        for column in 0..size.columns {
-            for row in 0..size.rows {
-                if let Some(color) = tiles[column as usize][row as usize] {
-                    if let Some(count) = count_for_color.get_mut(&color) {
-                        *count += 1;
-                    } else {
-                        count_for_color.insert(color, 1);
-                    }
-                    user_won = false;
-                    if self.is_legal(&Pos::new(column, row), color) {
-                        can_move = true;
-                    }
-                }
-            }
-        }
-        for (_, count) in count_for_color.iter() {
-            if *count == 1 {
-                can_move = false;
-                break;
-            }
+    pub fn time_up(&mut self) {
+        if *self.mode.borrow() != Mode::Playing {
+            return;
         }
-        (user_won, can_move)
+        let score = self.game.borrow().score;
+        self.add_statistics(false);
+        *self.mode.borrow_mut() = Mode::TimeUp(self.add_highscore(score));
+        self.dim_remaining_tiles();
+        self.sender.send(Action::GameOver);
+        self.sender.send(Action::UpdatedScore(score));
+        self.widget.redraw();
+    }
+
+    // Returns the high score category of the game's variant and preset
+    pub fn category(&self) -> String {
+        preset::category(
+            *self.variant.borrow(),
+            self.preset(),
+            self.setup.borrow().generator,
+            self.setup.borrow().solvable,
+        )
+    }
+
+    pub fn setup(&self) -> Setup {
+        *self.setup.borrow()
+    }
+
+    // Returns the board's difficulty from 1 to rating::STARS_MAX or 0 if
+    // it hasn't been rated yet
+    pub fn rating(&self) -> u8 {
+        *self.rating.borrow()
+    }
+
+    fn preset(&self) -> Preset {
+        Preset::of_setup(&self.setup.borrow())
+    }
+
+    // Returns true if the score is a new high score for this category
+    fn add_highscore(&self, score: u16) -> bool {
+        let category = self.category();
+        let mut config = CONFIG.get().write().unwrap();
+        let name = config.profile.clone();
+        config.highscores.add(&category, score, self.rating(), &name)
+    }
+
+    fn add_statistics(&self, won: bool) {
+        let preset = self.preset();
+        let game = self.game.borrow();
+        let mut config = CONFIG.get().write().unwrap();
+        config.statistics.update(preset, won, game.cleared, game.score);
+    }
+
+    fn add_endless_records(&self) {
+        let cleared = self.game.borrow().cleared;
+        let secs = self.started.borrow().elapsed().as_secs() as u32;
+        let mut config = CONFIG.get().write().unwrap();
+        config.endless_records.update(cleared, secs);
     }
 
     fn dim_remaining_tiles(&mut self) {
-        let size = *self.size.borrow();
-        let tiles = &mut *self.tiles.borrow_mut();
-        for column in 0..size.columns {
-            for row in 0..size.rows {
-                let x = column as usize;
-                let y = row as usize;
-                if let Some(color) = tiles[x][y] {
-                    tiles[x][y] = Some(color.darker());
+        let game = self.game.borrow();
+        let adjoining = &mut *self.adjoining.borrow_mut();
+        adjoining.clear();
+        for column in 0..game.size.columns {
+            for row in 0..game.size.rows {
+                let pos = Pos::new(column, row);
+                if game.tile(pos).is_some() {
+                    adjoining.insert(pos);
                 }
             }
         }
@@ -509,10 +724,43 @@ impl DerefMut for Board {
     }
 }
 
+// A pending step of a move that when paused is cancelled and when resumed
+// is rescheduled for whatever time it had left
+struct Timer {
+    handle: fltk::app::TimeoutHandle,
+    action: Action,
+    due: Instant,
+    left: Option<Duration>, // set while paused
+}
+
+// Returns a setup for a new board using the configured options; if only
+// solvable boards are wanted the caller must look for a solvable seed (see
+// solver::find_solvable_seed()) since that can take a while
+pub fn new_setup() -> Setup {
+    let config = CONFIG.get().read().unwrap();
+    Setup {
+        seed: rand::random(),
+        size: Size::new(
+            config.board_columns as i32,
+            config.board_rows as i32,
+        ),
+        maxcolors: config.board_maxcolors,
+        specials: Specials {
+            bombs: config.board_bombs,
+            wildcards: config.board_wildcards,
+            stones: config.board_stones,
+            multipliers: config.board_multipliers,
+        },
+        solvable: false,
+        generator: config.board_generator,
+    }
+}
+
 fn add_event_handler(board: &mut Board, sender: fltk::app::Sender<Action>) {
     let mode = Rc::clone(&board.mode);
+    let paused = Rc::clone(&board.paused);
     board.widget.handle(move |_, event| {
-        if *mode.borrow() != Mode::Playing {
+        if *mode.borrow() != Mode::Playing || paused.borrow().is_some() {
             return false;
         }
         match event {
@@ -520,37 +768,151 @@ fn add_event_handler(board: &mut Board, sender: fltk::app::Sender<Action>) {
                 sender.send(Action::ClickTile);
                 true
             }
+            // Must accept Enter to be sent Move events
+            fltk::enums::Event::Enter
+            | fltk::enums::Event::Move
+            | fltk::enums::Event::Leave => {
+                sender.send(Action::Hover);
+                true
+            }
             _ => false,
         }
     });
 }
 
+// Returns true if the board is waiting for a move
+fn is_idle(
+    mode: &RefCell<Mode>,
+    adjoining: &RefCell<PosSet>,
+    slide: &RefCell<Option<Slide>>,
+    vanish: &RefCell<Option<Vanish>>,
+) -> bool {
+    *mode.borrow() == Mode::Playing
+        && adjoining.borrow().is_empty()
+        && slide.borrow().is_none()
+        && vanish.borrow().is_none()
+}
+
 fn add_draw_handler(board: &mut Board) {
     let mode = Rc::clone(&board.mode);
     let selected = Rc::clone(&board.selected);
-    let tiles = Rc::clone(&board.tiles);
-    let size = Rc::clone(&board.size);
+    let hovered = Rc::clone(&board.hovered);
+    let game = Rc::clone(&board.game);
+    let colors = Rc::clone(&board.colors);
+    let symbols = Rc::clone(&board.symbols);
+    let tileset = Rc::clone(&board.tileset);
+    let adjoining = Rc::clone(&board.adjoining);
+    let slide = Rc::clone(&board.slide);
+    let vanish = Rc::clone(&board.vanish);
+    let players = Rc::clone(&board.players);
+    let paused = Rc::clone(&board.paused);
     board.widget.draw(move |widget| {
         let width = widget.width();
         let height = widget.height();
         let x1 = widget.x();
         let y1 = widget.y();
         fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
-        board_util::draw_background(x1, y1, width, height);
-        board_util::draw_tiles(
-            x1,
-            y1,
-            width,
-            height,
-            *size.borrow(),
-            &tiles.borrow(),
-            *selected.borrow(),
-        );
-        match *mode.borrow() {
-            Mode::Playing => (),
-            Mode::GameOver => draw_game_over(x1, y1, width, height),
-            Mode::UserWon(is_new_highscore) => {
-                draw_user_won(x1, y1, width, height, is_new_highscore)
+        let theme = THEME.get().read().unwrap();
+        board_util::draw_background(x1, y1, width, height, &theme);
+        if paused.borrow().is_some() {
+            // The tiles are hidden so that they can't be studied for free
+            draw_overlay(
+                x1,
+                y1,
+                width,
+                height,
+                tr("Paused"),
+                theme.game_over,
+                &theme,
+            );
+        } else if *mode.borrow() != Mode::Waiting {
+            let moving = slide
+                .borrow()
+                .as_ref()
+                .map(Slide::positions)
+                .unwrap_or_default();
+            board_util::draw_tiles(
+                x1,
+                y1,
+                width,
+                height,
+                &game.borrow(),
+                &colors.borrow(),
+                *selected.borrow(),
+                &adjoining.borrow(),
+                &moving,
+                *symbols.borrow(),
+                tileset.borrow().as_ref(),
+                &theme,
+            );
+            if let Some(pos) = *hovered.borrow() {
+                if is_idle(&mode, &adjoining, &slide, &vanish)
+                    && !players.borrow().is_computers_turn()
+                    && game.borrow().tile(pos).is_some()
+                {
+                    let game = game.borrow();
+                    let removal = game.removal(pos);
+                    let is_legal = !removal.is_empty();
+                    board_util::draw_outline(
+                        x1,
+                        y1,
+                        width,
+                        height,
+                        game.size,
+                        &if is_legal {
+                            removal
+                        } else {
+                            PosSet::from([pos])
+                        },
+                        is_legal,
+                        &theme,
+                    );
+                }
+            }
+            if let Some(vanish) = &*vanish.borrow() {
+                board_util::draw_vanish(
+                    x1,
+                    y1,
+                    width,
+                    height,
+                    game.borrow().size,
+                    vanish,
+                    &colors.borrow(),
+                    &theme,
+                );
+            }
+            match *mode.borrow() {
+                Mode::Playing | Mode::Waiting => (),
+                Mode::GameOver => {
+                    draw_game_over(x1, y1, width, height, &theme)
+                }
+                Mode::UserWon(is_new_highscore) => draw_user_won(
+                    x1,
+                    y1,
+                    width,
+                    height,
+                    is_new_highscore,
+                    &theme,
+                ),
+                Mode::TimeUp(is_new_highscore) => draw_time_up(
+                    x1,
+                    y1,
+                    width,
+                    height,
+                    is_new_highscore,
+                    &theme,
+                ),
+                Mode::PlayerWon(winner) => {
+                    let message = match winner {
+                        Some(i) => {
+                            trf("{0}\nWins!", &[&players.borrow().names[i]])
+                        }
+                        None => tr("It's a Draw!").to_string(),
+                    };
+                    draw_overlay(
+                        x1, y1, width, height, &message, theme.won, &theme,
+                    );
+                }
             }
         }
         // *MUST* restore the line style after custom drawing
@@ -558,11 +920,17 @@ fn add_draw_handler(board: &mut Board) {
     });
 }
 
-fn draw_game_over(x1: i32, y1: i32, width: i32, height: i32) {
-    fltk::draw::set_font(fltk::enums::Font::TimesBoldItalic, 48);
-    fltk::draw::set_draw_color(Color::White);
+fn draw_game_over(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    theme: &Theme,
+) {
+    fltk::draw::set_font(theme.overlay_font, theme.overlay_size);
+    fltk::draw::set_draw_color(theme.overlay_shadow);
     let height = (height * 3) / 2;
-    let message = "Game Over!";
+    let message = tr("Game Over!");
     fltk::draw::draw_text2(
         message,
         x1,
@@ -571,7 +939,7 @@ fn draw_game_over(x1: i32, y1: i32, width: i32, height: i32) {
         height,
         fltk::enums::Align::Center,
     );
-    fltk::draw::set_draw_color(Color::Green);
+    fltk::draw::set_draw_color(theme.game_over);
     fltk::draw::draw_text2(
         message,
         x1 - 2,
@@ -589,14 +957,62 @@ fn draw_user_won(
     width: i32,
     height: i32,
     is_new_highscore: bool,
+    theme: &Theme,
+) {
+    let message = if is_new_highscore {
+        tr("You Won!\n\nNew\nHighscore")
+    } else {
+        tr("You Won!")
+    };
+    fltk::draw::set_font(theme.overlay_font, theme.overlay_size);
+    fltk::draw::set_draw_color(theme.overlay_shadow);
+    fltk::draw::draw_text2(
+        message,
+        x1,
+        y1,
+        width,
+        height,
+        fltk::enums::Align::Center,
+    );
+    fltk::draw::set_draw_color(theme.won);
+    fltk::draw::draw_text2(
+        message,
+        x1 - 2,
+        y1 - 2,
+        width,
+        height,
+        fltk::enums::Align::Center,
+    );
+    fltk::draw::set_draw_color(Color::Black);
+}
+
+fn draw_time_up(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    is_new_highscore: bool,
+    theme: &Theme,
 ) {
     let message = if is_new_highscore {
-        "You Won!\n\nNew\nHighscore"
+        tr("Time's Up!\n\nNew\nHighscore")
     } else {
-        "You Won!"
+        tr("Time's Up!")
     };
-    fltk::draw::set_font(fltk::enums::Font::TimesBoldItalic, 48);
-    fltk::draw::set_draw_color(Color::White);
+    draw_overlay(x1, y1, width, height, message, theme.time_up, theme);
+}
+
+fn draw_overlay(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    message: &str,
+    color: Color,
+    theme: &Theme,
+) {
+    fltk::draw::set_font(theme.overlay_font, theme.overlay_size);
+    fltk::draw::set_draw_color(theme.overlay_shadow);
     fltk::draw::draw_text2(
         message,
         x1,
@@ -605,7 +1021,7 @@ fn draw_user_won(
         height,
         fltk::enums::Align::Center,
     );
-    fltk::draw::set_draw_color(Color::Red);
+    fltk::draw::set_draw_color(color);
     fltk::draw::draw_text2(
         message,
         x1 - 2,
@@ -1,30 +1,27 @@
 // Copyright © 2021-23 Mark Summerfield. All rights reserved.
 // License: GPLv3
 
+use crate::animation::Vanish;
+use crate::engine::{Game, Kind, PosSet, Size, MULTIPLIER};
+use crate::fixed::COLORS;
+use crate::fixed::{BLUE, GREEN, RED, YELLOW};
+use crate::i18n::{self, tr};
+use crate::palette;
+use crate::theme::Theme;
+use crate::tileset::TileSet;
 use crate::util::Pos;
 use fltk::enums::Color;
-
-pub const BACKGROUND_COLOR: Color = Color::BackGround;
-
-pub type Tiles = Vec<Vec<Option<Color>>>;
+use std::collections::HashMap;
+const SHAPES: u8 = 8; // symbols after these are letters
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Mode {
     Playing,
     GameOver,
-    UserWon(bool), // true if new highscore
-}
-
-#[derive(Copy, Clone, Debug, Default)]
-pub struct Size {
-    pub columns: i32,
-    pub rows: i32,
-}
-
-impl Size {
-    pub fn new(columns: i32, rows: i32) -> Self {
-        Self { columns, rows }
-    }
+    UserWon(bool),            // true if new highscore
+    TimeUp(bool),             // true if new highscore
+    PlayerWon(Option<usize>), // the winning player or None for a draw
+    Waiting,                  // for the next board to be ready
 }
 
 pub fn get_tile_size(
@@ -38,31 +35,97 @@ pub fn get_tile_size(
     (tile_width, tile_height)
 }
 
-pub fn draw_background(x1: i32, y1: i32, width: i32, height: i32) {
-    fltk::draw::set_draw_color(BACKGROUND_COLOR);
-    fltk::draw::draw_rect_fill(x1, y1, width, height, BACKGROUND_COLOR);
+pub fn draw_background(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    theme: &Theme,
+) {
+    fltk::draw::set_draw_color(theme.background);
+    fltk::draw::draw_rect_fill(x1, y1, width, height, theme.background);
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn draw_tiles(
     x1: i32,
     y1: i32,
     width: i32,
     height: i32,
-    size: Size,
-    tiles: &[Vec<Option<Color>>],
+    game: &Game,
+    colors: &[Color],
     selected: Option<Pos>,
+    dimmed: &PosSet,
+    moving: &HashMap<Pos, (f64, f64)>, // fractional (column, row)s
+    symbols: bool,
+    tileset: Option<&TileSet>,
+    theme: &Theme,
 ) {
+    let size = game.size;
     let (tile_width, tile_height) =
         get_tile_size(size.columns, size.rows, width, height);
     for column in 0..size.columns {
-        let x = x1 + (tile_width * column);
         for row in 0..size.rows {
-            let y = y1 + (tile_height * row);
-            if let Some(color) = tiles[column as usize][row as usize] {
-                draw_tile(x, y, tile_width, tile_height, color);
+            let pos = Pos::new(column, row);
+            // A moving tile is drawn on its way to its position
+            let (x, y) = match moving.get(&pos) {
+                Some(&(column, row)) => (
+                    x1 + (tile_width as f64 * column).round() as i32,
+                    y1 + (tile_height as f64 * row).round() as i32,
+                ),
+                None => (x1 + tile_width * column, y1 + tile_height * row),
+            };
+            if let Some(tile) = game.tile(pos) {
+                let is_dimmed = dimmed.contains(&pos);
+                let mut color = colors[tile.color as usize];
+                if is_dimmed {
+                    color = color.darker();
+                }
+                // Stones and wildcards have no color so are never images
+                let imaged = matches!(
+                    tile.kind,
+                    Kind::Plain | Kind::Bomb | Kind::Multiplier
+                ) && tileset.is_some_and(|tileset| {
+                    tileset.draw(
+                        tile.color,
+                        x,
+                        y,
+                        tile_width,
+                        tile_height,
+                        is_dimmed,
+                    )
+                });
+                draw_tile(
+                    x,
+                    y,
+                    tile_width,
+                    tile_height,
+                    color,
+                    tile.kind,
+                    !imaged,
+                    theme,
+                );
+                if symbols
+                    && !imaged
+                    && matches!(
+                        tile.kind,
+                        Kind::Plain | Kind::Bomb | Kind::Multiplier
+                    )
+                {
+                    // Specials show their symbol small in the corner
+                    draw_symbol(
+                        x,
+                        y,
+                        tile_width,
+                        tile_height,
+                        tile.color,
+                        color,
+                        tile.kind != Kind::Plain,
+                    );
+                }
                 if let Some(pos) = selected {
                     if pos.x == column && pos.y == row {
-                        draw_focus(x, y, tile_width, tile_height);
+                        draw_focus(x, y, tile_width, tile_height, theme);
                     }
                 }
             }
@@ -70,48 +133,383 @@ pub fn draw_tiles(
     }
 }
 
-fn draw_tile(x: i32, y: i32, width: i32, height: i32, color: Color) {
-    fltk::draw::draw_box(
-        fltk::enums::FrameType::UpBox,
+// If boxed is false the tile's image has already been drawn
+#[allow(clippy::too_many_arguments)]
+fn draw_tile(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    color: Color,
+    kind: Kind,
+    boxed: bool,
+    theme: &Theme,
+) {
+    if kind == Kind::Stone {
+        fltk::draw::draw_box(
+            fltk::enums::FrameType::DownBox,
+            x,
+            y,
+            width,
+            height,
+            theme.stone,
+        );
+    } else if boxed {
+        fltk::draw::draw_box(theme.tile_frame, x, y, width, height, color);
+    }
+    match kind {
+        Kind::Plain | Kind::Stone => (),
+        Kind::Bomb => draw_bomb(x, y, width, height),
+        Kind::Wildcard => draw_wildcard(x, y, width, height),
+        Kind::Multiplier => draw_multiplier(x, y, width, height),
+    }
+    fltk::draw::draw_rect_with_color(x, y, width, height, theme.background);
+}
+
+// Outlines the group a click would remove: solid if the click is legal,
+// otherwise dashed
+#[allow(clippy::too_many_arguments)]
+pub fn draw_outline(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    size: Size,
+    group: &PosSet,
+    is_legal: bool,
+    theme: &Theme,
+) {
+    let (tile_width, tile_height) =
+        get_tile_size(size.columns, size.rows, width, height);
+    let style = if is_legal {
+        fltk::draw::LineStyle::Solid
+    } else {
+        fltk::draw::LineStyle::Dash
+    };
+    for (color, line_width) in
+        [(theme.focus_inner, 5), (theme.focus_outer, 3)]
+    {
+        fltk::draw::set_draw_color(color);
+        fltk::draw::set_line_style(style, line_width);
+        for pos in group.iter() {
+            let left = x1 + tile_width * pos.x;
+            let top = y1 + tile_height * pos.y;
+            let right = left + tile_width;
+            let bottom = top + tile_height;
+            // Only the edges that aren't shared with the rest of the group
+            let outside = |x: i32, y: i32| {
+                !group.contains(&Pos::new(pos.x + x, pos.y + y))
+            };
+            if outside(-1, 0) {
+                fltk::draw::draw_line(left, top, left, bottom);
+            }
+            if outside(1, 0) {
+                fltk::draw::draw_line(right, top, right, bottom);
+            }
+            if outside(0, -1) {
+                fltk::draw::draw_line(left, top, right, top);
+            }
+            if outside(0, 1) {
+                fltk::draw::draw_line(left, bottom, right, bottom);
+            }
+        }
+    }
+    fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
+}
+
+// Draws the tiles just removed shrinking and fading into the background
+// and the points they scored floating up
+#[allow(clippy::too_many_arguments)]
+pub fn draw_vanish(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    size: Size,
+    vanish: &Vanish,
+    colors: &[Color],
+    theme: &Theme,
+) {
+    let (tile_width, tile_height) =
+        get_tile_size(size.columns, size.rows, width, height);
+    let scale = vanish.scale();
+    let w = (tile_width as f64 * scale).round() as i32;
+    let h = (tile_height as f64 * scale).round() as i32;
+    if w > 1 && h > 1 {
+        for (pos, tile) in vanish.tiles.iter() {
+            let mut color = colors[tile.color as usize];
+            if vanish.is_lit() {
+                color = color.lighter().lighter();
+            }
+            let color =
+                Color::color_average(color, theme.background, scale as f32);
+            draw_tile(
+                x1 + tile_width * pos.x + (tile_width - w) / 2,
+                y1 + tile_height * pos.y + (tile_height - h) / 2,
+                w,
+                h,
+                color,
+                tile.kind,
+                true,
+                theme,
+            );
+        }
+    }
+    let ((column, row), fade) = vanish.label();
+    let message = format!("+{}", i18n::number(vanish.points));
+    fltk::draw::set_font(theme.overlay_font, (tile_height * 2 / 3).max(10));
+    let (text_width, text_height) = fltk::draw::measure(&message, false);
+    let x = x1 + (tile_width as f64 * column) as i32 - text_width / 2;
+    let y = y1 + (tile_height as f64 * row) as i32 + text_height / 3;
+    for (color, offset) in [(theme.overlay_shadow, 1), (theme.won, 0)] {
+        fltk::draw::set_draw_color(Color::color_average(
+            color,
+            theme.background,
+            1.0 - fade as f32,
+        ));
+        fltk::draw::draw_text(&message, x + offset, y + offset);
+    }
+}
+
+fn draw_bomb(x: i32, y: i32, width: i32, height: i32) {
+    let side = width.min(height) / 2;
+    let bx = x + (width - side) / 2;
+    let by = y + (height - side) / 2;
+    fltk::draw::set_draw_color(Color::Black);
+    fltk::draw::draw_pie(bx, by, side, side, 0.0, 360.0);
+    fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 2);
+    fltk::draw::draw_line(
+        bx + (side * 3) / 4,
+        by + side / 8,
+        bx + side,
+        by - side / 8,
+    );
+    fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
+}
+
+fn draw_wildcard(x: i32, y: i32, width: i32, height: i32) {
+    let side = (width.min(height) * 2) / 3;
+    let wx = x + (width - side) / 2;
+    let wy = y + (height - side) / 2;
+    for (i, color) in [RED, YELLOW, GREEN, BLUE].iter().enumerate() {
+        let start = i as f64 * 90.0;
+        fltk::draw::set_draw_color(*color);
+        fltk::draw::draw_pie(wx, wy, side, side, start, start + 90.0);
+    }
+}
+
+fn draw_multiplier(x: i32, y: i32, width: i32, height: i32) {
+    let message = format!("×{MULTIPLIER}");
+    fltk::draw::set_font(
+        fltk::enums::Font::HelveticaBold,
+        (height / 2).max(8),
+    );
+    fltk::draw::set_draw_color(Color::Black);
+    fltk::draw::draw_text2(
+        &message,
+        x + 1,
+        y + 1,
+        width,
+        height,
+        fltk::enums::Align::Center,
+    );
+    fltk::draw::set_draw_color(Color::White);
+    fltk::draw::draw_text2(
+        &message,
         x,
         y,
         width,
         height,
-        color,
+        fltk::enums::Align::Center,
     );
-    fltk::draw::draw_rect_with_color(x, y, width, height, BACKGROUND_COLOR);
 }
 
-fn draw_focus(x: i32, y: i32, width: i32, height: i32) {
+// Draws the symbol for the given color index: a shape for the first few
+// colors and a letter for the rest
+fn draw_symbol(
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    index: u8,
+    color: Color,
+    small: bool,
+) {
+    let side = width.min(height) / if small { 4 } else { 2 };
+    let (sx, sy) = if small {
+        (x + 4, y + 4)
+    } else {
+        (x + (width - side) / 2, y + (height - side) / 2)
+    };
+    let half = side / 2;
+    let symbol_color = palette::contrasting(color);
+    fltk::draw::set_draw_color(symbol_color);
+    match index {
+        0 => fltk::draw::draw_pie(sx, sy, side, side, 0.0, 360.0),
+        1 => fltk::draw::draw_rect_fill(sx, sy, side, side, symbol_color),
+        2 => fltk::draw::draw_polygon(
+            sx,
+            sy + side,
+            sx + half,
+            sy,
+            sx + side,
+            sy + side,
+        ),
+        3 => {
+            // diamond
+            fltk::draw::draw_polygon(
+                sx,
+                sy + half,
+                sx + half,
+                sy,
+                sx + side,
+                sy + half,
+            );
+            fltk::draw::draw_polygon(
+                sx,
+                sy + half,
+                sx + half,
+                sy + side,
+                sx + side,
+                sy + half,
+            );
+        }
+        4 => {
+            // plus
+            let bar = (side / 4).max(1);
+            let offset = (side - bar) / 2;
+            fltk::draw::draw_rect_fill(
+                sx,
+                sy + offset,
+                side,
+                bar,
+                symbol_color,
+            );
+            fltk::draw::draw_rect_fill(
+                sx + offset,
+                sy,
+                bar,
+                side,
+                symbol_color,
+            );
+        }
+        5 => {
+            // cross
+            fltk::draw::set_line_style(
+                fltk::draw::LineStyle::Solid,
+                (side / 5).max(1),
+            );
+            fltk::draw::draw_line(sx, sy, sx + side, sy + side);
+            fltk::draw::draw_line(sx, sy + side, sx + side, sy);
+            fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
+        }
+        6 => {
+            // ring
+            fltk::draw::set_line_style(
+                fltk::draw::LineStyle::Solid,
+                (side / 6).max(1),
+            );
+            fltk::draw::draw_arc(sx, sy, side, side, 0.0, 360.0);
+            fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
+        }
+        7 => fltk::draw::draw_polygon(
+            sx,
+            sy,
+            sx + side,
+            sy,
+            sx + half,
+            sy + side,
+        ),
+        _ => {
+            let letter = char::from(b'A' + index - SHAPES).to_string();
+            fltk::draw::set_font(
+                fltk::enums::Font::HelveticaBold,
+                (side + half).max(8),
+            );
+            fltk::draw::draw_text2(
+                &letter,
+                sx - half,
+                sy - half,
+                side * 2,
+                side * 2,
+                fltk::enums::Align::Center,
+            );
+        }
+    }
+}
+
+fn draw_focus(x: i32, y: i32, width: i32, height: i32, theme: &Theme) {
     fltk::draw::set_line_style(fltk::draw::LineStyle::Dot, 2);
     fltk::draw::draw_rect_with_color(
         x + 4,
         y + 4,
         width - 8,
         height - 8,
-        Color::White,
+        theme.focus_inner,
     );
     fltk::draw::draw_rect_with_color(
         x + 3,
         y + 3,
         width - 6,
         height - 6,
-        Color::Black,
+        theme.focus_outer,
     );
     fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
 }
 
-pub fn ripple(n: usize) -> Vec<usize> {
-    // The purpose is to favor the player especially in the end game by
-    // working from the middle out.
-    let mut ripple = Vec::with_capacity(n);
-    let middle = n / 2;
-    for (i, j) in (middle..n).zip((0..middle).rev()) {
-        ripple.push(i);
-        ripple.push(j);
+// Draws a sample of the theme: two rows of tiles (one a stone), the focus
+// and an overlay message
+pub fn draw_preview(
+    x1: i32,
+    y1: i32,
+    width: i32,
+    height: i32,
+    theme: &Theme,
+) {
+    const COLUMNS: i32 = 5;
+    const ROWS: i32 = 2;
+    draw_background(x1, y1, width, height, theme);
+    let colors = if theme.palette.is_empty() {
+        COLORS.get().read().unwrap().clone()
+    } else {
+        theme.palette.clone()
+    };
+    let (tile_width, tile_height) =
+        get_tile_size(COLUMNS, ROWS, width, height);
+    for row in 0..ROWS {
+        for column in 0..COLUMNS {
+            let i = (row * COLUMNS + column) as usize;
+            let kind = if i == COLUMNS as usize {
+                Kind::Stone
+            } else {
+                Kind::Plain
+            };
+            draw_tile(
+                x1 + tile_width * column,
+                y1 + tile_height * row,
+                tile_width,
+                tile_height,
+                colors[i % colors.len()],
+                kind,
+                true,
+                theme,
+            );
+        }
     }
-    if (n % 2) != 0 {
-        ripple.push(n - 1);
+    draw_focus(x1, y1, tile_width, tile_height, theme);
+    fltk::draw::set_font(
+        theme.overlay_font,
+        (theme.overlay_size / 2).max(8),
+    );
+    for (color, offset) in [(theme.overlay_shadow, 0), (theme.won, -1)] {
+        fltk::draw::set_draw_color(color);
+        fltk::draw::draw_text2(
+            tr("You Won!"),
+            x1 + offset,
+            y1 + offset,
+            width,
+            height,
+            fltk::enums::Align::Center,
+        );
     }
-    ripple
 }
@@ -1,11 +1,39 @@
 // Copyright © 2021-23 Mark Summerfield. All rights reserved.
 // License: GPLv3
 
+// See storage.rs for where the files are kept. The config file and every
+// profile's files (see profile.rs) start with the version of their
+// layout, so that older files can be brought up to date (see
+// migrate_config() and migrate_profile()). Saving keeps any keys and
+// sections that aren't known, e.g., from a newer version.
+// Values that are out of range are clamped and those that aren't valid
+// are rejected; either way the problem is reported once at startup (see
+// report_problems()).
+
+use super::CONFIG;
+use crate::ai::Strength;
+use crate::fixed::Variant;
 use crate::fixed::{
-    APPNAME, COLORS, COLORS_MIN, DELAY_MS_MAX, DELAY_MS_MIN, SCALE_MAX,
-    SCALE_MIN, SIZE_MAX, SIZE_MIN,
+    APPNAME, COLORS, COLORS_MIN, DELAY_MS_MAX, DELAY_MS_MIN, DISTANCE_MAX,
+    DISTANCE_MIN, SCALE_MAX, SCALE_MIN, SIZE_MAX, SIZE_MIN, SPECIALS_MAX,
+    SPECIALS_MIN,
 };
+use crate::generator::Generator;
+use crate::highscores::{EndlessRecords, HighScores, Statistics};
+use crate::i18n::{self, tr, trf, Language};
+use crate::keys::KeyBindings;
+use crate::palette::Palette;
+use crate::preset::Preset;
+use crate::profile::{self, SavedGame, DEFAULT_PROFILE};
+use crate::storage;
+use crate::theme;
+use crate::tileset;
 use crate::util;
+use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
+
+pub const CONFIG_VERSION: u32 = 4;
 
 #[derive(Clone, Debug)]
 pub struct Config {
@@ -14,56 +42,389 @@ pub struct Config {
     pub window_height: i32,
     pub window_width: i32,
     pub window_scale: f32,
+    pub window_language: Language,
+    pub profile: String, // the active profile's name; see profile.rs
     pub board_columns: u8,
     pub board_rows: u8,
     pub board_maxcolors: u8,
     pub board_delay_ms: u16,
-    pub board_highscore: u16,
+    pub board_variant: Variant,
+    pub board_ai_strength: Strength,
+    pub board_bombs: u8, // percentages of special tiles
+    pub board_wildcards: u8,
+    pub board_stones: u8,
+    pub board_multipliers: u8,
+    pub board_palette: Palette,
+    pub board_symbols: bool, // draw a symbol on each color
+    pub board_min_distance: u8, // between colors (CIEDE2000)
+    pub board_theme: String,
+    pub board_tileset: String,
+    pub board_reduced_motion: bool, // move tiles without sliding
+    pub board_wrap: bool, // the keyboard focus wraps round at the edges
+    pub board_solvable: bool, // only play boards that can be cleared
+    pub board_generator: Generator, // how new boards are laid out
+    pub highscores: HighScores,
+    pub endless_records: EndlessRecords,
+    pub statistics: Statistics,
+    pub player_names: Vec<String>,
+    pub keys: KeyBindings,
+    pub saved_game: Option<SavedGame>,
+    pub problems: Vec<String>, // found while reading; not saved
     pub filename: std::path::PathBuf,
+    pub data_dir: std::path::PathBuf, // for the profiles' game data
 }
 
 impl Config {
     pub fn new() -> Self {
+        let filename = storage::config_filename();
         let mut config = Config {
-            filename: get_config_filename(),
+            data_dir: storage::data_dir(&filename),
+            filename,
             ..Default::default()
         };
-        if let Ok(ini) = ini::Ini::load_from_file(&config.filename) {
+        let mut name = DEFAULT_PROFILE.to_string();
+        let filename = config.filename.clone();
+        if let Some(mut ini) = config.load_ini(&filename) {
+            if version(&ini) < CONFIG_VERSION {
+                config.migrate_files(&mut ini);
+            }
+            let mut checker = Checker::new(&filename, &mut config.problems);
+            if let Some(properties) = ini.section(Some(PROFILES_SECTION)) {
+                checker.choice(
+                    properties,
+                    PROFILES_SECTION,
+                    CURRENT_KEY,
+                    &mut name,
+                    |name| {
+                        profile::is_valid_name(name)
+                            .then(|| name.to_string())
+                    },
+                    String::clone,
+                );
+            }
             if let Some(properties) = ini.section(Some(WINDOW_SECTION)) {
                 read_window_properties(properties, &mut config);
             }
-            if let Some(properties) = ini.section(Some(BOARD_SECTION)) {
-                read_board_properties(properties, &mut config);
-            }
         }
+        config.load_profile(&name);
         config
     }
 
+    // Makes the named profile active with its own settings, high scores,
+    // etc., or with the defaults if it is new
+    pub fn load_profile(&mut self, name: &str) {
+        *self = Config {
+            window_x: self.window_x,
+            window_y: self.window_y,
+            window_height: self.window_height,
+            window_width: self.window_width,
+            window_scale: self.window_scale,
+            window_language: self.window_language,
+            profile: name.to_string(),
+            problems: std::mem::take(&mut self.problems),
+            filename: self.filename.clone(),
+            data_dir: self.data_dir.clone(),
+            ..Default::default()
+        };
+        let filename = self.profile_filename(name);
+        let data_filename = self.profile_data_filename(name);
+        let mut is_new = true;
+        if let Some(mut ini) = self.load_ini(&filename) {
+            if version(&ini) < CONFIG_VERSION {
+                self.migrate_profile_files(&mut ini, &filename);
+            }
+            self.read_profile(&ini, &filename);
+            is_new = false;
+        }
+        if let Some(mut ini) = self.load_ini(&data_filename) {
+            migrate_data(&mut ini); // saved when the profile is saved
+            self.read_profile(&ini, &data_filename);
+            is_new = false;
+        }
+        if is_new && name != DEFAULT_PROFILE {
+            self.player_names[0] = name.to_string();
+        }
+    }
+
+    // Saves the active profile and then loads the named one
+    pub fn switch_profile(&mut self, name: &str) {
+        self.save_profile();
+        self.load_profile(name);
+    }
+
+    // Returns None if the file doesn't exist or can't be read, in which
+    // case it is copied so that saving doesn't lose it
+    fn load_ini(&mut self, filename: &Path) -> Option<ini::Ini> {
+        if !filename.exists() {
+            return None;
+        }
+        match ini::Ini::load_from_file(filename) {
+            Ok(ini) => {
+                if version(&ini) > CONFIG_VERSION {
+                    self.problems.push(trf(
+                        "{0}: written by a newer version of {1} so some \
+                        settings may not be used",
+                        &[&filename.display(), &APPNAME],
+                    ));
+                }
+                Some(ini)
+            }
+            Err(err) => {
+                let backup = filename.with_extension("ini.bak");
+                self.problems
+                    .push(match std::fs::copy(filename, &backup) {
+                    Ok(_) => trf(
+                        "{0}: {1}; the defaults are used and the file has \
+                        been copied to {2}",
+                        &[&filename.display(), &err, &backup.display()],
+                    ),
+                    Err(_) => trf(
+                        "{0}: {1}; the defaults are used",
+                        &[&filename.display(), &err],
+                    ),
+                });
+                None
+            }
+        }
+    }
+
+    // Brings an older config file up to date, moving any settings that
+    // now belong to a profile into the active profile's files, and saves
+    // them all so that this is only done once
+    fn migrate_files(&mut self, ini: &mut ini::Ini) {
+        let name = ini
+            .get_from(Some(PROFILES_SECTION), CURRENT_KEY)
+            .filter(|name| profile::is_valid_name(name))
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string();
+        if let Some(mut settings) = migrate_config(ini) {
+            let filename = self.profile_filename(&name);
+            self.migrate_profile_data(&mut settings, &name);
+            self.write_merged(&settings, &filename, |config, existing| {
+                config.migrate_profile_data(existing, &name)
+            });
+        }
+        let filename = self.filename.clone();
+        self.write_migrated(ini, &filename);
+    }
+
+    // Brings an older profile's file up to date, moving its game data
+    // into the profile's data file, and saves both
+    fn migrate_profile_files(
+        &mut self,
+        ini: &mut ini::Ini,
+        filename: &Path,
+    ) {
+        let name = self.profile.clone();
+        self.migrate_profile_data(ini, &name);
+        self.write_migrated(ini, filename);
+    }
+
+    // Brings a profile's settings up to date, moving any game data they
+    // hold into the profile's data file
+    fn migrate_profile_data(&mut self, ini: &mut ini::Ini, name: &str) {
+        if let Some(mut data) = migrate_profile(ini) {
+            migrate_data(&mut data);
+            let data_filename = self.profile_data_filename(name);
+            self.write_merged(&data, &data_filename, |_, existing| {
+                migrate_data(existing)
+            });
+        }
+    }
+
+    // Writes the migrated sections to the file or, if it already exists,
+    // merges them into it once it is brought up to date. Its own values
+    // are kept and any migrated value that differs is reported since it
+    // can't be kept too
+    fn write_merged(
+        &mut self,
+        migrated: &ini::Ini,
+        filename: &Path,
+        migrate: impl FnOnce(&mut Self, &mut ini::Ini),
+    ) {
+        let Some(mut ini) = self.load_ini(filename) else {
+            self.write_migrated(migrated, filename);
+            return;
+        };
+        migrate(self, &mut ini);
+        for (section, properties) in migrated.iter() {
+            for (key, value) in properties.iter() {
+                match ini.get_from(section, key) {
+                    None => {
+                        ini.with_section(section).set(key, value);
+                    }
+                    Some(kept) if kept != value && key != VERSION_KEY => {
+                        self.problems.push(trf(
+                            "{0}: [{1}] {2} is kept as “{3}” so the older \
+                            “{4}” is dropped",
+                            &[
+                                &filename.display(),
+                                &section.unwrap_or_default(),
+                                &key,
+                                &kept,
+                                &value,
+                            ],
+                        ))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+        self.write_migrated(&ini, filename);
+    }
+
+    fn write_migrated(&mut self, ini: &ini::Ini, filename: &Path) {
+        if let Err(err) = storage::write_ini(ini, filename) {
+            self.problems
+                .push(trf("failed to save configuration: {0}", &[&err]));
+        }
+    }
+
+    fn read_profile(&mut self, ini: &ini::Ini, filename: &Path) {
+        if let Some(properties) = ini.section(Some(HIGH_SCORES_SECTION)) {
+            self.highscores.read(properties);
+        }
+        if let Some(properties) = ini.section(Some(ENDLESS_SECTION)) {
+            self.endless_records.read(properties);
+        }
+        if let Some(properties) = ini.section(Some(STATISTICS_SECTION)) {
+            self.statistics.read(properties);
+        }
+        if let Some(properties) = ini.section(Some(PLAYERS_SECTION)) {
+            read_players_properties(properties, self);
+        }
+        if let Some(properties) = ini.section(Some(BOARD_SECTION)) {
+            read_board_properties(properties, filename, self);
+        }
+        if let Some(properties) = ini.section(Some(KEYS_SECTION)) {
+            let mut problems = self.keys.read(properties);
+            problems.extend(self.keys.conflicts());
+            self.problems.extend(problems.iter().map(|problem| {
+                format!(
+                    "{}: [{KEYS_SECTION}] {problem}",
+                    filename.display()
+                )
+            }));
+        }
+        if let Some(properties) = ini.section(Some(SAVED_GAME_SECTION)) {
+            self.saved_game = SavedGame::read(properties);
+            if self.saved_game.is_none() {
+                self.problems.push(trf(
+                    "{0}: the saved game couldn't be read so a new game is \
+                    started",
+                    &[&filename.display()],
+                ));
+            }
+        }
+    }
+
+    // Returns the directory that holds a file for each profile, e.g.,
+    // ~/.config/gravitate-profiles
+    pub fn profiles_dir(&self) -> std::path::PathBuf {
+        self.sibling_dir("profiles")
+    }
+
+    pub fn profile_filename(&self, name: &str) -> std::path::PathBuf {
+        self.profiles_dir().join(format!("{name}.ini"))
+    }
+
+    // Returns the file that holds the profile's high scores, statistics
+    // and saved game, e.g., ~/.local/share/gravitate/profiles/Default.ini
+    pub fn profile_data_filename(&self, name: &str) -> std::path::PathBuf {
+        self.data_dir.join("profiles").join(format!("{name}.ini"))
+    }
+
     pub fn save(&self, x: i32, y: i32, width: i32, height: i32) {
         if self.filename.to_string_lossy() == "" {
-            util::warning("failed to save configuration: no filename");
+            util::warning(&trf(
+                "failed to save configuration: {0}",
+                &[&tr("no filename")],
+            ));
         } else {
-            let mut ini = ini::Ini::new();
+            let mut ini = load_for_saving(&self.filename);
+            migrate_config(&mut ini);
             ini.with_section(Some(WINDOW_SECTION))
                 .set(X_KEY, x.to_string())
                 .set(Y_KEY, y.to_string())
                 .set(WIDTH_KEY, width.to_string())
                 .set(HEIGHT_KEY, height.to_string())
-                .set(SCALE_KEY, fltk::app::screen_scale(0).to_string());
-            ini.with_section(Some(BOARD_SECTION))
-                .set(COLUMNS_KEY, self.board_columns.to_string())
-                .set(ROWS_KEY, self.board_rows.to_string())
-                .set(MAX_COLORS_KEY, self.board_maxcolors.to_string())
-                .set(DELAY_MS_KEY, self.board_delay_ms.to_string())
-                .set(HIGH_SCORE_KEY, self.board_highscore.to_string());
-            match ini.write_to_file(&self.filename) {
+                .set(SCALE_KEY, fltk::app::screen_scale(0).to_string())
+                .set(LANGUAGE_KEY, self.window_language.name());
+            ini.with_section(Some(PROFILES_SECTION))
+                .set(CURRENT_KEY, &self.profile);
+            match storage::write_ini(&ini, &self.filename) {
                 Ok(_) => {}
-                Err(err) => util::warning(&format!(
-                    "failed to save configuration: {err}"
+                Err(err) => util::warning(&trf(
+                    "failed to save configuration: {0}",
+                    &[&err],
                 )),
             }
+            self.save_profile();
         }
     }
+
+    pub fn save_profile(&self) {
+        if self.filename.to_string_lossy() == "" {
+            return; // save() warns about this
+        }
+        let filename = self.profile_filename(&self.profile);
+        let mut ini = load_for_saving(&filename);
+        migrate_profile(&mut ini); // the game data is saved separately
+        ini.with_section(Some(BOARD_SECTION))
+            .set(COLUMNS_KEY, self.board_columns.to_string())
+            .set(ROWS_KEY, self.board_rows.to_string())
+            .set(MAX_COLORS_KEY, self.board_maxcolors.to_string())
+            .set(DELAY_MS_KEY, self.board_delay_ms.to_string())
+            .set(VARIANT_KEY, self.board_variant.name())
+            .set(AI_STRENGTH_KEY, self.board_ai_strength.name())
+            .set(BOMBS_KEY, self.board_bombs.to_string())
+            .set(WILDCARDS_KEY, self.board_wildcards.to_string())
+            .set(STONES_KEY, self.board_stones.to_string())
+            .set(MULTIPLIERS_KEY, self.board_multipliers.to_string())
+            .set(PALETTE_KEY, self.board_palette.name())
+            .set(SYMBOLS_KEY, self.board_symbols.to_string())
+            .set(MIN_DISTANCE_KEY, self.board_min_distance.to_string())
+            .set(THEME_KEY, &self.board_theme)
+            .set(TILE_SET_KEY, &self.board_tileset)
+            .set(REDUCED_MOTION_KEY, self.board_reduced_motion.to_string())
+            .set(WRAP_KEY, self.board_wrap.to_string())
+            .set(SOLVABLE_KEY, self.board_solvable.to_string())
+            .set(GENERATOR_KEY, self.board_generator.name());
+        self.keys.write(&mut ini, KEYS_SECTION);
+        for (i, name) in self.player_names.iter().enumerate() {
+            ini.with_section(Some(PLAYERS_SECTION))
+                .set(format!("{PLAYER_KEY}{}", i + 1), name);
+        }
+        let data_filename = self.profile_data_filename(&self.profile);
+        let mut data = load_for_saving(&data_filename);
+        migrate_data(&mut data);
+        self.highscores.write(&mut data, HIGH_SCORES_SECTION);
+        self.endless_records.write(&mut data, ENDLESS_SECTION);
+        self.statistics.write(&mut data, STATISTICS_SECTION);
+        data.delete(Some(SAVED_GAME_SECTION)); // it may have been played
+        if let Some(saved_game) = &self.saved_game {
+            saved_game.write(&mut data, SAVED_GAME_SECTION);
+        }
+        for (ini, filename) in [(ini, filename), (data, data_filename)] {
+            if let Err(err) = storage::write_ini(&ini, &filename) {
+                util::warning(&trf(
+                    "failed to save configuration: {0}",
+                    &[&err],
+                ));
+            }
+        }
+    }
+
+    // Returns a directory beside the config file, e.g., for "themes",
+    // ~/.config/gravitate-themes
+    pub fn sibling_dir(&self, suffix: &str) -> std::path::PathBuf {
+        let stem = self
+            .filename
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        self.filename.with_file_name(format!("{stem}-{suffix}"))
+    }
 }
 
 impl Default for Config {
@@ -74,33 +435,152 @@ impl Default for Config {
             window_height: 300,
             window_width: 260,
             window_scale: 1.0,
+            window_language: Language::detect(),
+            profile: DEFAULT_PROFILE.to_string(),
             board_columns: 9,
             board_rows: 9,
             board_maxcolors: 4,
             board_delay_ms: 250,
-            board_highscore: 0,
+            board_variant: Variant::Classic,
+            board_ai_strength: Strength::Greedy,
+            board_bombs: 0,
+            board_wildcards: 0,
+            board_stones: 0,
+            board_multipliers: 0,
+            board_palette: Palette::Standard,
+            board_symbols: false,
+            board_min_distance: 20,
+            board_theme: theme::LIGHT.to_string(),
+            board_tileset: tileset::NONE.to_string(),
+            board_reduced_motion: false,
+            board_wrap: false,
+            board_solvable: false,
+            board_generator: Generator::Uniform,
+            highscores: HighScores::default(),
+            endless_records: EndlessRecords::default(),
+            statistics: Statistics::default(),
+            player_names: vec![
+                tr("Player 1").to_string(),
+                tr("Player 2").to_string(),
+            ],
+            keys: KeyBindings::default(),
+            saved_game: None,
+            problems: vec![],
             filename: std::path::PathBuf::new(),
+            data_dir: std::path::PathBuf::new(),
         }
     }
 }
 
-fn get_config_filename() -> std::path::PathBuf {
-    let mut dir = dirs::config_dir();
-    let mut dot = "";
-    if dir.is_none() {
-        if std::env::consts::FAMILY == "unix" {
-            dot = ".";
+// Shows every problem found in the config and profile files in one go
+pub fn report_problems() {
+    let problems =
+        std::mem::take(&mut CONFIG.get().write().unwrap().problems);
+    if !problems.is_empty() {
+        util::warning(&format!(
+            "{}\n\n{}",
+            tr("Some settings couldn't be used as they are:"),
+            problems.join("\n")
+        ));
+    }
+}
+
+// Reads values, clamping those that are out of range and rejecting those
+// that aren't valid (so keeping the default), and noting each problem
+struct Checker<'a> {
+    filename: &'a Path,
+    problems: &'a mut Vec<String>,
+}
+
+impl<'a> Checker<'a> {
+    fn new(filename: &'a Path, problems: &'a mut Vec<String>) -> Self {
+        Self { filename, problems }
+    }
+
+    fn num<T>(
+        &mut self,
+        properties: &ini::Properties,
+        section: &str,
+        key: &str,
+        minimum: T,
+        maximum: T,
+        value: &mut T,
+    ) where
+        T: PartialOrd + Copy + FromStr + Display,
+    {
+        let Some(text) = properties.get(key) else {
+            return;
+        };
+        match text.trim().parse::<T>() {
+            Ok(n) if minimum <= n && n <= maximum => *value = n,
+            Ok(n) => {
+                *value = if n < minimum { minimum } else { maximum };
+                self.problems.push(trf(
+                    "{0}: [{1}] {2} = “{3}” isn't from {4} to {5} so {6} \
+                    is used",
+                    &[
+                        &self.filename.display(),
+                        &section,
+                        &key,
+                        &text,
+                        &minimum,
+                        &maximum,
+                        value,
+                    ],
+                ));
+            }
+            Err(_) => self.reject(section, key, text, &*value),
         }
-        dir = dirs::home_dir();
     }
-    if let Some(dir) = dir {
-        // to_lowercase is for backwards compatability
-        dir.join(format!("{dot}{}.ini", APPNAME.to_lowercase()))
-    } else {
-        std::path::PathBuf::new()
+
+    fn flag(
+        &mut self,
+        properties: &ini::Properties,
+        section: &str,
+        key: &str,
+        value: &mut bool,
+    ) {
+        if let Some(text) = properties.get(key) {
+            match text.trim().parse() {
+                Ok(flag) => *value = flag,
+                Err(_) => self.reject(section, key, text, &*value),
+            }
+        }
+    }
+
+    fn choice<T>(
+        &mut self,
+        properties: &ini::Properties,
+        section: &str,
+        key: &str,
+        value: &mut T,
+        from_name: impl Fn(&str) -> Option<T>,
+        name: impl Fn(&T) -> String,
+    ) {
+        if let Some(text) = properties.get(key) {
+            match from_name(text.trim()) {
+                Some(choice) => *value = choice,
+                None => self.reject(section, key, text, &name(value)),
+            }
+        }
+    }
+
+    fn reject(
+        &mut self,
+        section: &str,
+        key: &str,
+        text: &str,
+        value: &dyn Display,
+    ) {
+        self.problems.push(trf(
+            "{0}: [{1}] {2} = “{3}” isn't valid so {4} is used",
+            &[&self.filename.display(), &section, &key, &text, value],
+        ));
     }
 }
 
+// The window's geometry is where it was left rather than a setting
+// so it is fitted to the screen without being reported
 fn read_window_properties(
     properties: &ini::Properties,
     config: &mut Config,
@@ -121,58 +601,540 @@ fn read_window_properties(
         config.window_height =
             util::get_num(value, 240, max_y, config.window_height)
     }
-    if let Some(value) = properties.get(SCALE_KEY) {
-        config.window_scale =
-            util::get_num(value, SCALE_MIN, SCALE_MAX, config.window_scale);
+    let filename = config.filename.clone();
+    let mut checker = Checker::new(&filename, &mut config.problems);
+    let section = WINDOW_SECTION;
+    checker.num(
+        properties,
+        section,
+        SCALE_KEY,
+        SCALE_MIN,
+        SCALE_MAX,
+        &mut config.window_scale,
+    );
+    if properties.get(SCALE_KEY).is_some() {
         fltk::app::set_screen_scale(0, config.window_scale);
     }
+    checker.choice(
+        properties,
+        section,
+        LANGUAGE_KEY,
+        &mut config.window_language,
+        Language::from_name,
+        |language| language.name().to_string(),
+    );
+    i18n::set_language(config.window_language);
 }
 
 fn read_board_properties(
     properties: &ini::Properties,
+    filename: &Path,
     config: &mut Config,
 ) {
-    if let Some(value) = properties.get(COLUMNS_KEY) {
-        config.board_columns =
-            util::get_num(value, SIZE_MIN, SIZE_MAX, config.board_columns)
-    }
-    if let Some(value) = properties.get(ROWS_KEY) {
-        config.board_rows =
-            util::get_num(value, SIZE_MIN, SIZE_MAX, config.board_rows)
-    }
-    if let Some(value) = properties.get(MAX_COLORS_KEY) {
-        let colors_max = COLORS.get().read().unwrap().len();
-        config.board_maxcolors = util::get_num(
-            value,
+    let colors_max = COLORS.get().read().unwrap().len() as u8;
+    let mut checker = Checker::new(filename, &mut config.problems);
+    let section = BOARD_SECTION;
+    for (key, minimum, maximum, value) in [
+        (COLUMNS_KEY, SIZE_MIN, SIZE_MAX, &mut config.board_columns),
+        (ROWS_KEY, SIZE_MIN, SIZE_MAX, &mut config.board_rows),
+        (
+            MAX_COLORS_KEY,
             COLORS_MIN,
-            colors_max as u8,
-            config.board_maxcolors,
-        )
+            colors_max,
+            &mut config.board_maxcolors,
+        ),
+        (
+            MIN_DISTANCE_KEY,
+            DISTANCE_MIN,
+            DISTANCE_MAX,
+            &mut config.board_min_distance,
+        ),
+        (BOMBS_KEY, SPECIALS_MIN, SPECIALS_MAX, &mut config.board_bombs),
+        (
+            WILDCARDS_KEY,
+            SPECIALS_MIN,
+            SPECIALS_MAX,
+            &mut config.board_wildcards,
+        ),
+        (STONES_KEY, SPECIALS_MIN, SPECIALS_MAX, &mut config.board_stones),
+        (
+            MULTIPLIERS_KEY,
+            SPECIALS_MIN,
+            SPECIALS_MAX,
+            &mut config.board_multipliers,
+        ),
+    ] {
+        checker.num(properties, section, key, minimum, maximum, value);
     }
-    if let Some(value) = properties.get(DELAY_MS_KEY) {
-        config.board_delay_ms = util::get_num(
-            value,
-            DELAY_MS_MIN,
-            DELAY_MS_MAX,
-            config.board_delay_ms,
-        )
+    checker.num(
+        properties,
+        section,
+        DELAY_MS_KEY,
+        DELAY_MS_MIN,
+        DELAY_MS_MAX,
+        &mut config.board_delay_ms,
+    );
+    checker.choice(
+        properties,
+        section,
+        VARIANT_KEY,
+        &mut config.board_variant,
+        Variant::from_name,
+        |variant| variant.name().to_string(),
+    );
+    checker.choice(
+        properties,
+        section,
+        AI_STRENGTH_KEY,
+        &mut config.board_ai_strength,
+        Strength::from_name,
+        |strength| strength.name().to_string(),
+    );
+    checker.choice(
+        properties,
+        section,
+        PALETTE_KEY,
+        &mut config.board_palette,
+        Palette::from_name,
+        |palette| palette.name().to_string(),
+    );
+    checker.choice(
+        properties,
+        section,
+        GENERATOR_KEY,
+        &mut config.board_generator,
+        Generator::from_name,
+        |generator| generator.name().to_string(),
+    );
+    for (key, value) in [
+        (SYMBOLS_KEY, &mut config.board_symbols),
+        (REDUCED_MOTION_KEY, &mut config.board_reduced_motion),
+        (WRAP_KEY, &mut config.board_wrap),
+        (SOLVABLE_KEY, &mut config.board_solvable),
+    ] {
+        checker.flag(properties, section, key, value);
+    }
+    // Themes and tile sets that can't be found are reported when used
+    if let Some(value) = properties.get(THEME_KEY) {
+        config.board_theme = value.to_string();
+    }
+    if let Some(value) = properties.get(TILE_SET_KEY) {
+        config.board_tileset = value.to_string();
+    }
+}
+
+fn read_players_properties(
+    properties: &ini::Properties,
+    config: &mut Config,
+) {
+    for (i, name) in config.player_names.iter_mut().enumerate() {
+        if let Some(value) =
+            properties.get(format!("{PLAYER_KEY}{}", i + 1))
+        {
+            if !value.trim().is_empty() {
+                *name = value.trim().to_string();
+            }
+        }
+    }
+}
+
+// Unversioned files are version 0; a file that starts with a section has
+// no general section at all
+fn version(ini: &ini::Ini) -> u32 {
+    ini.get_from(None::<String>, VERSION_KEY)
+        .and_then(|version| version.trim().parse().ok())
+        .unwrap_or_default()
+}
+
+fn set_version(ini: &mut ini::Ini) {
+    if version(ini) < CONFIG_VERSION {
+        ini.with_general_section()
+            .set(VERSION_KEY, CONFIG_VERSION.to_string());
+    }
+}
+
+// Saving starts from what's in the file so that unknown keys are kept
+fn load_for_saving(filename: &Path) -> ini::Ini {
+    ini::Ini::load_from_file(filename).unwrap_or_default()
+}
+
+// Version 0 config files predate profiles so hold the profile's sections
+// too: these are removed and returned
+fn migrate_config(ini: &mut ini::Ini) -> Option<ini::Ini> {
+    let settings = if version(ini) < 1 {
+        take_sections(ini, &PROFILE_SECTIONS)
+    } else {
+        None
+    };
+    set_version(ini);
+    settings
+}
+
+// Version 0 profiles may have the single high score from before there
+// were tables of high scores for each variant. Version 0 and 1 profiles
+// hold their game data too: this is removed and returned
+fn migrate_profile(ini: &mut ini::Ini) -> Option<ini::Ini> {
+    if version(ini) < 1 {
+        if let Some(score) =
+            ini.delete_from(Some(BOARD_SECTION), HIGH_SCORE_KEY)
+        {
+            let category = Variant::Classic.name();
+            if score.trim().parse::<u16>().is_ok_and(|score| score > 0)
+                && ini
+                    .get_from(Some(HIGH_SCORES_SECTION), category)
+                    .is_none()
+            {
+                ini.with_section(Some(HIGH_SCORES_SECTION))
+                    .set(category, score.trim());
+            }
+        }
+    }
+    let data = if version(ini) < 2 {
+        take_sections(ini, &DATA_SECTIONS)
+    } else {
+        None
+    };
+    set_version(ini);
+    data
+}
+
+// Before version 3 there was one set of statistics rather than one for
+// each preset: these are kept as Custom's since they could be for any
+// board. Before version 4 high scores had no rating: these are given 0,
+// i.e., unknown
+fn migrate_data(ini: &mut ini::Ini) {
+    if version(ini) < 3 {
+        if let Some(properties) = ini.delete(Some(STATISTICS_SECTION)) {
+            let tally: Vec<&str> = OLD_STATISTICS_KEYS
+                .iter()
+                .map(|&key| properties.get(key).unwrap_or("0"))
+                .collect();
+            ini.with_section(Some(STATISTICS_SECTION))
+                .set(Preset::Custom.name(), tally.join(" "));
+        }
+    }
+    if version(ini) < 4 {
+        if let Some(properties) = ini.delete(Some(HIGH_SCORES_SECTION)) {
+            for (category, value) in properties.iter() {
+                let entries: Vec<String> = value
+                    .split(';')
+                    .map(|entry| {
+                        let mut parts: Vec<&str> =
+                            entry.split_whitespace().collect();
+                        if parts.len() >= 2 {
+                            parts.insert(2, "0"); // after the date
+                        }
+                        parts.join(" ")
+                    })
+                    .collect();
+                ini.with_section(Some(HIGH_SCORES_SECTION))
+                    .set(category, entries.join(";"));
+            }
+        }
     }
-    if let Some(value) = properties.get(HIGH_SCORE_KEY) {
-        config.board_highscore =
-            util::get_num(value, 0, 65535, config.board_highscore)
+    set_version(ini);
+}
+
+// Removes the sections and returns them or None if there were none
+fn take_sections(
+    ini: &mut ini::Ini,
+    sections: &[&str],
+) -> Option<ini::Ini> {
+    let mut taken = None;
+    for &section in sections {
+        if let Some(properties) = ini.delete(Some(section)) {
+            let taken = taken.get_or_insert_with(ini::Ini::new);
+            for (key, value) in properties.iter() {
+                taken.with_section(Some(section)).set(key, value);
+            }
+        }
     }
+    taken
 }
 
 // These names are for backwards compatability
+static VERSION_KEY: &str = "version";
 static WINDOW_SECTION: &str = "Window";
 static X_KEY: &str = "x";
 static Y_KEY: &str = "y";
 static WIDTH_KEY: &str = "width";
 static HEIGHT_KEY: &str = "height";
 static SCALE_KEY: &str = "scale";
+static LANGUAGE_KEY: &str = "language";
 static BOARD_SECTION: &str = "Board";
 static COLUMNS_KEY: &str = "columns";
 static ROWS_KEY: &str = "rows";
 static MAX_COLORS_KEY: &str = "maxColors";
 static DELAY_MS_KEY: &str = "delayMs";
 static HIGH_SCORE_KEY: &str = "HighScore";
+static BOMBS_KEY: &str = "bombs";
+static WILDCARDS_KEY: &str = "wildcards";
+static STONES_KEY: &str = "stones";
+static MULTIPLIERS_KEY: &str = "multipliers";
+static PALETTE_KEY: &str = "palette";
+static SYMBOLS_KEY: &str = "symbols";
+static MIN_DISTANCE_KEY: &str = "minDistance";
+static THEME_KEY: &str = "theme";
+static TILE_SET_KEY: &str = "tileSet";
+static REDUCED_MOTION_KEY: &str = "reducedMotion";
+static WRAP_KEY: &str = "wrapFocus";
+static SOLVABLE_KEY: &str = "solvable";
+static GENERATOR_KEY: &str = "generator";
+static VARIANT_KEY: &str = "variant";
+static AI_STRENGTH_KEY: &str = "aiStrength";
+static HIGH_SCORES_SECTION: &str = "HighScores";
+static ENDLESS_SECTION: &str = "Endless";
+static KEYS_SECTION: &str = "Keys";
+static STATISTICS_SECTION: &str = "Statistics";
+static SAVED_GAME_SECTION: &str = "SavedGame";
+static PROFILES_SECTION: &str = "Profiles";
+static CURRENT_KEY: &str = "current";
+static PLAYERS_SECTION: &str = "Players";
+static PLAYER_KEY: &str = "player";
+// The sections kept in each profile's files
+static PROFILE_SECTIONS: [&str; 7] = [
+    BOARD_SECTION,
+    HIGH_SCORES_SECTION,
+    ENDLESS_SECTION,
+    STATISTICS_SECTION,
+    PLAYERS_SECTION,
+    KEYS_SECTION,
+    SAVED_GAME_SECTION,
+];
+static DATA_SECTIONS: [&str; 4] = [
+    HIGH_SCORES_SECTION,
+    ENDLESS_SECTION,
+    STATISTICS_SECTION,
+    SAVED_GAME_SECTION,
+];
+static OLD_STATISTICS_KEYS: [&str; 4] =
+    ["played", "won", "tilesRemoved", "points"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns a new empty directory for the test
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("gravitate-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn test_config(dir: &Path) -> Config {
+        Config {
+            filename: dir.join("gravitate.ini"),
+            data_dir: dir.join("data"),
+            ..Default::default()
+        }
+    }
+
+    fn load(text: &str) -> ini::Ini {
+        ini::Ini::load_from_str(text).unwrap()
+    }
+
+    #[test]
+    fn migrating_merges_into_existing_profile_files() {
+        let dir = test_dir("merge");
+        let mut config = test_config(&dir);
+        let filename = config.profile_filename(DEFAULT_PROFILE);
+        let data_filename = config.profile_data_filename(DEFAULT_PROFILE);
+        let existing = load("version=4\n[Board]\ntheme=Dark\n");
+        storage::write_ini(&existing, &filename).unwrap();
+        let existing = load(
+            "version=4\n[HighScores]\n\
+            Classic/Normal=500 2023-01-01 3 Ann\n",
+        );
+        storage::write_ini(&existing, &data_filename).unwrap();
+        let mut ini = load(
+            "[Window]\nx=10\n[Board]\ntheme=Light\ncolumns=12\n\
+            [HighScores]\nClassic=100 2022-01-01 Bob\n",
+        );
+        config.migrate_files(&mut ini);
+        let settings = ini::Ini::load_from_file(&filename).unwrap();
+        assert_eq!(
+            settings.get_from(Some(BOARD_SECTION), THEME_KEY),
+            Some("Dark")
+        );
+        assert_eq!(
+            settings.get_from(Some(BOARD_SECTION), COLUMNS_KEY),
+            Some("12")
+        );
+        assert!(settings.section(Some(HIGH_SCORES_SECTION)).is_none());
+        let data = ini::Ini::load_from_file(&data_filename).unwrap();
+        let highscores = data.section(Some(HIGH_SCORES_SECTION)).unwrap();
+        assert_eq!(
+            highscores.get("Classic/Normal"),
+            Some("500 2023-01-01 3 Ann")
+        );
+        assert_eq!(highscores.get("Classic"), Some("100 2022-01-01 0 Bob"));
+        assert_eq!(config.problems.len(), 1, "{:?}", config.problems);
+        assert!(config.problems[0].contains("Light"));
+        let ini = ini::Ini::load_from_file(&config.filename).unwrap();
+        assert_eq!(version(&ini), CONFIG_VERSION);
+        assert!(ini.section(Some(BOARD_SECTION)).is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    // Returns the file's sections (the general section as "") with their
+    // keys and values
+    fn sections(ini: &ini::Ini) -> Vec<(String, Vec<(String, String)>)> {
+        ini.iter()
+            .map(|(section, properties)| {
+                (
+                    section.unwrap_or_default().to_string(),
+                    properties
+                        .iter()
+                        .map(|(key, value)| (key.into(), value.into()))
+                        .collect(),
+                )
+            })
+            .filter(|(_, properties): &(_, Vec<_>)| !properties.is_empty())
+            .collect()
+    }
+
+    fn section(
+        name: &str,
+        items: &[(&str, &str)],
+    ) -> (String, Vec<(String, String)>) {
+        (
+            name.to_string(),
+            items
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        )
+    }
+
+    const VERSION_0: &str = "[Window]\nx=10\nscale=1.5\nfuture=yes\n\
+        [Board]\ncolumns=12\nHighScore=1234\ndelayMs=100\nnewer=kept\n\
+        [Statistics]\nplayed=5\nwon=2\ntilesRemoved=300\npoints=900\n\
+        [Keys]\nnewGame=n\n[Mystery]\nkey=value\n";
+
+    #[test]
+    fn migrating_a_version_0_config_keeps_unknown_keys() {
+        let mut ini = load(VERSION_0);
+        let settings = migrate_config(&mut ini).unwrap();
+        assert_eq!(
+            sections(&ini),
+            [
+                section(
+                    "Window",
+                    &[("x", "10"), ("scale", "1.5"), ("future", "yes")]
+                ),
+                section("Mystery", &[("key", "value")]),
+                section("", &[("version", "4")]),
+            ]
+        );
+        assert_eq!(
+            sections(&settings),
+            [
+                section(
+                    "Board",
+                    &[
+                        ("columns", "12"),
+                        ("HighScore", "1234"),
+                        ("delayMs", "100"),
+                        ("newer", "kept"),
+                    ]
+                ),
+                section(
+                    "Statistics",
+                    &[
+                        ("played", "5"),
+                        ("won", "2"),
+                        ("tilesRemoved", "300"),
+                        ("points", "900"),
+                    ]
+                ),
+                section("Keys", &[("newGame", "n")]),
+            ]
+        );
+        assert!(migrate_config(&mut ini).is_none()); // only done once
+    }
+
+    #[test]
+    fn migrating_from_version_0_gives_the_final_layout() {
+        let mut settings = migrate_config(&mut load(VERSION_0)).unwrap();
+        let mut data = migrate_profile(&mut settings).unwrap();
+        migrate_data(&mut data);
+        assert_eq!(version(&settings), CONFIG_VERSION);
+        assert_eq!(
+            sections(&settings),
+            [
+                section(
+                    "Board",
+                    &[
+                        ("columns", "12"),
+                        ("delayMs", "100"),
+                        ("newer", "kept")
+                    ]
+                ),
+                section("Keys", &[("newGame", "n")]),
+                section("", &[("version", "4")]),
+            ]
+        );
+        assert_eq!(version(&data), CONFIG_VERSION);
+        let mut found = sections(&data);
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                section("", &[("version", "4")]),
+                section("HighScores", &[("Classic", "1234")]),
+                section("Statistics", &[("Custom", "5 2 300 900")]),
+            ]
+        );
+        // Files in the final layout are left as they are
+        let before = (sections(&settings), sections(&data));
+        assert!(migrate_profile(&mut settings).is_none());
+        migrate_data(&mut data);
+        assert_eq!((sections(&settings), sections(&data)), before);
+    }
+
+    #[test]
+    fn migrating_version_3_high_scores_adds_unknown_ratings() {
+        let mut data = load(
+            "version=3\n[HighScores]\n\
+            Classic/Normal=500 2023-01-01 Ann Lee;400 2023-01-02 Bob\n",
+        );
+        migrate_data(&mut data);
+        assert_eq!(
+            data.get_from(Some(HIGH_SCORES_SECTION), "Classic/Normal"),
+            Some("500 2023-01-01 0 Ann Lee;400 2023-01-02 0 Bob")
+        );
+    }
+
+    #[test]
+    fn each_bad_value_is_reported() {
+        crate::fixed::initialize_colors();
+        let filename = Path::new("test.ini");
+        let mut config = Config::default();
+        let ini = load(
+            "[Board]\ncolumns=99\nrows=x\nmaxColors=1\npalette=Nope\n\
+            solvable=maybe\ngenerator=Clustered\nwrapFocus=true\n",
+        );
+        let properties = ini.section(Some(BOARD_SECTION)).unwrap();
+        read_board_properties(properties, filename, &mut config);
+        assert_eq!(config.board_columns, SIZE_MAX); // clamped
+        assert_eq!(config.board_rows, Config::default().board_rows);
+        assert_eq!(config.board_maxcolors, COLORS_MIN); // clamped
+        assert_eq!(config.board_palette, Config::default().board_palette);
+        assert!(!config.board_solvable);
+        assert_eq!(config.board_generator, Generator::Clustered);
+        assert!(config.board_wrap);
+        let problems = &config.problems;
+        assert_eq!(problems.len(), 5, "{problems:?}");
+        for key in [COLUMNS_KEY, ROWS_KEY, MAX_COLORS_KEY, PALETTE_KEY] {
+            assert!(
+                problems.iter().any(|problem| problem.contains(key)),
+                "{key}: {problems:?}"
+            );
+        }
+        assert!(problems
+            .iter()
+            .any(|problem| problem.contains(SOLVABLE_KEY)));
+    }
+}
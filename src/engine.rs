@@ -0,0 +1,938 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// The game rules, independent of any GUI: tiles hold palette indices, not
+// colors, so that the widget decides how each tile looks.
+
+use crate::generator::{BoardGenerator, Generator};
+use crate::util::Pos;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+
+pub type PosSet = HashSet<Pos>;
+type PosForPos = HashMap<Pos, Pos>;
+pub type Tiles = Vec<Vec<Option<Tile>>>;
+pub type Path = Vec<Pos>; // a tile's positions, cell by cell, as it moves
+
+pub const BOMB_RADIUS: i32 = 1;
+pub const MULTIPLIER: u16 = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Plain,
+    Bomb,       // clears a radius when its group is removed
+    Wildcard,   // joins any adjoining color group
+    Stone,      // never moves and can only be removed by a bomb
+    Multiplier, // multiplies the score of the group it is removed with
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tile {
+    pub color: u8, // index into the game's colors
+    pub kind: Kind,
+}
+
+impl Tile {
+    pub fn new(color: u8, kind: Kind) -> Self {
+        Self { color, kind }
+    }
+
+    // Returns true if this tile belongs in a group of the given color;
+    // a color of None means a group of wildcards only.
+    fn joins(&self, color: Option<u8>) -> bool {
+        match self.kind {
+            Kind::Stone => false,
+            Kind::Wildcard => true,
+            _ => Some(self.color) == color,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Size {
+    pub columns: i32,
+    pub rows: i32,
+}
+
+impl Size {
+    pub fn new(columns: i32, rows: i32) -> Self {
+        Self { columns, rows }
+    }
+}
+
+// The percentage of tiles of each special kind
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Specials {
+    pub bombs: u8,
+    pub wildcards: u8,
+    pub stones: u8,
+    pub multipliers: u8,
+}
+
+impl Specials {
+    pub fn choose<R: Rng>(&self, rng: &mut R) -> Kind {
+        let mut roll = rng.gen_range(0..100);
+        for (percent, kind) in [
+            (self.stones, Kind::Stone),
+            (self.bombs, Kind::Bomb),
+            (self.wildcards, Kind::Wildcard),
+            (self.multipliers, Kind::Multiplier),
+        ] {
+            if roll < percent {
+                return kind;
+            }
+            roll -= percent;
+        }
+        Kind::Plain
+    }
+}
+
+// Where the keyboard focus can move to
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Navigation {
+    Left,
+    Right,
+    Up,
+    Down,
+    RowStart,
+    RowEnd,
+    ColumnStart,
+    ColumnEnd,
+    NextGroup, // the next legal group, reading left to right, top down
+}
+
+// Everything needed to generate a particular board: the same setup always
+// gives the same board
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Setup {
+    pub seed: u64,
+    pub size: Size,
+    pub maxcolors: u8,
+    pub specials: Specials,
+    pub solvable: bool, // the seed was chosen because it can be cleared
+    pub generator: Generator,
+}
+
+impl Setup {
+    // Returns the setup's board; the rng must be seeded with the seed
+    pub fn new_game<R: Rng>(&self, rng: &mut R) -> Game {
+        Game::new(
+            self.size,
+            self.maxcolors,
+            self.specials,
+            self.generator,
+            rng,
+        )
+    }
+}
+
+// The players taking turns on the one board
+#[derive(Clone, Debug, Default)]
+pub struct Players {
+    pub names: Vec<String>,
+    pub scores: Vec<u16>,
+    pub current: usize,
+    pub computer: Option<usize>, // the player the computer plays, if any
+}
+
+impl Players {
+    pub fn new(names: &[String]) -> Self {
+        Self {
+            names: names.to_vec(),
+            scores: vec![0; names.len()],
+            current: 0,
+            computer: None,
+        }
+    }
+
+    pub fn is_computers_turn(&self) -> bool {
+        self.computer == Some(self.current)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn current_name(&self) -> &str {
+        &self.names[self.current]
+    }
+
+    // Credits the current player with the points and passes the turn on
+    pub fn credit(&mut self, points: u16) {
+        self.scores[self.current] =
+            self.scores[self.current].saturating_add(points);
+        self.current = (self.current + 1) % self.names.len();
+    }
+
+    // Returns the index of the player with the highest score or None if
+    // it is a draw
+    pub fn winner(&self) -> Option<usize> {
+        let best = self.scores.iter().max()?;
+        let mut winners = self
+            .scores
+            .iter()
+            .enumerate()
+            .filter(|(_, score)| *score == best)
+            .map(|(i, _)| i);
+        match (winners.next(), winners.next()) {
+            (Some(i), None) => Some(i),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Game {
+    pub size: Size,
+    pub maxcolors: u8,
+    pub tiles: Tiles,
+    pub specials: Specials,
+    pub score: u16,
+    pub cleared: u32, // the number of tiles removed so far
+}
+
+impl Game {
+    pub fn new<R: Rng>(
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        generator: Generator,
+        rng: &mut R,
+    ) -> Self {
+        let tiles = generator.tiles(size, maxcolors, specials, rng);
+        Self { size, maxcolors, tiles, specials, score: 0, cleared: 0 }
+    }
+
+    pub fn contains(&self, pos: Pos) -> bool {
+        0 <= pos.x
+            && pos.x < self.size.columns
+            && 0 <= pos.y
+            && pos.y < self.size.rows
+    }
+
+    pub fn tile(&self, pos: Pos) -> Option<Tile> {
+        if self.contains(pos) {
+            self.tiles[pos.x as usize][pos.y as usize]
+        } else {
+            None
+        }
+    }
+
+    pub fn is_legal(&self, pos: Pos) -> bool {
+        // A legal click is on a tile that is adjacent to another tile it
+        // can be grouped with.
+        match self.tile(pos) {
+            None => false,
+            Some(tile) => match tile.kind {
+                Kind::Stone => false,
+                Kind::Wildcard => neighbours(pos).iter().any(|&pos| {
+                    matches!(self.tile(pos),
+                             Some(tile) if tile.kind != Kind::Stone)
+                }),
+                _ => neighbours(pos).iter().any(|&pos| {
+                    matches!(self.tile(pos),
+                             Some(other) if other.joins(Some(tile.color)))
+                }),
+            },
+        }
+    }
+
+    // Returns the group of tiles that would be removed along with the tile
+    // at the given position if it were clicked. A clicked wildcard takes
+    // the color that gives the largest group.
+    pub fn group(&self, pos: Pos) -> PosSet {
+        match self.tile(pos) {
+            None => PosSet::new(),
+            Some(tile) => match tile.kind {
+                Kind::Stone => PosSet::new(),
+                Kind::Wildcard => {
+                    let wildcards = self.flood(pos, None);
+                    let mut colors = HashSet::new();
+                    for &wildcard in wildcards.iter() {
+                        for pos in neighbours(wildcard) {
+                            if let Some(tile) = self.tile(pos) {
+                                if !matches!(
+                                    tile.kind,
+                                    Kind::Stone | Kind::Wildcard
+                                ) {
+                                    colors.insert(tile.color);
+                                }
+                            }
+                        }
+                    }
+                    let mut group = wildcards;
+                    for color in colors {
+                        let candidate = self.flood(pos, Some(color));
+                        if candidate.len() > group.len() {
+                            group = candidate;
+                        }
+                    }
+                    group
+                }
+                _ => self.flood(pos, Some(tile.color)),
+            },
+        }
+    }
+
+    fn flood(&self, pos: Pos, color: Option<u8>) -> PosSet {
+        let mut group = PosSet::new();
+        let mut todo = vec![pos];
+        while let Some(pos) = todo.pop() {
+            if group.contains(&pos) {
+                continue; // Already done
+            }
+            if let Some(tile) = self.tile(pos) {
+                if tile.joins(color) {
+                    group.insert(pos);
+                    todo.extend(neighbours(pos));
+                }
+            }
+        }
+        group
+    }
+
+    // Returns every tile that clicking the given position would remove:
+    // its group plus the blast of any bombs in it (which can set off
+    // further bombs). Returns an empty set if the click isn't legal.
+    pub fn removal(&self, pos: Pos) -> PosSet {
+        let mut removal = self.group(pos);
+        if removal.len() < 2 {
+            return PosSet::new();
+        }
+        let mut bombs: Vec<Pos> = removal
+            .iter()
+            .filter(|&&pos| self.is_kind(pos, Kind::Bomb))
+            .copied()
+            .collect();
+        while let Some(bomb) = bombs.pop() {
+            for x in (bomb.x - BOMB_RADIUS)..=(bomb.x + BOMB_RADIUS) {
+                for y in (bomb.y - BOMB_RADIUS)..=(bomb.y + BOMB_RADIUS) {
+                    let pos = Pos::new(x, y);
+                    if self.tile(pos).is_some()
+                        && removal.insert(pos)
+                        && self.is_kind(pos, Kind::Bomb)
+                    {
+                        bombs.push(pos);
+                    }
+                }
+            }
+        }
+        removal
+    }
+
+    fn is_kind(&self, pos: Pos, kind: Kind) -> bool {
+        matches!(self.tile(pos), Some(tile) if tile.kind == kind)
+    }
+
+    // Returns the points that removing the given tiles would score
+    pub fn score_for(&self, removal: &PosSet) -> u16 {
+        let count = removal.len() as u16;
+        let mut points = (((self.size.columns * self.size.rows) as f64)
+            .sqrt() as u16)
+            .saturating_add(
+                count.saturating_pow(self.maxcolors as u32 - 2),
+            );
+        for &pos in removal.iter() {
+            if self.is_kind(pos, Kind::Multiplier) {
+                points = points.saturating_mul(MULTIPLIER);
+            }
+        }
+        points
+    }
+
+    // Removes the given tiles, adding to and returning the points scored
+    pub fn remove(&mut self, removal: &PosSet) -> u16 {
+        let points = self.update_score(removal);
+        for &pos in removal.iter() {
+            self.tiles[pos.x as usize][pos.y as usize] = None;
+        }
+        self.cleared += removal.len() as u32;
+        points
+    }
+
+    fn update_score(&mut self, removal: &PosSet) -> u16 {
+        let points = self.score_for(removal);
+        self.score = self.score.saturating_add(points);
+        points
+    }
+
+    // Gravitates the tiles toward the middle; returns the path of every
+    // tile that moved, each ending where the tile now is
+    pub fn close_up(&mut self) -> Vec<Path> {
+        let mut paths = HashMap::<Pos, Path>::new(); // keyed by last pos
+        let mut moved = true;
+        let mut already_moved = PosForPos::new();
+        while moved {
+            moved = false;
+            for x in ripple(self.size.columns as usize) {
+                for y in ripple(self.size.rows as usize) {
+                    if matches!(self.tiles[x][y],
+                                Some(tile) if tile.kind != Kind::Stone)
+                    {
+                        let pos = Pos::new(x as i32, y as i32);
+                        if let Some(new_pos) =
+                            self.move_if_possible(pos, &mut already_moved)
+                        {
+                            let mut path = paths
+                                .remove(&pos)
+                                .unwrap_or_else(|| vec![pos]);
+                            path.push(new_pos);
+                            paths.insert(new_pos, path);
+                            moved = true;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        paths.into_values().collect()
+    }
+
+    // Returns the position the tile moved to if it moved
+    fn move_if_possible(
+        &mut self,
+        pos: Pos,
+        already_moved: &mut PosForPos,
+    ) -> Option<Pos> {
+        let empties = self.get_empty_neighbours(pos);
+        if !empties.is_empty() {
+            let (do_move, new_pos) = self.nearest_to_middle(pos, &empties);
+            if let Some(value) = already_moved.get(&new_pos) {
+                if value == &pos {
+                    return None; // avoid endless loop back and forth
+                }
+            }
+            if do_move {
+                let x = pos.x as usize;
+                let y = pos.y as usize;
+                self.tiles[new_pos.x as usize][new_pos.y as usize] =
+                    self.tiles[x][y];
+                self.tiles[x][y] = None;
+                already_moved.insert(pos, new_pos);
+                return Some(new_pos);
+            }
+        }
+        None
+    }
+
+    // Adds up to count new tiles, each entering at a random empty edge
+    // cell and gravitating toward the middle; returns how many were added
+    pub fn refill<R: Rng>(&mut self, count: usize, rng: &mut R) -> usize {
+        let mut added = 0;
+        while added < count {
+            match self.get_empty_edges().choose(rng) {
+                Some(pos) => {
+                    let color = rng.gen_range(0..self.maxcolors);
+                    let kind = self.specials.choose(rng);
+                    self.tiles[pos.x as usize][pos.y as usize] =
+                        Some(Tile::new(color, kind));
+                    self.close_up();
+                    added += 1;
+                }
+                None => break,
+            }
+        }
+        added
+    }
+
+    fn get_empty_edges(&self) -> Vec<Pos> {
+        let mut edges = Vec::new();
+        for x in 0..self.size.columns {
+            for y in 0..self.size.rows {
+                if (x == 0
+                    || y == 0
+                    || x + 1 == self.size.columns
+                    || y + 1 == self.size.rows)
+                    && self.tiles[x as usize][y as usize].is_none()
+                {
+                    edges.push(Pos::new(x, y));
+                }
+            }
+        }
+        edges
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.tiles.iter().all(|column| column.iter().all(Option::is_some))
+    }
+
+    // Returns one position for each group that can be removed
+    pub fn moves(&self) -> Vec<Pos> {
+        let mut done = PosSet::new();
+        let mut moves = Vec::new();
+        for x in 0..self.size.columns {
+            for y in 0..self.size.rows {
+                let pos = Pos::new(x, y);
+                if !done.contains(&pos) && self.is_legal(pos) {
+                    done.extend(self.group(pos));
+                    moves.push(pos);
+                }
+            }
+        }
+        moves
+    }
+
+    // Returns the positions of all the tiles, column by column
+    fn occupied(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.size.columns).flat_map(move |x| {
+            (0..self.size.rows)
+                .map(move |y| Pos::new(x, y))
+                .filter(|&pos| self.tile(pos).is_some())
+        })
+    }
+
+    // Returns the position of the tile nearest to the given (possibly
+    // empty) position or None if there are no tiles
+    pub fn nearest_tile(&self, pos: Pos) -> Option<Pos> {
+        self.occupied().min_by_key(|other| {
+            let (dx, dy) =
+                ((other.x - pos.x).abs(), (other.y - pos.y).abs());
+            (dx + dy, dx.max(dy))
+        })
+    }
+
+    // Returns where the focus should go from the given position, or from
+    // the middle if there's no focus; None means it should stay put.
+    // Arrows go to the nearest tile in their direction, preferring those
+    // in the same row or column, so gaps never trap the focus; if wrap is
+    // true they go round to the far side when there's no tile ahead.
+    pub fn navigate(
+        &self,
+        from: Option<Pos>,
+        navigation: Navigation,
+        wrap: bool,
+    ) -> Option<Pos> {
+        let middle = Pos::new(self.size.columns / 2, self.size.rows / 2);
+        let pos = match from {
+            Some(pos) if self.tile(pos).is_some() => pos,
+            _ => return self.nearest_tile(from.unwrap_or(middle)),
+        };
+        let in_row =
+            || self.occupied().filter(move |other| other.y == pos.y);
+        let in_column =
+            || self.occupied().filter(move |other| other.x == pos.x);
+        match navigation {
+            Navigation::Left => self.step(pos, -1, 0, wrap),
+            Navigation::Right => self.step(pos, 1, 0, wrap),
+            Navigation::Up => self.step(pos, 0, -1, wrap),
+            Navigation::Down => self.step(pos, 0, 1, wrap),
+            Navigation::RowStart => in_row().min_by_key(|other| other.x),
+            Navigation::RowEnd => in_row().max_by_key(|other| other.x),
+            Navigation::ColumnStart => {
+                in_column().min_by_key(|other| other.y)
+            }
+            Navigation::ColumnEnd => {
+                in_column().max_by_key(|other| other.y)
+            }
+            Navigation::NextGroup => self.next_group(pos),
+        }
+    }
+
+    // Returns the nearest tile in the direction given by dx or dy (one of
+    // which is 0 and the other ±1)
+    fn step(&self, pos: Pos, dx: i32, dy: i32, wrap: bool) -> Option<Pos> {
+        let (columns, rows) = (self.size.columns, self.size.rows);
+        self.occupied()
+            .filter_map(|other| {
+                // How far ahead and how far to the side the other tile is
+                let (mut ahead, aside) = if dx != 0 {
+                    ((other.x - pos.x) * dx, (other.y - pos.y).abs())
+                } else {
+                    ((other.y - pos.y) * dy, (other.x - pos.x).abs())
+                };
+                if wrap {
+                    ahead = ahead.rem_euclid(if dx != 0 {
+                        columns
+                    } else {
+                        rows
+                    });
+                }
+                if ahead > 0 {
+                    Some((other, ahead, aside))
+                } else {
+                    None
+                }
+            })
+            .min_by_key(|&(_, ahead, aside)| (ahead + 2 * aside, aside))
+            .map(|(other, _, _)| other)
+    }
+
+    // Returns the first tile (reading left to right and top down) of the
+    // legal group that comes after the given tile's group, wrapping round
+    fn next_group(&self, pos: Pos) -> Option<Pos> {
+        let columns = self.size.columns;
+        let count = columns * self.size.rows;
+        let index = |pos: Pos| pos.y * columns + pos.x;
+        let first = |pos: Pos| {
+            self.group(pos).into_iter().min_by_key(|&pos| index(pos))
+        };
+        let start = first(pos).map_or(index(pos), index);
+        (1..=count)
+            .map(|i| {
+                let i = (start + i) % count;
+                Pos::new(i % columns, i / columns)
+            })
+            .find(|&other| {
+                self.is_legal(other) && first(other) == Some(other)
+            })
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.tiles
+            .iter()
+            .flatten()
+            .filter(|tile| {
+                matches!(tile, Some(tile) if tile.kind != Kind::Stone)
+            })
+            .count() as u32
+    }
+
+    pub fn has_legal_move(&self) -> bool {
+        (0..self.size.columns).any(|x| {
+            (0..self.size.rows).any(|y| self.is_legal(Pos::new(x, y)))
+        })
+    }
+
+    // In a fixed order so that ties in nearest_to_middle() always go the
+    // same way and the same moves always give the same board
+    fn get_empty_neighbours(&self, pos: Pos) -> Vec<Pos> {
+        neighbours(pos)
+            .into_iter()
+            .filter(|&new_pos| {
+                self.contains(new_pos) && self.tile(new_pos).is_none()
+            })
+            .collect()
+    }
+
+    fn nearest_to_middle(&self, pos: Pos, empties: &[Pos]) -> (bool, Pos) {
+        let x = pos.x;
+        let y = pos.y;
+        let tile = self.tile(pos).unwrap();
+        let mid_x = self.size.columns / 2;
+        let mid_y = self.size.rows / 2;
+        let old_radius = ((mid_x - x) as f64).hypot((mid_y - y) as f64);
+        let mut shortest_radius = f64::NAN;
+        let mut radius_pos = Pos::default(); // invalid
+        for new_pos in empties.iter() {
+            let nx = new_pos.x;
+            let ny = new_pos.y;
+            if self.is_square(new_pos) {
+                let mut new_radius =
+                    ((mid_x - nx) as f64).hypot((mid_y - ny) as f64);
+                if self.has_same_color_neighbour(*new_pos, tile) {
+                    // Make same colors slightly attractive
+                    new_radius -= 0.1;
+                }
+                if !radius_pos.is_valid() || shortest_radius > new_radius {
+                    shortest_radius = new_radius;
+                    radius_pos = *new_pos;
+                }
+            }
+        }
+        if !shortest_radius.is_nan() && old_radius > shortest_radius {
+            (true, radius_pos)
+        } else {
+            (false, pos)
+        }
+    }
+
+    fn has_same_color_neighbour(&self, pos: Pos, tile: Tile) -> bool {
+        neighbours(pos).iter().any(|&pos| {
+            matches!(self.tile(pos),
+                     Some(other) if other.kind != Kind::Stone
+                         && other.color == tile.color)
+        })
+    }
+
+    fn is_square(&self, pos: &Pos) -> bool {
+        neighbours(*pos).iter().any(|&pos| self.tile(pos).is_some())
+    }
+
+    // Returns (user_won, can_move); stones don't have to be cleared
+    pub fn check_tiles(&self) -> (bool, bool) {
+        let mut count_for_color = HashMap::<u8, u32>::new();
+        let mut user_won = true;
+        let mut can_move = false;
+        let mut has_specials = false;
+        for column in 0..self.size.columns {
+            for row in 0..self.size.rows {
+                let pos = Pos::new(column, row);
+                if let Some(tile) = self.tile(pos) {
+                    match tile.kind {
+                        Kind::Stone => continue,
+                        Kind::Bomb | Kind::Wildcard => has_specials = true,
+                        _ => (),
+                    }
+                    if tile.kind != Kind::Wildcard {
+                        *count_for_color.entry(tile.color).or_insert(0) +=
+                            1;
+                    }
+                    user_won = false;
+                    if self.is_legal(pos) {
+                        can_move = true;
+                    }
+                }
+            }
+        }
+        // A lone tile of a color can only go with a bomb's or wildcard's
+        // help
+        if !has_specials && count_for_color.values().any(|&c| c == 1) {
+            can_move = false;
+        }
+        (user_won, can_move)
+    }
+}
+
+fn neighbours(pos: Pos) -> [Pos; 4] {
+    [
+        Pos::new(pos.x - 1, pos.y),
+        Pos::new(pos.x + 1, pos.y),
+        Pos::new(pos.x, pos.y - 1),
+        Pos::new(pos.x, pos.y + 1),
+    ]
+}
+
+pub fn ripple(n: usize) -> Vec<usize> {
+    // The purpose is to favor the player especially in the end game by
+    // working from the middle out.
+    let mut ripple = Vec::with_capacity(n);
+    let middle = n / 2;
+    for (i, j) in (middle..n).zip((0..middle).rev()) {
+        ripple.push(i);
+        ripple.push(j);
+    }
+    if (n % 2) != 0 {
+        ripple.push(n - 1);
+    }
+    ripple
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns a game whose tiles are given row by row: a letter is a
+    // plain tile of that color (a is 0) or a bomb if uppercase, a digit is
+    // a multiplier of that color, * is a wildcard, # is a stone and . is
+    // empty
+    fn make_game(rows: &[&str]) -> Game {
+        let size = Size::new(rows[0].len() as i32, rows.len() as i32);
+        let mut tiles = vec![vec![None; rows.len()]; rows[0].len()];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.bytes().enumerate() {
+                tiles[x][y] = match c {
+                    b'.' => None,
+                    b'#' => Some(Tile::new(0, Kind::Stone)),
+                    b'*' => Some(Tile::new(0, Kind::Wildcard)),
+                    b'A'..=b'Z' => Some(Tile::new(c - b'A', Kind::Bomb)),
+                    b'0'..=b'9' => {
+                        Some(Tile::new(c - b'0', Kind::Multiplier))
+                    }
+                    _ => Some(Tile::new(c - b'a', Kind::Plain)),
+                };
+            }
+        }
+        Game { size, maxcolors: 4, tiles, ..Default::default() }
+    }
+
+    fn go(game: &Game, from: (i32, i32), navigation: Navigation) -> Pos {
+        game.navigate(Some(Pos::new(from.0, from.1)), navigation, false)
+            .unwrap()
+    }
+
+    #[test]
+    fn arrows_cross_gaps_and_empty_columns() {
+        let game = make_game(&["a..b.", ".....", "c...d"]);
+        assert_eq!(go(&game, (0, 0), Navigation::Right), Pos::new(3, 0));
+        assert_eq!(go(&game, (3, 0), Navigation::Left), Pos::new(0, 0));
+        assert_eq!(go(&game, (3, 0), Navigation::Right), Pos::new(4, 2));
+        assert_eq!(go(&game, (0, 0), Navigation::Down), Pos::new(0, 2));
+        assert_eq!(go(&game, (0, 2), Navigation::Up), Pos::new(0, 0));
+        assert_eq!(go(&game, (0, 2), Navigation::Right), Pos::new(4, 2));
+        let nowhere = |from: (i32, i32), navigation| {
+            game.navigate(Some(Pos::new(from.0, from.1)), navigation, false)
+        };
+        assert_eq!(nowhere((4, 2), Navigation::Right), None);
+        assert_eq!(nowhere((0, 0), Navigation::Up), None);
+        assert_eq!(nowhere((0, 0), Navigation::Left), None);
+    }
+
+    #[test]
+    fn arrows_wrap_round() {
+        let game = make_game(&["a..b.", ".....", "c...d"]);
+        let wrap = |from: (i32, i32), navigation| {
+            game.navigate(Some(Pos::new(from.0, from.1)), navigation, true)
+        };
+        assert_eq!(wrap((4, 2), Navigation::Right), Some(Pos::new(0, 2)));
+        assert_eq!(wrap((0, 2), Navigation::Left), Some(Pos::new(4, 2)));
+        assert_eq!(wrap((0, 0), Navigation::Up), Some(Pos::new(0, 2)));
+        assert_eq!(wrap((0, 2), Navigation::Down), Some(Pos::new(0, 0)));
+        assert_eq!(wrap((0, 0), Navigation::Left), Some(Pos::new(3, 0)));
+    }
+
+    #[test]
+    fn home_end_page_up_and_page_down() {
+        let game = make_game(&[".a.b.", "c....", "..d.e"]);
+        assert_eq!(go(&game, (3, 0), Navigation::RowStart), Pos::new(1, 0));
+        assert_eq!(go(&game, (1, 0), Navigation::RowEnd), Pos::new(3, 0));
+        assert_eq!(go(&game, (2, 2), Navigation::RowEnd), Pos::new(4, 2));
+        assert_eq!(go(&game, (0, 1), Navigation::RowStart), Pos::new(0, 1));
+        let game = make_game(&["a.b", "..c", "d.e"]);
+        assert_eq!(
+            go(&game, (2, 2), Navigation::ColumnStart),
+            Pos::new(2, 0)
+        );
+        assert_eq!(
+            go(&game, (2, 0), Navigation::ColumnEnd),
+            Pos::new(2, 2)
+        );
+        assert_eq!(
+            go(&game, (0, 0), Navigation::ColumnEnd),
+            Pos::new(0, 2)
+        );
+    }
+
+    #[test]
+    fn focus_starts_nearest_the_middle() {
+        let game = make_game(&["a..b.", ".....", "c...d"]);
+        let pos = game.navigate(None, Navigation::Left, false);
+        assert_eq!(pos, Some(Pos::new(3, 0)));
+        let empty = make_game(&["...", "..."]);
+        assert_eq!(empty.navigate(None, Navigation::Left, false), None);
+    }
+
+    #[test]
+    fn next_group_cycles_through_the_legal_groups() {
+        // Groups start at (0,0) a, (2,0) b, (0,1) c and (1,1) d; the lone
+        // tiles and the stone are skipped
+        let game = make_game(&["aab#", "cdbe", "cddf"]);
+        let mut pos = Pos::new(1, 0);
+        let mut seen = vec![];
+        for _ in 0..5 {
+            pos = game
+                .navigate(Some(pos), Navigation::NextGroup, false)
+                .unwrap();
+            seen.push(pos);
+        }
+        assert_eq!(
+            seen,
+            [
+                Pos::new(2, 0),
+                Pos::new(0, 1),
+                Pos::new(1, 1),
+                Pos::new(0, 0),
+                Pos::new(2, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn selection_stays_on_a_tile_after_close_up() {
+        let mut game = make_game(&["aab", "cdb", "cdd"]);
+        let removal = game.removal(Pos::new(1, 1));
+        assert_eq!(removal.len(), 3);
+        game.remove(&removal);
+        game.close_up();
+        for &selected in removal.iter() {
+            let nearest = game.nearest_tile(selected).unwrap();
+            assert!(game.tile(nearest).is_some(), "{selected:?}");
+            let pos =
+                game.navigate(Some(selected), Navigation::Right, false);
+            assert!(game.tile(pos.unwrap()).is_some(), "{selected:?}");
+        }
+        // Once every tile has gone there's nothing to select
+        for x in 0..3 {
+            for y in 0..3 {
+                game.tiles[x][y] = None;
+            }
+        }
+        assert_eq!(game.nearest_tile(Pos::new(1, 1)), None);
+        assert_eq!(
+            game.navigate(Some(Pos::new(1, 1)), Navigation::Down, false),
+            None
+        );
+    }
+
+    fn positions(positions: &[(i32, i32)]) -> PosSet {
+        positions.iter().map(|&(x, y)| Pos::new(x, y)).collect()
+    }
+
+    #[test]
+    fn bombs_blast_their_neighbours_and_set_off_other_bombs() {
+        let game = make_game(&["aAb#c", "c.Bdd", "ccccd"]);
+        assert_eq!(
+            game.removal(Pos::new(0, 0)),
+            positions(&[
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (3, 0), // a stone, which only a bomb can remove
+                (0, 1),
+                (2, 1),
+                (3, 1),
+                (1, 2),
+                (2, 2),
+                (3, 2),
+            ])
+        );
+        // A bomb on its own is like any other lone tile
+        let game = make_game(&["aBa"]);
+        assert!(!game.is_legal(Pos::new(1, 0)));
+        assert!(game.removal(Pos::new(1, 0)).is_empty());
+    }
+
+    #[test]
+    fn wildcards_join_groups_of_different_colors() {
+        let game = make_game(&["aa*bbb"]);
+        assert_eq!(
+            game.removal(Pos::new(0, 0)),
+            positions(&[(0, 0), (1, 0), (2, 0)])
+        );
+        assert_eq!(
+            game.removal(Pos::new(5, 0)),
+            positions(&[(2, 0), (3, 0), (4, 0), (5, 0)])
+        );
+        // A clicked wildcard takes the color of the largest group
+        assert_eq!(
+            game.removal(Pos::new(2, 0)),
+            positions(&[(2, 0), (3, 0), (4, 0), (5, 0)])
+        );
+        // A wildcard bridges two tiles of the same color
+        let game = make_game(&["a*a", "b.b"]);
+        assert_eq!(game.removal(Pos::new(0, 0)).len(), 3);
+        // Wildcards only need a neighbour that isn't a stone
+        let game = make_game(&["#*#", "#b#"]);
+        assert!(game.is_legal(Pos::new(1, 0)));
+        assert!(!make_game(&["#*#"]).is_legal(Pos::new(1, 0)));
+    }
+
+    #[test]
+    fn stones_never_move_or_count_as_remaining() {
+        let mut game = make_game(&["ab.", ".#.", "b.a"]);
+        assert!(!game.is_legal(Pos::new(1, 1)));
+        assert!(game.group(Pos::new(1, 1)).is_empty());
+        assert_eq!(game.remaining(), 4);
+        game.close_up();
+        assert_eq!(game.tile(Pos::new(1, 1)).unwrap().kind, Kind::Stone);
+        assert_eq!(game.remaining(), 4);
+        let stones = make_game(&["#.", ".#"]);
+        assert_eq!(stones.remaining(), 0);
+        assert_eq!(stones.check_tiles(), (true, false)); // won
+    }
+
+    #[test]
+    fn multipliers_multiply_the_score() {
+        // A 4 x 1 board with 4 colors scores sqrt(4) + count²
+        let game = make_game(&["aabb"]);
+        assert_eq!(game.score_for(&game.removal(Pos::new(0, 0))), 6);
+        let game = make_game(&["a0bb"]);
+        assert_eq!(game.score_for(&game.removal(Pos::new(0, 0))), 12);
+        assert_eq!(game.score_for(&game.removal(Pos::new(2, 0))), 6);
+        let mut game = make_game(&["00bb"]);
+        let points = game.remove(&game.removal(Pos::new(1, 0)));
+        assert_eq!(points, 24);
+        assert_eq!(game.score, 24);
+        assert_eq!(game.cleared, 2);
+    }
+}
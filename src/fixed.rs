@@ -1,7 +1,11 @@
 // Copyright © 2021-23 Mark Summerfield. All rights reserved.
 // License: GPLv3
 
-use crate::util::capitalize_first;
+use crate::engine::Navigation;
+use crate::i18n::{self, tr, Language};
+use crate::i18n_de;
+use crate::keys::{Command, KeyBindings};
+use crate::util::{capitalize_first, Pos};
 use chrono::prelude::*;
 use fltk::enums::Color;
 use std::env;
@@ -11,15 +15,20 @@ pub static APPNAME: &str = "Gravitate";
 pub static VERSION: &str = "8.0.8";
 pub const ABOUT_ICON: &str = include_str!("../images/about.svg");
 pub const HELP_ICON: &str = include_str!("../images/help.svg");
+pub const HIGHSCORES_ICON: &str = include_str!("../images/highscores.svg");
 pub const ICON: &str = include_str!("../images/gravitate.svg");
+pub const KEYS_ICON: &str = include_str!("../images/keys.svg");
 pub const NEW_ICON: &str = include_str!("../images/new.svg");
 pub const OPTIONS_ICON: &str = include_str!("../images/options.svg");
+pub const PAUSE_ICON: &str = include_str!("../images/pause.svg");
+pub const PROFILES_ICON: &str = include_str!("../images/profiles.svg");
 pub const QUIT_ICON: &str = include_str!("../images/quit.svg");
 pub const PAD: i32 = 6;
 pub const TOOLBUTTON_SIZE: i32 = 28;
 pub const TOOLBAR_HEIGHT: i32 = ((TOOLBUTTON_SIZE * 3) / 2) + (2 * PAD);
 pub const BUTTON_HEIGHT: i32 = 30;
 pub const BUTTON_WIDTH: i32 = 70;
+pub const PREVIEW_HEIGHT: i32 = 60;
 pub const SCALE_MIN: f32 = 0.5;
 pub const SCALE_MAX: f32 = 3.5;
 pub const SIZE_MIN: u8 = 5;
@@ -27,8 +36,15 @@ pub const SIZE_MAX: u8 = 30;
 pub const COLORS_MIN: u8 = 3;
 pub const DELAY_MS_MIN: u16 = 0;
 pub const DELAY_MS_MAX: u16 = 1000;
+pub const DISTANCE_MIN: u8 = 0; // CIEDE2000
+pub const DISTANCE_MAX: u8 = 50; // CIEDE2000
+pub const SPECIALS_MIN: u8 = 0; // percent
+pub const SPECIALS_MAX: u8 = 20; // percent
 pub const MESSAGE_DELAY: f64 = 10.0; // seconds
 pub const TINY_DELAY: f64 = 0.005; // seconds
+pub const TICK: f64 = 0.1; // seconds
+pub const TIME_ATTACK_SECS: f64 = 120.0;
+pub const TIME_PER_TILE: f64 = 0.5; // seconds
 
 pub const MAROON: Color = Color::from_hex(0x800000);
 pub const BROWN: Color = Color::from_hex(0x9A6324);
@@ -67,27 +83,79 @@ pub fn initialize_colors() {
 pub enum Action {
     New,
     Options,
+    KeyBindings,
+    Profiles,
     About,
     Help,
     Quit,
-    MoveUp,
-    MoveDown,
-    MoveLeft,
-    MoveRight,
+    Pause,                // pause or resume
+    AutoPause,            // the main window lost the focus or was minimized
+    Navigate(Navigation), // move the keyboard focus
     ClickTile,
+    Hover, // the mouse entered, moved over or left the board
     PressTile,
     DeleteAdjoining,
     CloseUp,
-    Redraw,
+    Animate, // the next frame of tiles sliding
     UpdatedScore(u16),
+    Removed(u16), // the number of tiles removed
     GameOver,
+    Tick,
+    HighScores,
+    ComputerMove(Pos, u32), // the position to click and the game's ID
+    Race,
+    RaceUpdate, // a racer has joined, moved or left
+    SolvableSeed(Option<u64>, u32), // the seed found and the search's ID
+    Rated(u8, u32), // the board's rating and the game's ID
 }
 
-pub enum Arrow {
-    Left,
-    Right,
-    Up,
-    Down,
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Variant {
+    Classic,
+    TimeAttack,
+    Endless,
+    HotSeat,
+    Versus,
+}
+
+impl Variant {
+    pub const ALL: [Variant; 5] = [
+        Variant::Classic,
+        Variant::TimeAttack,
+        Variant::Endless,
+        Variant::HotSeat,
+        Variant::Versus,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Variant::Classic => "Classic",
+            Variant::TimeAttack => "Time Attack",
+            Variant::Endless => "Endless",
+            Variant::HotSeat => "Hot Seat",
+            Variant::Versus => "Versus Computer",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Variant> {
+        Variant::ALL.iter().find(|variant| variant.name() == name).copied()
+    }
+}
+
+// Returns the help text in the current language with the given key
+// bindings' keys in the text and a table of them all
+pub fn help_html(keys: &KeyBindings) -> String {
+    let html = match i18n::language() {
+        Language::English => HELP_HTML,
+        Language::German => i18n_de::HELP_HTML,
+    };
+    html.replace("<!--KEYS-->", &keys.to_html())
+        .replace("<!--CLICK-->", &keys.keys_html(Command::Click))
+        .replace(
+            "<!--KEYBINDINGS-->",
+            &keys.keys_html(Command::KeyBindings),
+        )
+        .replace("<!--RACE-->", &keys.keys_html(Command::Race))
 }
 
 pub fn about_html() -> String {
@@ -100,7 +168,7 @@ pub fn about_html() -> String {
     format!(
         "<p><center><font size=6 color=navy><b>{}</b> v{}</font>
 </center></p>
-<p><center><font color=navy size=5>A TileFall/SameGame-like game.</font>
+<p><center><font color=navy size=5>{}</font>
 </center></p>
 <p><center><font size=4>
 <a href=\"http://www.qtrac.eu/gravitate.html\">www.qtrac.eu/gravitate.html</a>
@@ -108,15 +176,18 @@ pub fn about_html() -> String {
 <p><center>
 <font size=4 color=green>
 Copyright © {} Mark Summerfield.<br>
-All rights reserved.<br>
-License: GPLv3.</font>
+{}<br>
+{}</font>
 </center></p>
 <p><center><font size=4 color=#555>
 Rust {} • fltk-rs {} • FLTK {} • {}/{}
 </font></center></p>",
         APPNAME,
         VERSION,
+        tr("A TileFall/SameGame-like game."),
         year,
+        tr("All rights reserved."),
+        tr("License: GPLv3."),
         rustc_version_runtime::version(),
         fltk::app::crate_version(),
         fltk::app::version_str(),
@@ -125,7 +196,7 @@ Rust {} • fltk-rs {} • FLTK {} • {}/{}
     )
 }
 
-pub static HELP_HTML: &str = "<body>
+static HELP_HTML: &str = "<body>
 <p><center><font color=navy size=6><b>Gravitate</b></font></center></p>
 <font color=blue size=5>The purpose of the game is to remove all the
 tiles.</font>
@@ -139,23 +210,119 @@ of the same color does nothing.)</i> The more tiles that are removed in one
 go, the higher the score.
 </font>
 </p>
-<table border=1 align=center>
-<font size=4 color=blue>
-<tr><th>Key</th><th>Action</th></tr>
-<tr><td><b>a</b></td><td>Show About box</td></tr>
-<tr><td><b>h</b> or <b>F1</b></td><td>Show this Help window</td></tr>
-<tr><td><b>n</b></td><td>New Game</td></tr>
-<tr><td><b>o</b></td><td>View or Edit Options</td></tr>
-<tr><td><b>q</b> or <b>Esc</b></td><td>Quit</td></tr>
-<tr><td><b>←</b></td><td>Move the focus left</td></tr>
-<tr><td><b>→</b></td><td>Move the focus right</td></tr>
-<tr><td><b>↑</b></td><td>Move the focus up</td></tr>
-<tr><td><b>↓</b></td><td>Move the focus down</td></tr>
-<tr><td><b>Space</b></td><td>Click the focused tile</td></tr>
+<p>
+<font color=#008000 size=4>
+If enabled in Options, some tiles are special. A <b>bomb</b> (black ball)
+also removes every tile around it when its group is removed. A
+<b>wildcard</b> (four-colored disc) joins any adjoining color. A
+<b>stone</b> (sunken gray) never moves and only a bomb can remove it; stones
+don't have to be removed to win. A <b>multiplier</b> (×2) doubles the score
+of the tiles removed with it.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+The <b>Difficulty Level</b> in Options sets the board's size, colors and
+special tiles together: Beginner, Normal, Expert or Marathon, or Custom
+once any of them is changed. High scores and statistics are kept for each
+level so that like is compared with like. If <b>Check Boards Are
+Solvable</b> is checked each new board is only used once it has been shown
+that it can be cleared, which is marked ✓ beside the time; these boards
+have their own high scores. Each board is rated from one star (easy) to
+five (hard) by playing it out many times before it is shown; the rating is
+beside the time and recorded with each high score. The <b>Jumble</b> sets
+how each new board's colors are laid out: Uniform picks each tile's color
+at random; Balanced has as many tiles of each color; Clustered makes bigger
+groups and Scattered smaller ones; and Mirror and Rotational make boards
+that are symmetric left to right or upside down. Boards that aren't laid
+out uniformly have their own high scores too.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+If some colors are hard to tell apart, choose a <b>Palette</b> designed for
+deuteranopia, protanopia or tritanopia in Options, and check
+<b>Symbols</b> to show a shape or letter on each tile that is unique to its
+color. The colors used are always the most distinguishable ones available;
+Options warns if there aren't enough that differ by at least the <b>Color
+Distance</b>. The <b>Theme</b> sets the board's colors, fonts and tile
+style: besides the Light, Dark and High-Contrast themes, any theme
+<tt>.ini</tt> files in the themes directory (shown in the Theme tooltip) are
+offered too. A <b>Tile Set</b> draws the tiles as images: Gems is built in,
+and each subdirectory of the tile sets directory (shown in the Tile Set
+tooltip) with a <tt>manifest.ini</tt> listing an SVG or PNG image per color
+is offered too. Hovering over a tile (or moving the focus to it) outlines
+the tiles clicking it would remove and shows how many points they would
+score; a dashed outline means that clicking it isn't legal. Removed tiles
+flash and shrink away while the points they scored float up; click or press
+<!--CLICK--> to skip this. Check <b>Reduced Motion</b> to have tiles vanish
+at once and move straight to their new places rather than slide there, and
+<b>Focus Wraps</b> to have the arrow keys go round to the far side of the
+board when there's no tile ahead.
 </font>
-</table>
+</p>
+<p>
+<font color=#008000 size=4>
+Choose the <b>Language</b> of the user interface in Options: English or
+German (Deutsch); by default it's the system's language if available.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Click <b>Profiles</b> to add, delete or switch between players. Each
+profile has its own options, key bindings, high scores and statistics
+(shown in High Scores), and a one-player game that is still in progress
+when switching or quitting is carried on the next time its profile is used.
+The active profile's name is shown in the window's title and recorded with
+each high score; the window's size, position, scale and language are shared
+by every profile.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Options are saved in the system's configuration folder and high scores,
+statistics and saved games in its data folder. To keep everything beside
+the program instead, e.g., on a USB stick, put a file called
+<tt>portable</tt> beside it or set the <tt>GRAVITATE_PORTABLE</tt>
+environment variable to 1.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Every key below can be changed in <b>Key Bindings</b> (the keyboard
+toolbar button or <!--KEYBINDINGS-->), either one by one or by choosing a
+preset such as Vi, which moves the focus with <b>h</b>, <b>j</b>, <b>k</b>
+and <b>l</b>. A key can't be bound to more than one action.
+</font>
+</p>
+<!--KEYS-->
 <font size=4 color=#008000>
 Gravitate works like TileFall and the SameGame except that instead of tiles
 falling to the bottom and moving off to the left, they “gravitate” to the
 middle.</font>
+<p>
+<font size=4 color=#008000>
+In the <b>Time Attack</b> game (chosen in Options) you have two minutes,
+with more time added for every tile removed. In the <b>Endless</b> game new
+tiles enter from the edges after every move, and the game only ends when the
+board is full and no tile can be removed. In the <b>Hot Seat</b> game two
+players take turns on the same board and whoever scores the most wins. The
+<b>Versus Computer</b> game is the same except that the computer takes every
+other turn, playing at the strength set in Options.</font>
+</p>
+<p>
+<font size=4 color=#008000>
+Click <b>Pause</b> to hide the board and stop the clock until it is clicked
+again; the game also pauses itself whenever its window is minimized or
+another application has the focus.</font>
+</p>
+<p>
+<font size=4 color=#008000>
+To <b>race</b> on the local network, one player presses <!--RACE--> to host
+and the others press <!--RACE--> to join using the host's address (e.g.,
+<tt>192.168.1.5:9527</tt>). Once everyone has joined the host presses
+<!--RACE--> again to start: everyone gets the same board and can see each
+other's progress. The first to clear their board wins; if everyone gets
+stuck, whoever has the fewest tiles left wins.</font>
+</p>
 </body>";
@@ -0,0 +1,406 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// The ways a new board's tiles can be laid out. Each depends only on the
+// rng so that a setup's seed always gives the same board.
+
+use crate::engine::{Size, Specials, Tile, Tiles};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const CLUSTER_BIAS: f64 = 0.45; // the chance of copying a neighbour
+const SCATTER_BIAS: f64 = 0.8; // the chance of avoiding the neighbours
+
+pub trait BoardGenerator {
+    // Returns the tiles, column by column
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles;
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Generator {
+    #[default]
+    Uniform,
+    Balanced,
+    Clustered,
+    Scattered,
+    Mirror,
+    Rotational,
+}
+
+impl Generator {
+    pub const ALL: [Generator; 6] = [
+        Generator::Uniform,
+        Generator::Balanced,
+        Generator::Clustered,
+        Generator::Scattered,
+        Generator::Mirror,
+        Generator::Rotational,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Generator::Uniform => "Uniform",
+            Generator::Balanced => "Balanced",
+            Generator::Clustered => "Clustered",
+            Generator::Scattered => "Scattered",
+            Generator::Mirror => "Mirror",
+            Generator::Rotational => "Rotational",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Generator> {
+        Generator::ALL
+            .iter()
+            .find(|generator| generator.name() == name)
+            .copied()
+    }
+}
+
+impl BoardGenerator for Generator {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        match self {
+            Generator::Uniform => {
+                Uniform.tiles(size, maxcolors, specials, rng)
+            }
+            Generator::Balanced => {
+                Balanced.tiles(size, maxcolors, specials, rng)
+            }
+            Generator::Clustered => {
+                Clustered.tiles(size, maxcolors, specials, rng)
+            }
+            Generator::Scattered => {
+                Scattered.tiles(size, maxcolors, specials, rng)
+            }
+            Generator::Mirror => {
+                Mirror.tiles(size, maxcolors, specials, rng)
+            }
+            Generator::Rotational => {
+                Rotational.tiles(size, maxcolors, specials, rng)
+            }
+        }
+    }
+}
+
+// Each tile's color is picked at random
+pub struct Uniform;
+
+impl BoardGenerator for Uniform {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let mut tiles = Vec::with_capacity(size.columns as usize);
+        for column in 0..size.columns {
+            tiles.push(Vec::with_capacity(size.rows as usize));
+            for _ in 0..size.rows {
+                let color = rng.gen_range(0..maxcolors);
+                let kind = specials.choose(rng);
+                tiles[column as usize].push(Some(Tile::new(color, kind)));
+            }
+        }
+        tiles
+    }
+}
+
+// Every color has the same number of tiles (give or take one)
+pub struct Balanced;
+
+impl BoardGenerator for Balanced {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let count = (size.columns * size.rows) as usize;
+        let mut colors: Vec<u8> =
+            (0..count).map(|i| (i % maxcolors as usize) as u8).collect();
+        colors.shuffle(rng);
+        let colors = colors
+            .chunks(size.rows as usize)
+            .map(|column| column.to_vec())
+            .collect();
+        with_kinds(colors, specials, rng)
+    }
+}
+
+// Tiles tend to have the same color as a neighbour, which makes for
+// bigger groups and easier boards
+pub struct Clustered;
+
+impl BoardGenerator for Clustered {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let colors = by_neighbours(
+            size,
+            |neighbours, rng| {
+                if !neighbours.is_empty() && rng.gen_bool(CLUSTER_BIAS) {
+                    *neighbours.choose(rng).unwrap()
+                } else {
+                    rng.gen_range(0..maxcolors)
+                }
+            },
+            rng,
+        );
+        with_kinds(colors, specials, rng)
+    }
+}
+
+// Tiles tend to differ from their neighbours, which makes for smaller
+// groups and harder boards
+pub struct Scattered;
+
+impl BoardGenerator for Scattered {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let colors = by_neighbours(
+            size,
+            |neighbours, rng| {
+                let others: Vec<u8> = (0..maxcolors)
+                    .filter(|color| !neighbours.contains(color))
+                    .collect();
+                if !others.is_empty() && rng.gen_bool(SCATTER_BIAS) {
+                    *others.choose(rng).unwrap()
+                } else {
+                    rng.gen_range(0..maxcolors)
+                }
+            },
+            rng,
+        );
+        with_kinds(colors, specials, rng)
+    }
+}
+
+// The right half is the left half reflected
+pub struct Mirror;
+
+impl BoardGenerator for Mirror {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let mut tiles = Uniform.tiles(size, maxcolors, specials, rng);
+        let columns = size.columns as usize;
+        for column in 0..columns / 2 {
+            tiles[columns - 1 - column] = tiles[column].clone();
+        }
+        tiles
+    }
+}
+
+// The board looks the same when turned upside down
+pub struct Rotational;
+
+impl BoardGenerator for Rotational {
+    fn tiles<R: Rng>(
+        &self,
+        size: Size,
+        maxcolors: u8,
+        specials: Specials,
+        rng: &mut R,
+    ) -> Tiles {
+        let mut tiles = Uniform.tiles(size, maxcolors, specials, rng);
+        let columns = size.columns as usize;
+        let rows = size.rows as usize;
+        for column in 0..columns {
+            for row in 0..rows {
+                // Copy the first half (reading column by column) over the
+                // second
+                if column * rows + row < (columns * rows) / 2 {
+                    tiles[columns - 1 - column][rows - 1 - row] =
+                        tiles[column][row];
+                }
+            }
+        }
+        tiles
+    }
+}
+
+// Returns the colors picked for each cell in turn, column by column,
+// given the colors of its neighbours that have already been picked
+// (above and to the left)
+fn by_neighbours<R: Rng>(
+    size: Size,
+    pick: impl Fn(&[u8], &mut R) -> u8,
+    rng: &mut R,
+) -> Vec<Vec<u8>> {
+    let mut colors: Vec<Vec<u8>> =
+        Vec::with_capacity(size.columns as usize);
+    for column in 0..size.columns as usize {
+        let mut current = Vec::with_capacity(size.rows as usize);
+        for row in 0..size.rows as usize {
+            let mut neighbours = vec![];
+            if column > 0 {
+                neighbours.push(colors[column - 1][row]);
+            }
+            if row > 0 {
+                neighbours.push(current[row - 1]);
+            }
+            current.push(pick(&neighbours, rng));
+        }
+        colors.push(current);
+    }
+    colors
+}
+
+// Returns the tiles with the given colors, each of a kind chosen at
+// random
+fn with_kinds<R: Rng>(
+    colors: Vec<Vec<u8>>,
+    specials: Specials,
+    rng: &mut R,
+) -> Tiles {
+    colors
+        .into_iter()
+        .map(|column| {
+            column
+                .into_iter()
+                .map(|color| Some(Tile::new(color, specials.choose(rng))))
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const SEEDS: u64 = 20;
+
+    fn tiles(generator: Generator, size: Size, seed: u64) -> Tiles {
+        let mut rng = StdRng::seed_from_u64(seed);
+        generator.tiles(size, 5, Specials::default(), &mut rng)
+    }
+
+    // Returns the share of horizontally or vertically adjoining tiles
+    // that have the same color over many boards
+    fn same_neighbour_rate(generator: Generator) -> f64 {
+        let size = Size::new(20, 20);
+        let (mut same, mut pairs) = (0, 0);
+        for seed in 0..SEEDS {
+            let tiles = tiles(generator, size, seed);
+            let color = |x: usize, y: usize| tiles[x][y].unwrap().color;
+            for x in 0..size.columns as usize {
+                for y in 0..size.rows as usize {
+                    for (nx, ny) in [(x + 1, y), (x, y + 1)] {
+                        if nx < size.columns as usize
+                            && ny < size.rows as usize
+                        {
+                            pairs += 1;
+                            if color(x, y) == color(nx, ny) {
+                                same += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        same as f64 / pairs as f64
+    }
+
+    #[test]
+    fn balanced_counts_differ_by_at_most_one() {
+        for (columns, rows) in [(9, 9), (12, 7), (20, 20)] {
+            for seed in 0..SEEDS {
+                let tiles = tiles(
+                    Generator::Balanced,
+                    Size::new(columns, rows),
+                    seed,
+                );
+                let mut counts = [0; 5];
+                for tile in tiles.iter().flatten().flatten() {
+                    counts[tile.color as usize] += 1;
+                }
+                let most = counts.iter().max().unwrap();
+                let least = counts.iter().min().unwrap();
+                assert!(most - least <= 1, "{columns}x{rows}: {counts:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn clustered_and_scattered_change_the_same_neighbour_rate() {
+        let uniform = same_neighbour_rate(Generator::Uniform);
+        let clustered = same_neighbour_rate(Generator::Clustered);
+        let scattered = same_neighbour_rate(Generator::Scattered);
+        // With 5 colors a uniform board's rate is about 0.2
+        assert!((uniform - 0.2).abs() < 0.03, "uniform {uniform}");
+        assert!(clustered > uniform + 0.1, "clustered {clustered}");
+        assert!(scattered < uniform - 0.1, "scattered {scattered}");
+    }
+
+    #[test]
+    fn mirror_is_symmetric_left_to_right() {
+        for (columns, rows) in [(9, 9), (10, 7)] {
+            let tiles =
+                tiles(Generator::Mirror, Size::new(columns, rows), 7);
+            let columns = columns as usize;
+            for column in 0..columns {
+                assert_eq!(tiles[column], tiles[columns - 1 - column]);
+            }
+        }
+    }
+
+    #[test]
+    fn rotational_is_symmetric_upside_down() {
+        for (columns, rows) in [(9, 9), (10, 7), (8, 6)] {
+            let tiles =
+                tiles(Generator::Rotational, Size::new(columns, rows), 7);
+            let (columns, rows) = (columns as usize, rows as usize);
+            for column in 0..columns {
+                for row in 0..rows {
+                    assert_eq!(
+                        tiles[column][row],
+                        tiles[columns - 1 - column][rows - 1 - row]
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_board() {
+        let size = Size::new(12, 9);
+        for generator in Generator::ALL {
+            assert_eq!(
+                tiles(generator, size, 42),
+                tiles(generator, size, 42)
+            );
+            assert_ne!(
+                tiles(generator, size, 42),
+                tiles(generator, size, 43)
+            );
+        }
+    }
+}
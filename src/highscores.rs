@@ -0,0 +1,302 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use crate::i18n::{self, tr};
+use crate::preset::Preset;
+use crate::rating;
+use chrono::prelude::*;
+use std::cmp;
+use std::collections::BTreeMap;
+
+pub const MAX_ENTRIES: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub score: u16,
+    pub date: String,
+    pub rating: u8, // the board's difficulty in stars; 0 if unknown
+    pub name: String, // the profile's; empty for older scores
+}
+
+// A table of the best scores for each category (see preset::category())
+#[derive(Clone, Debug, Default)]
+pub struct HighScores {
+    tables: BTreeMap<String, Vec<Entry>>,
+}
+
+impl HighScores {
+    pub fn best(&self, category: &str) -> u16 {
+        self.tables
+            .get(category)
+            .and_then(|entries| entries.first())
+            .map_or(0, |entry| entry.score)
+    }
+
+    // Records the score (and the board's rating and who made it) if it
+    // makes the table; returns true if it is the new best
+    pub fn add(
+        &mut self,
+        category: &str,
+        score: u16,
+        rating: u8,
+        name: &str,
+    ) -> bool {
+        if score == 0 {
+            return false;
+        }
+        let is_best = score > self.best(category);
+        let entries = self.tables.entry(category.to_string()).or_default();
+        let index = entries
+            .iter()
+            .position(|entry| entry.score < score)
+            .unwrap_or(entries.len());
+        if index < MAX_ENTRIES {
+            let date = Local::now().format("%Y-%m-%d").to_string();
+            let name = name.to_string();
+            entries.insert(index, Entry { score, date, rating, name });
+            entries.truncate(MAX_ENTRIES);
+        }
+        is_best
+    }
+
+    pub fn read(&mut self, properties: &ini::Properties) {
+        for (category, value) in properties.iter() {
+            let mut entries = Vec::new();
+            for item in value.split(';') {
+                let mut parts = item.split_whitespace();
+                if let Some(Ok(score)) = parts.next().map(|s| s.parse()) {
+                    let date = parts.next().unwrap_or_default().to_string();
+                    let rating = parts
+                        .next()
+                        .and_then(|rating| rating.parse().ok())
+                        .unwrap_or_default();
+                    let name = parts.collect::<Vec<&str>>().join(" ");
+                    entries.push(Entry { score, date, rating, name });
+                }
+            }
+            entries.sort_by_key(|entry| cmp::Reverse(entry.score));
+            entries.truncate(MAX_ENTRIES);
+            if !entries.is_empty() {
+                self.tables.insert(category.to_string(), entries);
+            }
+        }
+    }
+
+    pub fn write(&self, ini: &mut ini::Ini, section: &str) {
+        for (category, entries) in self.tables.iter() {
+            let value = entries
+                .iter()
+                .map(|entry| {
+                    format!(
+                        "{} {} {} {}",
+                        entry.score, entry.date, entry.rating, entry.name
+                    )
+                    .trim_end()
+                    .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(";");
+            ini.with_section(Some(section)).set(category, value);
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = String::from("<body>");
+        if self.tables.is_empty() {
+            html.push_str(&format!(
+                "<p><center><font size=4>{}</font></center></p>",
+                tr("No high scores yet.")
+            ));
+        }
+        for (category, entries) in self.tables.iter() {
+            html.push_str(&format!(
+                "<p><center><font color=navy size=5><b>{}</b>\
+                </font></center></p>
+<table border=1 align=center><font size=4>
+<tr><th>#</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+                category
+                    .split('/')
+                    .map(tr)
+                    .collect::<Vec<&str>>()
+                    .join(" — "),
+                tr("Score"),
+                tr("Date"),
+                tr("Rating"),
+                tr("Player")
+            ));
+            for (i, entry) in entries.iter().enumerate() {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td align=right>{}</td><td>{}</td>\
+                    <td>{}</td><td>{}</td></tr>",
+                    i + 1,
+                    i18n::number(entry.score),
+                    entry.date,
+                    if entry.rating > 0 {
+                        rating::stars(entry.rating)
+                    } else {
+                        String::new()
+                    },
+                    entry.name
+                ));
+            }
+            html.push_str("</font></table>");
+        }
+        html.push_str("</body>");
+        html
+    }
+}
+
+// The bests for the Endless game beyond its score
+#[derive(Clone, Debug, Default)]
+pub struct EndlessRecords {
+    pub most_cleared: u32,
+    pub longest_secs: u32,
+}
+
+impl EndlessRecords {
+    // Records any new bests; returns true if there were any
+    pub fn update(&mut self, cleared: u32, secs: u32) -> bool {
+        let mut updated = false;
+        if cleared > self.most_cleared {
+            self.most_cleared = cleared;
+            updated = true;
+        }
+        if secs > self.longest_secs {
+            self.longest_secs = secs;
+            updated = true;
+        }
+        updated
+    }
+
+    pub fn read(&mut self, properties: &ini::Properties) {
+        if let Some(value) = properties.get(MOST_CLEARED_KEY) {
+            self.most_cleared = value.parse().unwrap_or_default();
+        }
+        if let Some(value) = properties.get(LONGEST_SECS_KEY) {
+            self.longest_secs = value.parse().unwrap_or_default();
+        }
+    }
+
+    pub fn write(&self, ini: &mut ini::Ini, section: &str) {
+        ini.with_section(Some(section))
+            .set(MOST_CLEARED_KEY, self.most_cleared.to_string())
+            .set(LONGEST_SECS_KEY, self.longest_secs.to_string());
+    }
+
+    pub fn to_html(&self) -> String {
+        format!(
+            "<p><center><font color=navy size=5><b>{}</b>\
+            </font></center></p>
+<table border=1 align=center><font size=4>
+<tr><td>{}</td><td align=right>{}</td></tr>
+<tr><td>{}</td><td align=right>{}:{:02}</td></tr>
+</font></table>",
+            tr("Endless Records"),
+            tr("Most tiles cleared"),
+            i18n::number(self.most_cleared),
+            tr("Longest survival"),
+            self.longest_secs / 60,
+            self.longest_secs % 60
+        )
+    }
+}
+
+// How all of a profile's one-player games have gone for each preset
+#[derive(Clone, Debug, Default)]
+pub struct Statistics {
+    tallies: BTreeMap<Preset, Tally>,
+}
+
+impl Statistics {
+    pub fn update(
+        &mut self,
+        preset: Preset,
+        won: bool,
+        cleared: u32,
+        score: u16,
+    ) {
+        let tally = self.tallies.entry(preset).or_default();
+        tally.played += 1;
+        if won {
+            tally.won += 1;
+        }
+        tally.cleared = tally.cleared.saturating_add(cleared);
+        tally.points += score as u64;
+    }
+
+    // Each preset's tally is written as "played won tilesRemoved points"
+    pub fn read(&mut self, properties: &ini::Properties) {
+        for (key, value) in properties.iter() {
+            if let Some(preset) = Preset::from_name(key) {
+                let parts: Vec<u64> = value
+                    .split_whitespace()
+                    .map(|part| part.parse().unwrap_or_default())
+                    .collect();
+                let part = |i: usize| parts.get(i).copied().unwrap_or(0);
+                let tally = Tally {
+                    played: part(0) as u32,
+                    won: part(1) as u32,
+                    cleared: part(2) as u32,
+                    points: part(3),
+                };
+                self.tallies.insert(preset, tally);
+            }
+        }
+    }
+
+    pub fn write(&self, ini: &mut ini::Ini, section: &str) {
+        for (preset, tally) in self.tallies.iter() {
+            ini.with_section(Some(section)).set(
+                preset.name(),
+                format!(
+                    "{} {} {} {}",
+                    tally.played, tally.won, tally.cleared, tally.points
+                ),
+            );
+        }
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<p><center><font color=navy size=5><b>{}</b>\
+            </font></center></p>
+<table border=1 align=center><font size=4>
+<tr><th>{}</th><th>{}</th><th>{}</th><th>{}</th><th>{}</th></tr>",
+            tr("Statistics"),
+            tr("Difficulty"),
+            tr("Games played"),
+            tr("Games won"),
+            tr("Tiles removed"),
+            tr("Average score"),
+        );
+        for (preset, tally) in self.tallies.iter() {
+            let average = tally.points.checked_div(tally.played as u64);
+            let percent =
+                (tally.won as u64 * 100).checked_div(tally.played as u64);
+            html.push_str(&format!(
+                "<tr><td>{}</td><td align=right>{}</td>\
+                <td align=right>{} ({}%)</td><td align=right>{}</td>\
+                <td align=right>{}</td></tr>",
+                tr(preset.name()),
+                i18n::number(tally.played),
+                i18n::number(tally.won),
+                percent.unwrap_or_default(),
+                i18n::number(tally.cleared),
+                i18n::number(average.unwrap_or_default())
+            ));
+        }
+        html.push_str("</font></table>");
+        html
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct Tally {
+    played: u32,
+    won: u32,
+    cleared: u32, // the number of tiles removed
+    points: u64,
+}
+
+static MOST_CLEARED_KEY: &str = "mostCleared";
+static LONGEST_SECS_KEY: &str = "longestSecs";
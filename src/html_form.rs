@@ -2,10 +2,12 @@
 // License: GPLv3
 
 use crate::fixed::{APPNAME, BUTTON_HEIGHT, BUTTON_WIDTH, ICON};
+use crate::i18n::tr;
 use fltk::prelude::*;
 
 pub struct Form {
     form: fltk::window::Window,
+    view: fltk::misc::HelpView,
 }
 
 impl Form {
@@ -17,7 +19,7 @@ impl Form {
         height: i32,
         resizable: bool,
     ) -> Self {
-        let (mut form, mut ok_button) =
+        let (mut form, view, mut ok_button) =
             make_widgets(title, html_text, width, height, resizable);
         form.make_modal(modal);
         add_event_handler(&mut form, &mut ok_button);
@@ -27,12 +29,20 @@ impl Form {
                 fltk::app::wait();
             }
         }
-        Self { form }
+        Self { form, view }
     }
 
     pub fn show(&mut self) {
         self.form.show();
     }
+
+    pub fn shown(&self) -> bool {
+        self.form.shown()
+    }
+
+    pub fn set_html(&mut self, html_text: &str) {
+        self.view.set_value(html_text);
+    }
 }
 
 impl Drop for Form {
@@ -47,7 +57,7 @@ fn make_widgets(
     width: i32,
     height: i32,
     resizable: bool,
-) -> (fltk::window::Window, fltk::button::Button) {
+) -> (fltk::window::Window, fltk::misc::HelpView, fltk::button::Button) {
     let image = fltk::image::SvgImage::from_data(ICON).unwrap();
     let mut form = fltk::window::Window::new(0, 0, width, height, "");
     if let Some(window) = fltk::app::first_window() {
@@ -64,14 +74,14 @@ fn make_widgets(
     let mut button_row =
         fltk::group::Flex::default().size_of_parent().row();
     fltk::frame::Frame::default(); // pad left of button
-    let ok_button = fltk::button::Button::default().with_label("&OK");
+    let ok_button = fltk::button::Button::default().with_label(tr("&OK"));
     fltk::frame::Frame::default(); // pad right of button
     button_row.set_size(&ok_button, BUTTON_WIDTH);
     button_row.end();
     vbox.set_size(&button_row, BUTTON_HEIGHT);
     vbox.end();
     form.end();
-    (form, ok_button)
+    (form, view, ok_button)
 }
 
 fn add_event_handler(
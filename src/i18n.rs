@@ -0,0 +1,125 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Every user-visible string is passed through tr() (or trf() if it has
+// arguments) which returns its translation in the current language or the
+// English original if there isn't one. Each language's catalog is a list of
+// (English, translation) pairs in its own module (e.g., i18n_de) where any
+// arguments are written {0}, {1}, etc., so that translations can reorder
+// them.
+
+use crate::i18n_de;
+use std::{env, fmt, sync};
+use thousands::policies::{COMMA_SEPARATOR, DOT_SEPARATOR};
+use thousands::{Separable, SeparatorPolicy};
+
+pub static LANGUAGE: state::Storage<sync::RwLock<Language>> =
+    state::Storage::new();
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    // Each language's name is in that language
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Language> {
+        Language::ALL
+            .iter()
+            .find(|language| language.name() == name)
+            .copied()
+    }
+
+    // The ISO 639-1 code
+    fn code(&self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::German => "de",
+        }
+    }
+
+    // Returns the language of the user's locale (e.g., LANG=de_DE.UTF-8)
+    // or English if there's no catalog for it
+    pub fn detect() -> Language {
+        for name in ["LC_ALL", "LC_MESSAGES", "LANG"] {
+            if let Ok(locale) = env::var(name) {
+                if !locale.is_empty() {
+                    let code = locale
+                        .split(['_', '-', '.', '@'])
+                        .next()
+                        .unwrap_or_default();
+                    return Language::ALL
+                        .iter()
+                        .find(|language| language.code() == code)
+                        .copied()
+                        .unwrap_or(Language::English);
+                }
+            }
+        }
+        Language::English
+    }
+
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::German => i18n_de::CATALOG,
+        }
+    }
+
+    fn number_policy(&self) -> SeparatorPolicy<'static> {
+        match self {
+            Language::English => COMMA_SEPARATOR,
+            Language::German => DOT_SEPARATOR,
+        }
+    }
+}
+
+// *MUST* be done before CONFIG is created since reading the config sets
+// the language
+pub fn initialize(language: Language) {
+    LANGUAGE.set(sync::RwLock::new(language));
+}
+
+// English until initialized, e.g., for a panic at startup
+pub fn language() -> Language {
+    LANGUAGE
+        .try_get()
+        .map_or(Language::English, |language| *language.read().unwrap())
+}
+
+pub fn set_language(language: Language) {
+    *LANGUAGE.get().write().unwrap() = language;
+}
+
+pub fn tr(text: &str) -> &str {
+    language()
+        .catalog()
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translation)| translation)
+}
+
+// Returns the translation with each {i} replaced by the i-th argument
+pub fn trf(text: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut text = tr(text).to_string();
+    for (i, arg) in args.iter().enumerate() {
+        text = text.replace(&format!("{{{i}}}"), &arg.to_string());
+    }
+    text
+}
+
+// Returns the number with the language's thousands separator, e.g.,
+// 1,234 in English or 1.234 in German
+pub fn number<N: Separable>(n: N) -> String {
+    n.separate_by_policy(language().number_policy())
+}
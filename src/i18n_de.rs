@@ -0,0 +1,663 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// The German (Deutsch) translation: see i18n.rs
+
+pub const CATALOG: &[(&str, &str)] = &[
+    ("New game! Click a tile…", "Neues Spiel! Klicke auf einen Stein…"),
+    ("New game! {0}", "Neues Spiel! {0}"),
+    (
+        "Start New Game for New Options",
+        "Neues Spiel starten, um die neuen Optionen zu nutzen",
+    ),
+    ("About", "Über"),
+    ("High Scores", "Bestenliste"),
+    ("Help", "Hilfe"),
+    ("Score • High score", "Punkte • Rekord"),
+    ("Waiting for the other racers…", "Warte auf die anderen Teilnehmer…"),
+    (
+        "Click New or press {0} to play…",
+        "Klicke auf Neu oder drücke {0}, um zu spielen…",
+    ),
+    ("Race — {0}", "Rennen — {0}"),
+    ("Leave the race?", "Das Rennen verlassen?"),
+    ("&No", "&Nein"),
+    ("&Yes", "&Ja"),
+    ("Left the race", "Rennen verlassen"),
+    ("Race on the local network", "Rennen im lokalen Netzwerk"),
+    ("&Cancel", "Abbre&chen"),
+    ("&Host", "&Veranstalten"),
+    ("&Join", "&Beitreten"),
+    (
+        "Failed to host a race on port {0}:\n{1}",
+        "Das Rennen auf Port {0} konnte nicht veranstaltet werden:\n{1}",
+    ),
+    ("Host's address:", "Adresse des Veranstalters:"),
+    (
+        "Failed to join the race at {0}:\n{1}",
+        "Dem Rennen unter {0} konnte nicht beigetreten werden:\n{1}",
+    ),
+    (
+        "Hosting a race on port {0}: press {1} to start once everyone \
+        has joined",
+        "Rennen wird auf Port {0} veranstaltet: drücke {1} zum Starten, \
+        sobald alle beigetreten sind",
+    ),
+    (
+        "Joined the race: waiting for the host to start…",
+        "Dem Rennen beigetreten: warte auf den Start durch den \
+        Veranstalter…",
+    ),
+    (
+        "The race is on! Click a tile…",
+        "Das Rennen läuft! Klicke auf einen Stein…",
+    ),
+    (
+        "{0} wins! Press {1} to race again…",
+        "{0} gewinnt! Drücke {1} für ein neues Rennen…",
+    ),
+    ("{0} wins!", "{0} gewinnt!"),
+    ("Race", "Rennen"),
+    (
+        "Paused: click Pause to resume",
+        "Pausiert: klicke auf Pause, um fortzufahren",
+    ),
+    (
+        "Paused: click Pause or press {0} to resume",
+        "Pausiert: klicke auf Pause oder drücke {0}, um fortzufahren",
+    ),
+    ("{0} is thinking…", "{0} denkt nach…"),
+    ("{0} to play", "{0} ist am Zug"),
+    ("Hot Seat — {0}", "Hot Seat — {0}"),
+    ("Player {0}'s name:", "Name von Spieler {0}:"),
+    ("Computer", "Computer"),
+    (
+        "failed to load tile set: {0}",
+        "Steinsatz konnte nicht geladen werden: {0}",
+    ),
+    (
+        "Remove {0} tiles for {1} points",
+        "{0} Steine für {1} Punkte entfernen",
+    ),
+    (
+        "Not legal: stones can only be removed by bombs",
+        "Nicht erlaubt: Felsen können nur von Bomben entfernt werden",
+    ),
+    (
+        "Not legal: no adjoining tile of the same color",
+        "Nicht erlaubt: kein angrenzender Stein derselben Farbe",
+    ),
+    ("Paused", "Pause"),
+    ("{0}\nWins!", "{0}\ngewinnt!"),
+    ("It's a Draw!", "Unentschieden!"),
+    ("Game Over!", "Spiel vorbei!"),
+    ("You Won!\n\nNew\nHighscore", "Gewonnen!\n\nNeuer\nRekord"),
+    ("You Won!", "Gewonnen!"),
+    ("Time's Up!\n\nNew\nHighscore", "Zeit um!\n\nNeuer\nRekord"),
+    ("Time's Up!", "Zeit um!"),
+    (
+        "failed to save configuration: {0}",
+        "Einstellungen konnten nicht gespeichert werden: {0}",
+    ),
+    ("no filename", "kein Dateiname"),
+    (
+        "A TileFall/SameGame-like game.",
+        "Ein Spiel wie TileFall oder SameGame.",
+    ),
+    ("All rights reserved.", "Alle Rechte vorbehalten."),
+    ("License: GPLv3.", "Lizenz: GPLv3."),
+    ("No high scores yet.", "Noch keine Bestleistungen."),
+    ("Score", "Punkte"),
+    ("Date", "Datum"),
+    ("Endless Records", "Endlos-Rekorde"),
+    ("Most tiles cleared", "Meiste entfernte Steine"),
+    ("Longest survival", "Längstes Durchhalten"),
+    ("&OK", "&OK"),
+    ("{0} is bound to: {1}", "{0} ist belegt mit: {1}"),
+    ("or", "oder"),
+    ("Key", "Taste"),
+    ("Action", "Aktion"),
+    ("none", "keine"),
+    ("Key Bindings — {0}", "Tastenbelegung — {0}"),
+    ("&Preset", "&Vorlage"),
+    ("Custom", "Eigene"),
+    (
+        "Replace all the key bindings with those of a preset: Standard \
+        (the defaults) or Vi with h, j, k and l to move the focus",
+        "Alle Tasten durch die einer Vorlage ersetzen: Standard (die \
+        Voreinstellung) oder Vi mit h, j, k und l, um den Fokus zu \
+        bewegen",
+    ),
+    (
+        "Keys separated by spaces, each a character or Space, Esc, Tab, \
+        Enter, BackSpace, Insert, Delete, Help, Left, Right, Up, Down, \
+        Home, End, PgUp, PgDn or F1 to F12, optionally after Ctrl+, \
+        Alt+ or Shift+, e.g., Ctrl+Shift+n",
+        "Tasten durch Leerzeichen getrennt, jede ein Zeichen oder Space, \
+        Esc, Tab, Enter, BackSpace, Insert, Delete, Help, Left, Right, \
+        Up, Down, Home, End, PgUp, PgDn oder F1 bis F12, wahlweise nach \
+        Ctrl+, Alt+ oder Shift+, z.B. Ctrl+Shift+n",
+    ),
+    ("“{0}” isn't a key ({1})", "„{0}“ ist keine Taste ({1})"),
+    ("Error — {0}", "Fehler — {0}"),
+    ("Failed to read theme: {0}", "Thema konnte nicht gelesen werden: {0}"),
+    ("New game", "Neues Spiel"),
+    ("Pause or resume", "Pause oder fortfahren"),
+    ("Options…", "Optionen…"),
+    ("Time left", "Verbleibende Zeit"),
+    ("Options — {0}", "Optionen — {0}"),
+    ("&Language", "S&prache"),
+    (
+        "The language of the user interface (default the system's)",
+        "Die Sprache der Benutzeroberfläche (Voreinstellung: die des \
+        Systems)",
+    ),
+    ("&Game", "&Spiel"),
+    (
+        "Classic; Time Attack to score as much as possible against the \
+        clock; Endless with new tiles after every move; or Hot Seat for \
+        two players taking turns; or Versus Computer (default Classic)",
+        "Klassisch; Gegen die Uhr, um so viele Punkte wie möglich zu \
+        erzielen; Endlos mit neuen Steinen nach jedem Zug; Hot Seat für \
+        zwei Spieler, die sich abwechseln; oder Gegen den Computer \
+        (Voreinstellung: Klassisch)",
+    ),
+    ("Com&puter", "Co&mputer"),
+    (
+        "How the computer plays in the Versus Computer game: Random; \
+        Greedy takes the highest scoring move; Lookahead also considers \
+        your best reply (default Greedy)",
+        "Wie der Computer im Spiel Gegen den Computer spielt: Zufällig; \
+        Gierig wählt den Zug mit den meisten Punkten; Vorausschauend \
+        berücksichtigt auch deine beste Antwort (Voreinstellung: \
+        Gierig)",
+    ),
+    ("P&alette", "Pa&lette"),
+    (
+        "The tile colors: Standard (the theme's); or one designed for \
+        deuteranopia, protanopia or tritanopia (default Standard)",
+        "Die Farben der Steine: Standard (die des Themas) oder eine für \
+        Deuteranopie, Protanopie oder Tritanopie entworfene Palette \
+        (Voreinstellung: Standard)",
+    ),
+    ("S&ymbols", "S&ymbole"),
+    (
+        "Draw a shape or letter on each tile so that every color can be \
+        told apart without seeing it (default off)",
+        "Auf jeden Stein eine Form oder einen Buchstaben zeichnen, damit \
+        sich jede Farbe auch ohne sie zu sehen unterscheiden lässt \
+        (Voreinstellung: aus)",
+    ),
+    ("Reduced Motio&n", "Reduzierte Be&wegung"),
+    (
+        "Remove tiles at once and move tiles straight to their new \
+        places rather than animating them (default off)",
+        "Steine sofort entfernen und direkt an ihren neuen Platz setzen, \
+        statt sie zu animieren (Voreinstellung: aus)",
+    ),
+    ("&Focus Wraps", "&Fokus springt um"),
+    (
+        "Moving the keyboard focus past the last tile in a row or column \
+        goes round to the other side (default off)",
+        "Wird der Tastaturfokus über den letzten Stein einer Zeile oder \
+        Spalte hinaus bewegt, springt er auf die andere Seite \
+        (Voreinstellung: aus)",
+    ),
+    ("T&heme", "T&hema"),
+    (
+        "The board's colors, fonts and tile style: Light, Dark, \
+        High-Contrast, or one from {0} (default Light)",
+        "Farben, Schriften und Steinstil des Spielfelds: Hell, Dunkel, \
+        Hoher Kontrast oder eines aus {0} (Voreinstellung: Hell)",
+    ),
+    ("Til&e Set", "Steinsa&tz"),
+    (
+        "Draw the tiles as images: None for plain colored tiles, Gems, \
+        or a tile set from {0} (default None)",
+        "Die Steine als Bilder zeichnen: Keiner für einfarbige Steine, \
+        Edelsteine oder ein Steinsatz aus {0} (Voreinstellung: Keiner)",
+    ),
+    ("Col&umns", "Spalt&en"),
+    ("Columns (default 9)", "Spalten (Voreinstellung: 9)"),
+    ("&Rows", "&Zeilen"),
+    ("Rows (default 9)", "Zeilen (Voreinstellung: 9)"),
+    ("Ma&x. Colors", "Max. Fa&rben"),
+    ("Max. Colors (default 4)", "Max. Farben (Voreinstellung: 4)"),
+    ("Color D&istance", "Farb&abstand"),
+    (
+        "The smallest acceptable difference between any two colors \
+        (CIEDE2000, where 2 is just noticeable); Options warns if there \
+        aren't enough colors this different (default 20)",
+        "Der kleinste annehmbare Unterschied zwischen zwei Farben \
+        (CIEDE2000, wobei 2 gerade wahrnehmbar ist); Optionen warnt, \
+        wenn es nicht genug so verschiedene Farben gibt \
+        (Voreinstellung: 20)",
+    ),
+    ("&Delay (ms)", "&Verzögerung (ms)"),
+    (
+        "Delay to show the tiles about to be removed (default 250 \
+        milliseconds)",
+        "Wie lange die zu entfernenden Steine angezeigt werden \
+        (Voreinstellung: 250 Millisekunden)",
+    ),
+    ("&Bombs (%)", "&Bomben (%)"),
+    (
+        "Percentage of bombs, which clear the tiles around them when \
+        removed (default 0)",
+        "Anteil der Bomben, die beim Entfernen die Steine um sich herum \
+        mitnehmen (Voreinstellung: 0)",
+    ),
+    ("&Wildcards (%)", "&Joker (%)"),
+    (
+        "Percentage of wildcards, which join any adjoining color \
+        (default 0)",
+        "Anteil der Joker, die sich jeder angrenzenden Farbe anschließen \
+        (Voreinstellung: 0)",
+    ),
+    ("S&tones (%)", "Felse&n (%)"),
+    (
+        "Percentage of stones, which never move and only bombs can \
+        remove (default 0)",
+        "Anteil der Felsen, die sich nie bewegen und nur von Bomben \
+        entfernt werden können (Voreinstellung: 0)",
+    ),
+    ("&Multipliers (%)", "M&ultiplikatoren (%)"),
+    (
+        "Percentage of multipliers, which double the score of the tiles \
+        removed with them (default 0)",
+        "Anteil der Multiplikatoren, die die Punkte der mit ihnen \
+        entfernten Steine verdoppeln (Voreinstellung: 0)",
+    ),
+    ("&Scale", "S&kalierung"),
+    (
+        "User interface scale (default 1.0)",
+        "Skalierung der Benutzeroberfläche (Voreinstellung: 1,0)",
+    ),
+    (
+        "Only {0} of the {1} colors from the {2} palette can be at least \
+        {3} apart so some will be hard to tell apart.",
+        "Nur {0} der {1} Farben der Palette {2} können mindestens {3} \
+        voneinander entfernt sein, daher werden einige schwer zu \
+        unterscheiden sein.",
+    ),
+    ("Checking Symbols may help.", "Symbole einzuschalten kann helfen."),
+    ("Racer", "Teilnehmer"),
+    ("Tiles Left", "Übrige Steine"),
+    ("{0} has left the race", "{0} hat das Rennen verlassen"),
+    (
+        "The host has left the race",
+        "Der Veranstalter hat das Rennen verlassen",
+    ),
+    ("{0}: no [{1}] section", "{0}: kein Abschnitt [{1}]"),
+    ("{0}: invalid {1} “{2}”", "{0}: ungültiges {1} „{2}“"),
+    ("{0}: not an SVG or PNG image", "{0}: kein SVG- oder PNG-Bild"),
+    (
+        "{0}: “{1}” isn't a palette index",
+        "{0}: „{1}“ ist kein Palettenindex",
+    ),
+    (
+        "there is no “{0}” tile set in {1}",
+        "es gibt keinen Steinsatz „{0}“ in {1}",
+    ),
+    ("Warning — {0}", "Warnung — {0}"),
+    ("New Game", "Neues Spiel"),
+    ("View or Edit Options", "Optionen ansehen oder ändern"),
+    ("View or Edit Key Bindings", "Tastenbelegung ansehen oder ändern"),
+    ("Show About box", "Infos über das Spiel anzeigen"),
+    ("Show this Help window", "Dieses Hilfefenster anzeigen"),
+    ("Show the High Scores", "Die Bestenliste anzeigen"),
+    (
+        "Host, join, start or leave a race",
+        "Ein Rennen veranstalten, ihm beitreten, es starten oder verlassen",
+    ),
+    ("Pause or resume the game", "Das Spiel pausieren oder fortsetzen"),
+    ("Quit", "Beenden"),
+    (
+        "Move the focus to the nearest tile to the left",
+        "Den Fokus zum nächsten Stein links bewegen",
+    ),
+    (
+        "Move the focus to the nearest tile to the right",
+        "Den Fokus zum nächsten Stein rechts bewegen",
+    ),
+    (
+        "Move the focus to the nearest tile above",
+        "Den Fokus zum nächsten Stein darüber bewegen",
+    ),
+    (
+        "Move the focus to the nearest tile below",
+        "Den Fokus zum nächsten Stein darunter bewegen",
+    ),
+    (
+        "Move the focus to the first tile in the row",
+        "Den Fokus zum ersten Stein der Zeile bewegen",
+    ),
+    (
+        "Move the focus to the last tile in the row",
+        "Den Fokus zum letzten Stein der Zeile bewegen",
+    ),
+    (
+        "Move the focus to the top tile in the column",
+        "Den Fokus zum obersten Stein der Spalte bewegen",
+    ),
+    (
+        "Move the focus to the bottom tile in the column",
+        "Den Fokus zum untersten Stein der Spalte bewegen",
+    ),
+    (
+        "Move the focus to the next group that can be removed",
+        "Den Fokus zur nächsten entfernbaren Gruppe bewegen",
+    ),
+    ("Click the focused tile", "Den Stein mit dem Fokus anklicken"),
+    ("Classic", "Klassisch"),
+    ("Time Attack", "Gegen die Uhr"),
+    ("Endless", "Endlos"),
+    ("Hot Seat", "Hot Seat"),
+    ("Versus Computer", "Gegen den Computer"),
+    ("Random", "Zufällig"),
+    ("Greedy", "Gierig"),
+    ("Lookahead", "Vorausschauend"),
+    ("Standard", "Standard"),
+    ("Deuteranopia", "Deuteranopie"),
+    ("Protanopia", "Protanopie"),
+    ("Tritanopia", "Tritanopie"),
+    ("waiting", "wartet"),
+    ("playing", "spielt"),
+    ("cleared", "geschafft"),
+    ("stuck", "festgefahren"),
+    ("gone", "weg"),
+    ("Vi (hjkl)", "Vi (hjkl)"),
+    ("Light", "Hell"),
+    ("Dark", "Dunkel"),
+    ("High-Contrast", "Hoher Kontrast"),
+    ("None", "Keiner"),
+    ("Gems", "Edelsteine"),
+    ("Carrying on where you left off…", "Weiter, wo du aufgehört hast…"),
+    ("High Scores — {0}", "Bestenliste — {0}"),
+    ("Player", "Spieler"),
+    ("Statistics", "Statistik"),
+    ("Games played", "Gespielte Spiele"),
+    ("Games won", "Gewonnene Spiele"),
+    ("Tiles removed", "Entfernte Steine"),
+    ("Average score", "Durchschnittliche Punktzahl"),
+    ("Profiles…", "Profile…"),
+    ("Profiles — {0}", "Profile — {0}"),
+    ("&New…", "&Neu…"),
+    ("Add a profile", "Ein Profil hinzufügen"),
+    ("&Delete", "&Löschen"),
+    ("&Switch", "&Wechseln"),
+    ("New profile's name:", "Name des neuen Profils:"),
+    (
+        "“{0}” can't be used as a profile name: use up to {1} letters, \
+        digits, spaces, hyphens and underscores",
+        "„{0}“ kann nicht als Profilname verwendet werden: verwende bis \
+        zu {1} Buchstaben, Ziffern, Leerzeichen, Bindestriche und \
+        Unterstriche",
+    ),
+    (
+        "There is already a “{0}” profile",
+        "Es gibt bereits ein Profil „{0}“",
+    ),
+    (
+        "The active profile can't be deleted",
+        "Das aktive Profil kann nicht gelöscht werden",
+    ),
+    (
+        "Delete the “{0}” profile with its settings, high scores, \
+        statistics and saved game?",
+        "Das Profil „{0}“ mit seinen Einstellungen, Bestleistungen, \
+        seiner Statistik und seinem gespeicherten Spiel löschen?",
+    ),
+    ("failed to delete {0}: {1}", "{0} konnte nicht gelöscht werden: {1}"),
+    (
+        "Switch, add or delete player profiles",
+        "Spielerprofile wechseln, hinzufügen oder löschen",
+    ),
+    (
+        "{0}: written by a newer version of {1} so some settings may not \
+        be used",
+        "{0}: von einer neueren Version von {1} geschrieben, daher \
+        werden manche Einstellungen eventuell nicht verwendet",
+    ),
+    (
+        "{0}: {1}; the defaults are used and the file has been copied to \
+        {2}",
+        "{0}: {1}; die Voreinstellungen werden verwendet und die Datei \
+        wurde nach {2} kopiert",
+    ),
+    (
+        "{0}: {1}; the defaults are used",
+        "{0}: {1}; die Voreinstellungen werden verwendet",
+    ),
+    (
+        "{0}: the saved game couldn't be read so a new game is started",
+        "{0}: das gespeicherte Spiel konnte nicht gelesen werden, daher \
+        wird ein neues Spiel begonnen",
+    ),
+    (
+        "Some settings couldn't be used as they are:",
+        "Einige Einstellungen konnten nicht so verwendet werden, wie sie \
+        sind:",
+    ),
+    (
+        "{0}: [{1}] {2} = “{3}” isn't from {4} to {5} so {6} is used",
+        "{0}: [{1}] {2} = „{3}“ liegt nicht zwischen {4} und {5}, daher \
+        wird {6} verwendet",
+    ),
+    (
+        "{0}: [{1}] {2} = “{3}” isn't valid so {4} is used",
+        "{0}: [{1}] {2} = „{3}“ ist ungültig, daher wird {4} verwendet",
+    ),
+    ("Beginner", "Anfänger"),
+    ("Normal", "Normal"),
+    ("Expert", "Experte"),
+    ("Marathon", "Marathon"),
+    ("Difficulty", "Schwierigkeit"),
+    ("Difficulty Le&vel", "Schwieri&gkeit"),
+    (
+        "Set the columns, rows, max. colors and special tiles together: \
+        Beginner, Normal, Expert or Marathon; or Custom for your own. \
+        High scores and statistics are kept for each (default Normal)",
+        "Spalten, Zeilen, max. Farben und Sondersteine gemeinsam \
+        festlegen: Anfänger, Normal, Experte oder Marathon; oder Eigene \
+        für eigene Werte. Bestenlisten und Statistiken werden für jede \
+        Stufe getrennt geführt (Voreinstellung: Normal)",
+    ),
+    ("Solvable", "Lösbar"),
+    (
+        "No solvable board was found in time so this one may not be \
+        clearable",
+        "Es wurde nicht rechtzeitig ein lösbares Spielfeld gefunden, \
+        daher lässt sich dieses eventuell nicht abräumen",
+    ),
+    ("This board can be cleared", "Dieses Spielfeld lässt sich abräumen"),
+    ("Chec&k Boards Are Solvable", "Nur lösbare Spielfel&der"),
+    (
+        "Only play boards that have been proven to be clearable, marked \
+        ✓; their high scores are kept apart (default off)",
+        "Nur Spielfelder spielen, die nachweislich abgeräumt werden \
+        können, mit ✓ markiert; ihre Bestleistungen werden getrennt \
+        geführt (Voreinstellung: aus)",
+    ),
+    ("Difficulty: {0} of {1}", "Schwierigkeit: {0} von {1}"),
+    ("Rating", "Bewertung"),
+    ("Uniform", "Gleichmäßig"),
+    ("Balanced", "Ausgewogen"),
+    ("Clustered", "Gehäuft"),
+    ("Scattered", "Verstreut"),
+    ("Mirror", "Gespiegelt"),
+    ("Rotational", "Gedreht"),
+    ("&Jumble", "Verte&ilung"),
+    (
+        "How a new board's colors are laid out: Uniform at random; \
+        Balanced with as many tiles of each color; Clustered for bigger \
+        groups; Scattered for smaller ones; or Mirror or Rotational for \
+        symmetric boards (default Uniform)",
+        "Wie die Farben eines neuen Spielfelds angeordnet werden: \
+        Gleichmäßig zufällig; Ausgewogen mit gleich vielen Steinen \
+        jeder Farbe; Gehäuft für größere Gruppen; Verstreut für \
+        kleinere; oder Gespiegelt oder Gedreht für symmetrische \
+        Spielfelder (Voreinstellung: Gleichmäßig)",
+    ),
+    (
+        "The host started a race on a board that can't be played here",
+        "Der Gastgeber hat ein Rennen auf einem Spielfeld gestartet, das \
+        hier nicht gespielt werden kann",
+    ),
+    ("Finding a solvable board…", "Ein lösbares Spielfeld wird gesucht…"),
+    ("Rating the board…", "Das Spielfeld wird bewertet…"),
+    ("Key bindings…", "Tastenbelegung…"),
+    ("High scores", "Bestenliste"),
+    ("Player 1", "Spieler 1"),
+    ("Player 2", "Spieler 2"),
+    (
+        "{0}: [{1}] {2} is kept as “{3}” so the older “{4}” is dropped",
+        "{0}: [{1}] {2} bleibt „{3}“, daher wird das ältere „{4}“ \
+        verworfen",
+    ),
+];
+
+pub static HELP_HTML: &str = "<body>
+<p><center><font color=navy size=6><b>Gravitate</b></font></center></p>
+<font color=blue size=5>Ziel des Spiels ist es, alle Steine zu
+entfernen.</font>
+<p>
+<font color=#008000 size=4>
+Klicke auf einen Stein, der mindestens einen senkrecht oder waagerecht
+angrenzenden Stein derselben Farbe hat, um ihn und alle senkrecht oder
+waagerecht angrenzenden Steine derselben Farbe zu entfernen, ebenso
+<i>deren</i> senkrecht oder waagerecht angrenzende Steine und so weiter.
+<i>(Ein Klick auf einen Stein ohne angrenzende Steine derselben Farbe
+bewirkt also nichts.)</i> Je mehr Steine auf einmal entfernt werden, desto
+höher die Punktzahl.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Wenn in den Optionen eingeschaltet, sind manche Steine besonders. Eine
+<b>Bombe</b> (schwarze Kugel) entfernt beim Entfernen ihrer Gruppe auch
+jeden Stein um sich herum. Ein <b>Joker</b> (vierfarbige Scheibe) schließt
+sich jeder angrenzenden Farbe an. Ein <b>Fels</b> (eingelassenes Grau)
+bewegt sich nie und kann nur von einer Bombe entfernt werden; Felsen müssen
+zum Gewinnen nicht entfernt werden. Ein <b>Multiplikator</b> (×2) verdoppelt
+die Punkte der mit ihm entfernten Steine.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Die <b>Schwierigkeit</b> in den Optionen legt Größe, Farben und Sondersteine
+des Spielfelds gemeinsam fest: Anfänger, Normal, Experte oder Marathon, oder
+Eigene, sobald einer der Werte geändert wird. Bestenlisten und Statistiken
+werden für jede Stufe getrennt geführt, damit Gleiches mit Gleichem
+verglichen wird. Ist <b>Nur lösbare Spielfelder</b> angekreuzt, wird jedes
+neue Spielfeld erst verwendet, wenn gezeigt wurde, dass es sich abräumen
+lässt, was neben der Zeit mit ✓ markiert wird; diese Spielfelder haben
+eigene Bestenlisten. Jedes Spielfeld wird mit einem Stern (leicht) bis fünf
+(schwer) bewertet, indem es vor dem Anzeigen viele Male durchgespielt wird;
+die Bewertung steht neben der Zeit und wird mit jeder Bestleistung
+gespeichert. Die <b>Verteilung</b> legt fest, wie die Farben jedes neuen
+Spielfelds angeordnet werden: Gleichmäßig wählt die Farbe jedes Steins
+zufällig; Ausgewogen hat von jeder Farbe gleich viele Steine; Gehäuft ergibt
+größere Gruppen und Verstreut kleinere; Gespiegelt und Gedreht ergeben
+Spielfelder, die von links nach rechts oder auf dem Kopf stehend symmetrisch
+sind. Spielfelder, die nicht gleichmäßig angeordnet sind, haben ebenfalls
+eigene Bestenlisten.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Wenn sich manche Farben schwer unterscheiden lassen, wähle in den Optionen
+eine für Deuteranopie, Protanopie oder Tritanopie entworfene <b>Palette</b>
+und schalte <b>Symbole</b> ein, um auf jedem Stein eine Form oder einen
+Buchstaben zu zeigen, die nur zu seiner Farbe gehören. Es werden immer die
+am besten unterscheidbaren Farben verwendet; die Optionen warnen, wenn nicht
+genug Farben sich mindestens um den <b>Farbabstand</b> unterscheiden. Das
+<b>Thema</b> legt Farben, Schriften und Steinstil des Spielfelds fest: außer
+den Themen Hell, Dunkel und Hoher Kontrast werden auch alle
+<tt>.ini</tt>-Themendateien im Themenverzeichnis (siehe Tooltip von Thema)
+angeboten. Ein <b>Steinsatz</b> zeichnet die Steine als Bilder: Edelsteine
+ist eingebaut, und jedes Unterverzeichnis des Steinsatzverzeichnisses (siehe
+Tooltip von Steinsatz) mit einer <tt>manifest.ini</tt>, die ein SVG- oder
+PNG-Bild pro Farbe aufführt, wird ebenfalls angeboten. Steht die Maus über
+einem Stein (oder hat er den Fokus), werden die Steine umrandet, die ein
+Klick entfernen würde, und ihre Punkte angezeigt; eine gestrichelte
+Umrandung bedeutet, dass der Klick nicht erlaubt ist. Entfernte Steine
+blinken und schrumpfen, während ihre Punkte nach oben schweben; klicke oder
+drücke <!--CLICK-->, um das zu überspringen. Schalte <b>Reduzierte
+Bewegung</b> ein, damit Steine sofort verschwinden und direkt an ihren neuen
+Platz springen, statt dorthin zu gleiten, und <b>Fokus springt um</b>, damit
+die Pfeiltasten zur gegenüberliegenden Seite des Spielfelds springen, wenn
+davor kein Stein mehr liegt.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Wähle die <b>Sprache</b> der Benutzeroberfläche in den Optionen: Englisch
+(English) oder Deutsch; voreingestellt ist die Sprache des Systems, falls
+vorhanden.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Klicke auf <b>Profile</b>, um Spieler hinzuzufügen, zu löschen oder zwischen
+ihnen zu wechseln. Jedes Profil hat eigene Optionen, eine eigene
+Tastenbelegung, Bestenliste und Statistik (in der Bestenliste zu sehen), und
+ein Einzelspielerspiel, das beim Wechseln oder Beenden noch läuft, wird beim
+nächsten Gebrauch seines Profils fortgesetzt. Der Name des aktiven Profils
+steht im Fenstertitel und wird mit jeder Bestleistung gespeichert; Größe,
+Position, Skalierung und Sprache des Fensters gelten für alle Profile.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Optionen werden im Konfigurationsordner des Systems gespeichert,
+Bestenlisten, Statistiken und gespeicherte Spiele in seinem Datenordner. Um
+stattdessen alles neben dem Programm zu behalten, z.B. auf einem USB-Stick,
+lege eine Datei namens <tt>portable</tt> daneben oder setze die
+Umgebungsvariable <tt>GRAVITATE_PORTABLE</tt> auf 1.
+</font>
+</p>
+<p>
+<font color=#008000 size=4>
+Jede der folgenden Tasten lässt sich unter <b>Tastenbelegung</b> (die
+Tastatur-Schaltfläche oder <!--KEYBINDINGS-->) ändern, entweder einzeln oder
+durch Wahl einer Vorlage wie Vi, die den Fokus mit <b>h</b>, <b>j</b>,
+<b>k</b> und <b>l</b> bewegt. Eine Taste kann nicht mit mehr als einer
+Aktion belegt werden.
+</font>
+</p>
+<!--KEYS-->
+<font size=4 color=#008000>
+Gravitate funktioniert wie TileFall und SameGame, nur dass die Steine nicht
+nach unten fallen und nach links rücken, sondern zur Mitte hin
+„gravitieren“.</font>
+<p>
+<font size=4 color=#008000>
+Im Spiel <b>Gegen die Uhr</b> (in den Optionen gewählt) hast du zwei
+Minuten, und für jeden entfernten Stein kommt Zeit hinzu. Im Spiel
+<b>Endlos</b> kommen nach jedem Zug neue Steine von den Rändern herein, und
+das Spiel endet erst, wenn das Spielfeld voll ist und kein Stein mehr
+entfernt werden kann. Im Spiel <b>Hot Seat</b> ziehen zwei Spieler
+abwechselnd auf demselben Spielfeld, und wer die meisten Punkte hat,
+gewinnt. Das Spiel <b>Gegen den Computer</b> ist genauso, nur dass der
+Computer jeden zweiten Zug macht, mit der in den Optionen eingestellten
+Stärke.</font>
+</p>
+<p>
+<font size=4 color=#008000>
+Klicke auf <b>Pause</b>, um das Spielfeld zu verbergen und die Uhr
+anzuhalten, bis erneut darauf geklickt wird; das Spiel pausiert auch von
+selbst, wenn sein Fenster minimiert wird oder eine andere Anwendung den
+Fokus hat.</font>
+</p>
+<p>
+<font size=4 color=#008000>
+Für ein <b>Rennen</b> im lokalen Netzwerk drückt ein Spieler <!--RACE-->, um
+es zu veranstalten, und die anderen drücken <!--RACE-->, um mit der Adresse
+des Veranstalters (z.B. <tt>192.168.1.5:9527</tt>) beizutreten. Sobald alle
+beigetreten sind, drückt der Veranstalter erneut <!--RACE--> zum Starten:
+alle bekommen dasselbe Spielfeld und sehen den Fortschritt der anderen. Wer
+zuerst sein Spielfeld leert, gewinnt; stecken alle fest, gewinnt, wer die
+wenigsten Steine übrig hat.</font>
+</p>
+</body>";
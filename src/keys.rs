@@ -0,0 +1,582 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Every keyboard command and the keys bound to it. The bindings are saved
+// in the config file's [Keys] section, one command per line, with each
+// command's keys separated by spaces, e.g.:
+//   [Keys]
+//   help = F1 h
+//   newGame = n Ctrl+n
+
+use crate::engine::Navigation;
+use crate::fixed::Action;
+use crate::i18n::{tr, trf};
+use fltk::enums::{EventState, Key};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    NewGame,
+    Options,
+    KeyBindings,
+    Profiles,
+    About,
+    Help,
+    HighScores,
+    Race,
+    Pause,
+    Quit,
+    Left,
+    Right,
+    Up,
+    Down,
+    RowStart,
+    RowEnd,
+    ColumnStart,
+    ColumnEnd,
+    NextGroup,
+    Click,
+}
+
+impl Command {
+    pub const ALL: [Command; 20] = [
+        Command::NewGame,
+        Command::Options,
+        Command::KeyBindings,
+        Command::Profiles,
+        Command::About,
+        Command::Help,
+        Command::HighScores,
+        Command::Race,
+        Command::Pause,
+        Command::Quit,
+        Command::Left,
+        Command::Right,
+        Command::Up,
+        Command::Down,
+        Command::RowStart,
+        Command::RowEnd,
+        Command::ColumnStart,
+        Command::ColumnEnd,
+        Command::NextGroup,
+        Command::Click,
+    ];
+
+    // The name used in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::NewGame => "newGame",
+            Command::Options => "options",
+            Command::KeyBindings => "keyBindings",
+            Command::Profiles => "profiles",
+            Command::About => "about",
+            Command::Help => "help",
+            Command::HighScores => "highScores",
+            Command::Race => "race",
+            Command::Pause => "pause",
+            Command::Quit => "quit",
+            Command::Left => "left",
+            Command::Right => "right",
+            Command::Up => "up",
+            Command::Down => "down",
+            Command::RowStart => "rowStart",
+            Command::RowEnd => "rowEnd",
+            Command::ColumnStart => "columnStart",
+            Command::ColumnEnd => "columnEnd",
+            Command::NextGroup => "nextGroup",
+            Command::Click => "click",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Command> {
+        Command::ALL.iter().find(|command| command.name() == name).copied()
+    }
+
+    pub fn description(&self) -> &'static str {
+        tr(match self {
+            Command::NewGame => "New Game",
+            Command::Options => "View or Edit Options",
+            Command::KeyBindings => "View or Edit Key Bindings",
+            Command::Profiles => "Switch, add or delete player profiles",
+            Command::About => "Show About box",
+            Command::Help => "Show this Help window",
+            Command::HighScores => "Show the High Scores",
+            Command::Race => "Host, join, start or leave a race",
+            Command::Pause => "Pause or resume the game",
+            Command::Quit => "Quit",
+            Command::Left => {
+                "Move the focus to the nearest tile to the left"
+            }
+            Command::Right => {
+                "Move the focus to the nearest tile to the right"
+            }
+            Command::Up => "Move the focus to the nearest tile above",
+            Command::Down => "Move the focus to the nearest tile below",
+            Command::RowStart => {
+                "Move the focus to the first tile in the row"
+            }
+            Command::RowEnd => "Move the focus to the last tile in the row",
+            Command::ColumnStart => {
+                "Move the focus to the top tile in the column"
+            }
+            Command::ColumnEnd => {
+                "Move the focus to the bottom tile in the column"
+            }
+            Command::NextGroup => {
+                "Move the focus to the next group that can be removed"
+            }
+            Command::Click => "Click the focused tile",
+        })
+    }
+
+    pub fn action(&self) -> Action {
+        match self {
+            Command::NewGame => Action::New,
+            Command::Options => Action::Options,
+            Command::KeyBindings => Action::KeyBindings,
+            Command::Profiles => Action::Profiles,
+            Command::About => Action::About,
+            Command::Help => Action::Help,
+            Command::HighScores => Action::HighScores,
+            Command::Race => Action::Race,
+            Command::Pause => Action::Pause,
+            Command::Quit => Action::Quit,
+            Command::Left => Action::Navigate(Navigation::Left),
+            Command::Right => Action::Navigate(Navigation::Right),
+            Command::Up => Action::Navigate(Navigation::Up),
+            Command::Down => Action::Navigate(Navigation::Down),
+            Command::RowStart => Action::Navigate(Navigation::RowStart),
+            Command::RowEnd => Action::Navigate(Navigation::RowEnd),
+            Command::ColumnStart => {
+                Action::Navigate(Navigation::ColumnStart)
+            }
+            Command::ColumnEnd => Action::Navigate(Navigation::ColumnEnd),
+            Command::NextGroup => Action::Navigate(Navigation::NextGroup),
+            Command::Click => Action::PressTile,
+        }
+    }
+}
+
+const KEY_NAMES: [(Key, &str); 27] = [
+    (Key::Left, "Left"),
+    (Key::Right, "Right"),
+    (Key::Up, "Up"),
+    (Key::Down, "Down"),
+    (Key::Home, "Home"),
+    (Key::End, "End"),
+    (Key::PageUp, "PgUp"),
+    (Key::PageDown, "PgDn"),
+    (Key::Escape, "Esc"),
+    (Key::Tab, "Tab"),
+    (Key::Enter, "Enter"),
+    (Key::BackSpace, "BackSpace"),
+    (Key::Insert, "Insert"),
+    (Key::Delete, "Delete"),
+    (Key::Help, "Help"),
+    (Key::F1, "F1"),
+    (Key::F2, "F2"),
+    (Key::F3, "F3"),
+    (Key::F4, "F4"),
+    (Key::F5, "F5"),
+    (Key::F6, "F6"),
+    (Key::F7, "F7"),
+    (Key::F8, "F8"),
+    (Key::F9, "F9"),
+    (Key::F10, "F10"),
+    (Key::F11, "F11"),
+    (Key::F12, "F12"),
+];
+const SPACE: &str = "Space";
+const CTRL: &str = "Ctrl+";
+const ALT: &str = "Alt+";
+const SHIFT: &str = "Shift+";
+
+// A key pressed with any modifiers, written like "Ctrl+Shift+n", "F1" or
+// "Space"; letters are always lowercase since Shift is explicit
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Chord {
+    pub key: Key,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Chord {
+    pub fn new(key: Key) -> Self {
+        Self { key, ctrl: false, alt: false, shift: false }
+    }
+
+    // Returns the chord for the key event being handled
+    pub fn from_event() -> Self {
+        let state = fltk::app::event_state();
+        Self {
+            key: fltk::app::event_key(),
+            ctrl: state.contains(EventState::Ctrl),
+            alt: state.contains(EventState::Alt),
+            shift: state.contains(EventState::Shift),
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut chord = Chord::new(Key::None);
+        let mut rest = text.trim();
+        loop {
+            if let Some(after) = rest.strip_prefix(CTRL) {
+                chord.ctrl = true;
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix(ALT) {
+                chord.alt = true;
+                rest = after;
+            } else if let Some(after) = rest.strip_prefix(SHIFT) {
+                chord.shift = true;
+                rest = after;
+            } else {
+                break;
+            }
+        }
+        chord.key = if rest == SPACE {
+            Key::from_char(' ')
+        } else if let Some((key, _)) =
+            KEY_NAMES.iter().find(|(_, name)| *name == rest)
+        {
+            *key
+        } else {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) if c.is_ascii_graphic() => {
+                    Key::from_char(c.to_ascii_lowercase())
+                }
+                _ => return None,
+            }
+        };
+        Some(chord)
+    }
+
+    // Returns true for keys that are only ever used as modifiers
+    pub fn is_modifier(&self) -> bool {
+        matches!(
+            self.key,
+            Key::ShiftL
+                | Key::ShiftR
+                | Key::ControlL
+                | Key::ControlR
+                | Key::AltL
+                | Key::AltR
+        )
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut text = String::new();
+        for (on, prefix) in
+            [(self.ctrl, CTRL), (self.alt, ALT), (self.shift, SHIFT)]
+        {
+            if on {
+                text.push_str(prefix);
+            }
+        }
+        if self.key == Key::from_char(' ') {
+            text.push_str(SPACE);
+        } else if let Some((_, name)) =
+            KEY_NAMES.iter().find(|(key, _)| *key == self.key)
+        {
+            text.push_str(name);
+        } else {
+            match char::from_u32(self.key.bits() as u32) {
+                Some(c) if c.is_ascii_graphic() => text.push(c),
+                _ => text.push_str(&format!("#{:X}", self.key.bits())),
+            }
+        }
+        write!(f, "{text}")
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyPreset {
+    Standard,
+    Vi,
+}
+
+impl KeyPreset {
+    pub const ALL: [KeyPreset; 2] = [KeyPreset::Standard, KeyPreset::Vi];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyPreset::Standard => "Standard",
+            KeyPreset::Vi => "Vi (hjkl)",
+        }
+    }
+
+    fn keys(&self, command: Command) -> &'static [&'static str] {
+        match (self, command) {
+            (_, Command::NewGame) => &["n"],
+            (_, Command::Options) => &["o"],
+            (_, Command::KeyBindings) => &["b"],
+            (_, Command::Profiles) => &["u"],
+            (_, Command::About) => &["a"],
+            (KeyPreset::Standard, Command::Help) => &["F1", "h"],
+            (KeyPreset::Vi, Command::Help) => &["F1", "Shift+/"],
+            (_, Command::HighScores) => &["s"],
+            (_, Command::Race) => &["r"],
+            (_, Command::Pause) => &["p"],
+            (_, Command::Quit) => &["Esc", "q"],
+            (KeyPreset::Standard, Command::Left) => &["Left"],
+            (KeyPreset::Vi, Command::Left) => &["h", "Left"],
+            (KeyPreset::Standard, Command::Right) => &["Right"],
+            (KeyPreset::Vi, Command::Right) => &["l", "Right"],
+            (KeyPreset::Standard, Command::Up) => &["Up"],
+            (KeyPreset::Vi, Command::Up) => &["k", "Up"],
+            (KeyPreset::Standard, Command::Down) => &["Down"],
+            (KeyPreset::Vi, Command::Down) => &["j", "Down"],
+            (KeyPreset::Standard, Command::RowStart) => &["Home"],
+            (KeyPreset::Vi, Command::RowStart) => &["0", "Home"],
+            (KeyPreset::Standard, Command::RowEnd) => &["End"],
+            (KeyPreset::Vi, Command::RowEnd) => &["Shift+4", "End"],
+            (KeyPreset::Standard, Command::ColumnStart) => &["PgUp"],
+            (KeyPreset::Vi, Command::ColumnStart) => &["Shift+k", "PgUp"],
+            (KeyPreset::Standard, Command::ColumnEnd) => &["PgDn"],
+            (KeyPreset::Vi, Command::ColumnEnd) => &["Shift+j", "PgDn"],
+            (KeyPreset::Standard, Command::NextGroup) => &["g"],
+            (KeyPreset::Vi, Command::NextGroup) => &["w"],
+            (KeyPreset::Standard, Command::Click) => &["Space"],
+            (KeyPreset::Vi, Command::Click) => &["Space", "x"],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    chords: Vec<Vec<Chord>>, // in Command::ALL order
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings::preset(KeyPreset::Standard)
+    }
+}
+
+impl KeyBindings {
+    pub fn preset(preset: KeyPreset) -> Self {
+        Self {
+            chords: Command::ALL
+                .iter()
+                .map(|&command| {
+                    preset
+                        .keys(command)
+                        .iter()
+                        .filter_map(|text| Chord::parse(text))
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn chords(&self, command: Command) -> &[Chord] {
+        &self.chords[command as usize]
+    }
+
+    pub fn set(&mut self, command: Command, chords: Vec<Chord>) {
+        self.chords[command as usize] = chords;
+    }
+
+    // Returns the command bound to the chord, if any
+    pub fn command(&self, chord: Chord) -> Option<Command> {
+        Command::ALL
+            .iter()
+            .find(|&&command| self.chords(command).contains(&chord))
+            .copied()
+    }
+
+    // Returns a message for every chord bound to more than one command
+    pub fn conflicts(&self) -> Vec<String> {
+        let mut conflicts = vec![];
+        let mut done = vec![];
+        for chords in &self.chords {
+            for chord in chords {
+                if done.contains(chord) {
+                    continue;
+                }
+                done.push(*chord);
+                let commands: Vec<&str> = Command::ALL
+                    .iter()
+                    .filter(|&&command| {
+                        self.chords(command).contains(chord)
+                    })
+                    .map(|command| command.description())
+                    .collect();
+                if commands.len() > 1 {
+                    conflicts.push(trf(
+                        "{0} is bound to: {1}",
+                        &[&chord, &commands.join("; ")],
+                    ));
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Returns the command's keys as text for tooltips, e.g., "F1 or h"
+    pub fn describe(&self, command: Command) -> String {
+        self.chords(command)
+            .iter()
+            .map(|chord| chord.to_string())
+            .collect::<Vec<String>>()
+            .join(&format!(" {} ", tr("or")))
+    }
+
+    // Returns the key table for the Help window
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<table border=1 align=center>\n<font size=4 color=blue>\n\
+            <tr><th>{}</th><th>{}</th></tr>\n",
+            tr("Key"),
+            tr("Action")
+        );
+        for command in Command::ALL {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td></tr>\n",
+                self.keys_html(command),
+                command.description()
+            ));
+        }
+        html.push_str("</font>\n</table>");
+        html
+    }
+
+    // Returns the command's keys in bold for the Help window
+    pub fn keys_html(&self, command: Command) -> String {
+        let keys: Vec<String> = self
+            .chords(command)
+            .iter()
+            .map(|chord| format!("<b>{chord}</b>"))
+            .collect();
+        if keys.is_empty() {
+            format!("<i>{}</i>", tr("none"))
+        } else {
+            keys.join(&format!(" {} ", tr("or")))
+        }
+    }
+
+    // Returns a message for each key that isn't valid and so is dropped
+    pub fn read(&mut self, properties: &ini::Properties) -> Vec<String> {
+        let mut problems = vec![];
+        for (key, value) in properties.iter() {
+            if let Some(command) = Command::from_name(key) {
+                let mut chords = vec![];
+                for text in value.split_whitespace() {
+                    match Chord::parse(text) {
+                        Some(chord) if !chord.is_modifier() => {
+                            chords.push(chord)
+                        }
+                        _ => problems.push(trf(
+                            "“{0}” isn't a key ({1})",
+                            &[&text, &command.description()],
+                        )),
+                    }
+                }
+                self.set(command, chords);
+            }
+        }
+        problems
+    }
+
+    pub fn write(&self, ini: &mut ini::Ini, section: &str) {
+        for command in Command::ALL {
+            let keys: Vec<String> = self
+                .chords(command)
+                .iter()
+                .map(|chord| chord.to_string())
+                .collect();
+            ini.with_section(Some(section))
+                .set(command.name(), keys.join(" "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chords_parse_and_display_the_same() {
+        for text in [
+            "n",
+            "F1",
+            "Space",
+            "Esc",
+            "PgDn",
+            "Shift+4",
+            "Ctrl+n",
+            "Ctrl+Alt+Shift+F12",
+        ] {
+            let chord = Chord::parse(text).unwrap();
+            assert_eq!(chord.to_string(), text);
+            assert_eq!(Chord::parse(&chord.to_string()), Some(chord));
+        }
+        // Letters are lowercased and modifiers can come in any order
+        assert_eq!(Chord::parse("N").unwrap().to_string(), "n");
+        assert_eq!(
+            Chord::parse(" Shift+Ctrl+x ").unwrap().to_string(),
+            "Ctrl+Shift+x"
+        );
+        for text in ["", "Ctrl+", "nn", "F13", "Meta+n", "é"] {
+            assert_eq!(Chord::parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn every_preset_has_valid_keys_and_no_conflicts() {
+        for preset in KeyPreset::ALL {
+            let bindings = KeyBindings::preset(preset);
+            for command in Command::ALL {
+                assert_eq!(
+                    bindings.chords(command).len(),
+                    preset.keys(command).len(),
+                    "{preset:?} {command:?}"
+                );
+            }
+            assert!(bindings.conflicts().is_empty(), "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn a_chord_bound_twice_is_one_conflict() {
+        let mut bindings = KeyBindings::default();
+        let chord = Chord::parse("n").unwrap();
+        bindings.set(Command::Pause, vec![chord]);
+        bindings.set(Command::Quit, vec![chord, Chord::new(Key::Escape)]);
+        assert_eq!(bindings.conflicts().len(), 1);
+        assert_eq!(bindings.command(chord), Some(Command::NewGame));
+        bindings.set(Command::Quit, vec![Chord::new(Key::Escape)]);
+        bindings.set(Command::Pause, vec![]);
+        assert!(bindings.conflicts().is_empty());
+    }
+
+    #[test]
+    fn read_keeps_valid_keys_and_reports_the_rest() {
+        let ini = ini::Ini::load_from_str(
+            "[Keys]\nhelp = F2 Ctrl+h\nquit = q Nope\npause =\n\
+            unknown = x\n",
+        )
+        .unwrap();
+        let mut bindings = KeyBindings::default();
+        let problems = bindings.read(ini.section(Some("Keys")).unwrap());
+        assert_eq!(problems.len(), 1);
+        assert!(problems[0].contains("Nope"));
+        assert_eq!(bindings.describe(Command::Help), "F2 or Ctrl+h");
+        assert_eq!(bindings.chords(Command::Quit).len(), 1);
+        assert!(bindings.chords(Command::Pause).is_empty());
+        // Commands that aren't in the section keep their keys
+        assert_eq!(
+            bindings.chords(Command::NewGame),
+            KeyBindings::default().chords(Command::NewGame)
+        );
+        let mut written = ini::Ini::new();
+        bindings.write(&mut written, "Keys");
+        let mut reread = KeyBindings::default();
+        assert!(reread
+            .read(written.section(Some("Keys")).unwrap())
+            .is_empty());
+        assert_eq!(reread, bindings);
+    }
+}
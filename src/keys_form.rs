@@ -0,0 +1,261 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use super::CONFIG;
+use crate::fixed::{APPNAME, BUTTON_HEIGHT, BUTTON_WIDTH, ICON, PAD};
+use crate::i18n::{tr, trf};
+use crate::keys::{Chord, Command, KeyBindings, KeyPreset};
+use crate::util;
+use fltk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const ROW_HEIGHT: i32 = 24;
+const LABEL_WIDTH: i32 = 300;
+
+pub struct Form {
+    form: fltk::window::Window,
+    pub(crate) ok: Rc<RefCell<bool>>,
+}
+
+impl Form {
+    pub fn default() -> Self {
+        let ok = Rc::from(RefCell::from(false));
+        let mut form = make_form();
+        let mut vbox =
+            fltk::group::Flex::default().size_of_parent().column();
+        vbox.set_margin(PAD);
+        vbox.set_pad(2);
+        let (preset_row, mut preset_choice) = make_preset_row();
+        vbox.set_size(&preset_row, ROW_HEIGHT);
+        let keys = CONFIG.get().read().unwrap().keys.clone();
+        let mut inputs = vec![];
+        for command in Command::ALL {
+            let (row, input) = make_row(command, &keys);
+            vbox.set_size(&row, ROW_HEIGHT);
+            inputs.push(input);
+        }
+        let mut problems = fltk::frame::Frame::default().with_align(
+            fltk::enums::Align::Inside
+                | fltk::enums::Align::Left
+                | fltk::enums::Align::Wrap,
+        );
+        problems.set_label_color(fltk::enums::Color::Red);
+        vbox.set_size(&problems, ROW_HEIGHT * 2);
+        let (button_row, mut ok_button, mut cancel_button) = make_buttons();
+        vbox.set_size(&button_row, BUTTON_HEIGHT);
+        vbox.end();
+        form.end();
+        form.make_modal(true);
+        add_event_handlers(
+            &mut form,
+            &mut preset_choice,
+            &mut inputs,
+            &mut problems,
+            &mut ok_button,
+            &mut cancel_button,
+            Rc::clone(&ok),
+        );
+        form.show();
+        while form.shown() {
+            fltk::app::wait();
+        }
+        Self { form, ok }
+    }
+}
+
+impl Drop for Form {
+    fn drop(&mut self) {
+        fltk::app::delete_widget(self.form.clone());
+    }
+}
+
+fn make_form() -> fltk::window::Window {
+    let image = fltk::image::SvgImage::from_data(ICON).unwrap();
+    let height = (ROW_HEIGHT + 2) * (Command::ALL.len() as i32 + 3)
+        + BUTTON_HEIGHT
+        + PAD * 2;
+    let mut form = fltk::window::Window::default()
+        .with_size(LABEL_WIDTH + 200, height)
+        .with_label(&trf("Key Bindings — {0}", &[&APPNAME]));
+    if let Some(window) = fltk::app::first_window() {
+        form.set_pos(window.x() + 50, window.y() + 50);
+    }
+    form.set_icon(Some(image));
+    form
+}
+
+fn make_preset_row() -> (fltk::group::Flex, fltk::menu::Choice) {
+    let mut row = fltk::group::Flex::default().row();
+    let mut label = fltk::button::Button::default()
+        .with_label(tr("&Preset"))
+        .with_align(fltk::enums::Align::Inside | fltk::enums::Align::Left);
+    label.set_frame(fltk::enums::FrameType::NoBox);
+    let mut choice = fltk::menu::Choice::default();
+    choice.add_choice(tr("Custom"));
+    for preset in KeyPreset::ALL {
+        choice.add_choice(tr(preset.name()));
+    }
+    choice.set_value(0);
+    choice.set_tooltip(tr(
+        "Replace all the key bindings with those of a preset: Standard \
+        (the defaults) or Vi with h, j, k and l to move the focus",
+    ));
+    row.set_size(&label, LABEL_WIDTH);
+    row.end();
+    label.set_callback({
+        let mut choice = choice.clone();
+        move |_| {
+            choice.take_focus().unwrap();
+        }
+    });
+    (row, choice)
+}
+
+fn make_row(
+    command: Command,
+    keys: &KeyBindings,
+) -> (fltk::group::Flex, fltk::input::Input) {
+    let mut row = fltk::group::Flex::default().row();
+    let mut label = fltk::button::Button::default()
+        .with_label(command.description())
+        .with_align(fltk::enums::Align::Inside | fltk::enums::Align::Left);
+    label.set_frame(fltk::enums::FrameType::NoBox);
+    let mut input = fltk::input::Input::default();
+    input.set_value(&chords_text(keys.chords(command)));
+    input.set_tooltip(tr(
+        "Keys separated by spaces, each a character or Space, Esc, Tab, \
+        Enter, BackSpace, Insert, Delete, Help, Left, Right, Up, Down, \
+        Home, End, PgUp, PgDn or F1 to F12, optionally after Ctrl+, Alt+ \
+        or Shift+, e.g., Ctrl+Shift+n",
+    ));
+    row.set_size(&label, LABEL_WIDTH);
+    row.end();
+    label.set_callback({
+        let mut input = input.clone();
+        move |_| {
+            input.take_focus().unwrap();
+        }
+    });
+    (row, input)
+}
+
+fn make_buttons(
+) -> (fltk::group::Flex, fltk::button::Button, fltk::button::Button) {
+    let mut row = fltk::group::Flex::default().size_of_parent().row();
+    fltk::frame::Frame::default(); // pad left of buttons
+    let ok_button = fltk::button::Button::default().with_label(tr("&OK"));
+    let cancel_button =
+        fltk::button::Button::default().with_label(tr("&Cancel"));
+    fltk::frame::Frame::default(); // pad right of buttons
+    row.set_size(&ok_button, BUTTON_WIDTH);
+    row.set_size(&cancel_button, BUTTON_WIDTH);
+    row.end();
+    (row, ok_button, cancel_button)
+}
+
+fn add_event_handlers(
+    form: &mut fltk::window::Window,
+    preset_choice: &mut fltk::menu::Choice,
+    inputs: &mut [fltk::input::Input],
+    problems: &mut fltk::frame::Frame,
+    ok_button: &mut fltk::button::Button,
+    cancel_button: &mut fltk::button::Button,
+    ok: Rc<RefCell<bool>>,
+) {
+    // Problems are shown as soon as they arise
+    let all_inputs = inputs.to_vec();
+    for input in inputs.iter_mut() {
+        input.set_trigger(fltk::enums::CallbackTrigger::Changed);
+        input.set_callback({
+            let inputs = all_inputs.clone();
+            let mut problems = problems.clone();
+            let mut preset_choice = preset_choice.clone();
+            move |_| {
+                preset_choice.set_value(0); // Custom
+                show_problems(&inputs, &mut problems);
+            }
+        });
+    }
+    preset_choice.set_callback({
+        let mut inputs = inputs.to_vec();
+        let mut problems = problems.clone();
+        move |preset_choice| {
+            if let Some(&preset) =
+                KeyPreset::ALL.get((preset_choice.value() - 1) as usize)
+            {
+                let keys = KeyBindings::preset(preset);
+                for (input, command) in inputs.iter_mut().zip(Command::ALL)
+                {
+                    input.set_value(&chords_text(keys.chords(command)));
+                }
+                show_problems(&inputs, &mut problems);
+            }
+        }
+    });
+    ok_button.set_callback({
+        let inputs = inputs.to_vec();
+        let mut form = form.clone();
+        move |_| match get_keys(&inputs) {
+            Ok(keys) => {
+                *ok.borrow_mut() = true;
+                CONFIG.get().write().unwrap().keys = keys;
+                form.hide();
+            }
+            Err(problems) => util::warning(&problems.join("\n")),
+        }
+    });
+    cancel_button.set_callback({
+        let mut form = form.clone();
+        move |_| {
+            form.hide();
+        }
+    });
+}
+
+fn show_problems(
+    inputs: &[fltk::input::Input],
+    problems: &mut fltk::frame::Frame,
+) {
+    problems.set_label(&match get_keys(inputs) {
+        Ok(_) => String::new(),
+        Err(problems) => problems.join("\n"),
+    });
+    problems.redraw();
+}
+
+// Returns the key bindings from the inputs or the reasons they can't be
+// used: unknown keys and keys bound to more than one command
+fn get_keys(
+    inputs: &[fltk::input::Input],
+) -> Result<KeyBindings, Vec<String>> {
+    let mut keys = KeyBindings::default();
+    let mut problems = vec![];
+    for (input, command) in inputs.iter().zip(Command::ALL) {
+        let mut chords = vec![];
+        for text in input.value().split_whitespace() {
+            match Chord::parse(text) {
+                Some(chord) if !chord.is_modifier() => chords.push(chord),
+                _ => problems.push(trf(
+                    "“{0}” isn't a key ({1})",
+                    &[&text, &command.description()],
+                )),
+            }
+        }
+        keys.set(command, chords);
+    }
+    problems.extend(keys.conflicts());
+    if problems.is_empty() {
+        Ok(keys)
+    } else {
+        Err(problems)
+    }
+}
+
+fn chords_text(chords: &[Chord]) -> String {
+    chords
+        .iter()
+        .map(|chord| chord.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
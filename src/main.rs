@@ -2,33 +2,70 @@
 // License: GPLv3
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod ai;
+mod animation;
 mod application;
 mod board;
 mod board_util;
 mod config;
+mod engine;
 mod fixed;
+mod generator;
+mod highscores;
 mod html_form;
+mod i18n;
+mod i18n_de;
+mod keys;
+mod keys_form;
 mod mainwindow;
 mod options_form;
+mod palette;
+mod preset;
+mod profile;
+mod profiles_form;
+mod race;
+mod rating;
+mod solver;
+mod storage;
+mod strategy;
+mod theme;
+mod tileset;
+mod tournament;
 mod util;
 
 use crate::application::Application;
 use crate::fixed::{initialize_colors, APPNAME};
-use std::{panic, sync};
+use crate::i18n::trf;
+use std::{env, panic, process, sync};
 
 pub static CONFIG: state::Storage<sync::RwLock<config::Config>> =
     state::Storage::new();
 
 fn main() {
+    // The tournament has no GUI; on Windows only debug builds have a
+    // console to print to
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some(tournament::FLAG) {
+        process::exit(tournament::run(&args[1..]));
+    }
     panic::set_hook(Box::new(|info| {
         let err = dbg!(&info);
-        fltk::dialog::message_title(&format!("Error — {APPNAME}"));
+        fltk::dialog::message_title(&trf("Error — {0}", &[&APPNAME]));
         let x = util::x() - 200;
         let y = util::y() - 100;
         fltk::dialog::message(x, y, &err.to_string());
     }));
     initialize_colors(); // *MUST* be done before CONFIG is created
+
+    // The config's language is set as soon as it is read so that any
+    // problems with the config are reported in it
+    i18n::initialize(i18n::Language::detect());
     CONFIG.set(sync::RwLock::new(config::Config::new()));
+    let errors = theme::initialize(); // *MUST* be done after CONFIG
     let mut app = Application::new();
+    for error in errors {
+        util::warning(&trf("Failed to read theme: {0}", &[&error]));
+    }
+    config::report_problems();
     app.run();
 }
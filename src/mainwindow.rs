@@ -4,136 +4,181 @@
 use super::CONFIG;
 use crate::board;
 use crate::fixed::{
-    Action, ABOUT_ICON, APPNAME, HELP_ICON, ICON, NEW_ICON, OPTIONS_ICON,
-    PAD, QUIT_ICON, TOOLBAR_HEIGHT, TOOLBUTTON_SIZE,
+    Action, ABOUT_ICON, APPNAME, HELP_ICON, HIGHSCORES_ICON, ICON,
+    KEYS_ICON, NEW_ICON, OPTIONS_ICON, PAD, PAUSE_ICON, PROFILES_ICON,
+    QUIT_ICON, TOOLBAR_HEIGHT, TOOLBUTTON_SIZE,
 };
+use crate::i18n::{self, tr};
+use crate::keys::{Chord, Command, KeyBindings};
+use crate::preset::{self, Preset};
 use crate::util;
 use fltk::prelude::*;
-use thousands::Separable;
+
+pub type ToolButtons = Vec<(fltk::button::Button, Command)>;
+
+const TOOLBUTTON_WIDTH: i32 = TOOLBUTTON_SIZE + PAD + 8;
 
 pub fn make(
     sender: fltk::app::Sender<Action>,
 ) -> (
     fltk::window::Window,
+    ToolButtons,
     board::Board,
     fltk::frame::Frame,
     fltk::frame::Frame,
+    fltk::frame::Frame,
+    fltk::frame::Frame,
 ) {
     fltk::window::Window::set_default_xclass(APPNAME);
     let icon = fltk::image::SvgImage::from_data(ICON).unwrap();
     let (x, y, width, height) = get_config_window_rect();
-    let mut mainwindow =
-        fltk::window::Window::new(x, y, width, height, APPNAME);
+    let mut mainwindow = fltk::window::Window::new(x, y, width, height, "");
+    mainwindow.set_label(&title());
     mainwindow.set_icon(Some(icon));
-    let size = ((TOOLBUTTON_SIZE * 4) / 3) * 6;
-    mainwindow.size_range(size, size, size * 4, size * 4);
+    let size = ((TOOLBUTTON_SIZE * 4) / 3) * 9; // room for the status row
+    let width_min = TOOLBUTTON_WIDTH * 11; // room for the toolbar
+    mainwindow.size_range(width_min, size, size * 4, size * 4);
     mainwindow.make_resizable(true);
     let mut vbox = fltk::group::Flex::default().column().size_of_parent();
     vbox.set_margin(PAD);
-    let toolbar = add_toolbar(sender, width);
+    let (toolbar, mut toolbuttons) = add_toolbar(sender, width);
     vbox.set_size(&toolbar, TOOLBAR_HEIGHT);
+    update_tooltips(&mut toolbuttons, &CONFIG.get().read().unwrap().keys);
     let mut board = board::Board::new(sender);
     board.set_size(width, height - (TOOLBAR_HEIGHT * 2));
-    let (statusbar, scorelabel) = add_status_row(&mut vbox, width);
+    let (statusbar, badgelabel, timelabel, scorelabel) =
+        add_status_row(&mut vbox, width);
     vbox.end();
     mainwindow.end();
-    (mainwindow, board, statusbar, scorelabel)
+    (
+        mainwindow,
+        toolbuttons,
+        board,
+        statusbar,
+        badgelabel,
+        timelabel,
+        scorelabel,
+    )
 }
 
 fn add_toolbar(
     sender: fltk::app::Sender<Action>,
     width: i32,
-) -> fltk::group::Flex {
+) -> (fltk::group::Flex, ToolButtons) {
     let mut button_box =
         fltk::group::Flex::default().row().with_size(width, TOOLBAR_HEIGHT);
     button_box.set_frame(fltk::enums::FrameType::UpBox);
     button_box.set_margin(PAD);
-    add_toolbutton(
-        sender,
-        'n',
-        "New game • n",
-        Action::New,
-        NEW_ICON,
-        &mut button_box,
-    );
-    add_toolbutton(
-        sender,
-        'o',
-        "Options… • o",
-        Action::Options,
-        OPTIONS_ICON,
-        &mut button_box,
-    );
-    fltk::frame::Frame::default().with_size(PAD, PAD);
-    add_toolbutton(
-        sender,
-        'a',
-        "About • a",
-        Action::About,
-        ABOUT_ICON,
-        &mut button_box,
-    );
-    add_toolbutton(
-        sender,
-        'h',
-        "Help • F1 or h",
-        Action::Help,
-        HELP_ICON,
-        &mut button_box,
-    );
-    fltk::frame::Frame::default().with_size(PAD, PAD);
-    add_toolbutton(
-        sender,
-        'q',
-        "Quit • Esc or q",
-        Action::Quit,
-        QUIT_ICON,
-        &mut button_box,
-    );
+    let mut toolbuttons = vec![];
+    for (command, icon) in [
+        (Command::NewGame, NEW_ICON),
+        (Command::Pause, PAUSE_ICON),
+        (Command::Options, OPTIONS_ICON),
+        (Command::KeyBindings, KEYS_ICON),
+        (Command::Profiles, PROFILES_ICON),
+        (Command::HighScores, HIGHSCORES_ICON),
+        (Command::About, ABOUT_ICON),
+        (Command::Help, HELP_ICON),
+        (Command::Quit, QUIT_ICON),
+    ] {
+        if matches!(command, Command::About | Command::Quit) {
+            fltk::frame::Frame::default().with_size(PAD, PAD);
+        }
+        let button =
+            add_toolbutton(sender, command.action(), icon, &mut button_box);
+        toolbuttons.push((button, command));
+    }
     button_box.end();
-    button_box
+    (button_box, toolbuttons)
 }
 
+// Keys are handled by the main window using the key bindings rather than
+// by toolbutton shortcuts
 fn add_toolbutton(
     sender: fltk::app::Sender<Action>,
-    shortcut: char,
-    tooltip: &str,
     action: Action,
     icon: &str,
     button_box: &mut fltk::group::Flex,
-) {
-    let width = TOOLBUTTON_SIZE + PAD + 8;
+) -> fltk::button::Button {
+    let width = TOOLBUTTON_WIDTH;
     let mut button = fltk::button::Button::default();
     button.set_size(width, TOOLBUTTON_SIZE + PAD);
     button.visible_focus(false);
     button.set_label_size(0);
-    button.set_shortcut(fltk::enums::Shortcut::from_char(shortcut));
-    button.set_tooltip(tooltip);
     let mut icon = fltk::image::SvgImage::from_data(icon).unwrap();
     icon.scale(TOOLBUTTON_SIZE, TOOLBUTTON_SIZE, true, true);
     button.set_image(Some(icon));
     button.emit(sender, action);
     button_box.set_size(&button, width);
+    button
+}
+
+// Each tooltip shows the keys currently bound to the button's command
+pub fn update_tooltips(toolbuttons: &mut ToolButtons, keys: &KeyBindings) {
+    for (button, command) in toolbuttons.iter_mut() {
+        let name = match command {
+            Command::NewGame => tr("New game"),
+            Command::Pause => tr("Pause or resume"),
+            Command::Options => tr("Options…"),
+            Command::KeyBindings => tr("Key bindings…"),
+            Command::Profiles => tr("Profiles…"),
+            Command::HighScores => tr("High scores"),
+            Command::About => tr("About"),
+            Command::Help => tr("Help"),
+            _ => command.description(),
+        };
+        let chords = keys.describe(*command);
+        button.set_tooltip(&if chords.is_empty() {
+            name.to_string()
+        } else {
+            format!("{name} • {chords}")
+        });
+    }
+}
+
+// The title shows whose profile is being played
+pub fn title() -> String {
+    format!("{APPNAME} — {}", CONFIG.get().read().unwrap().profile)
 }
 
 fn add_status_row(
     vbox: &mut fltk::group::Flex,
     width: i32,
-) -> (fltk::frame::Frame, fltk::frame::Frame) {
+) -> (
+    fltk::frame::Frame,
+    fltk::frame::Frame,
+    fltk::frame::Frame,
+    fltk::frame::Frame,
+) {
     let mut status_row = fltk::group::Flex::default()
         .row()
         .with_size(width, TOOLBUTTON_SIZE);
     let mut statusbar = fltk::frame::Frame::default();
     statusbar.set_frame(fltk::enums::FrameType::EngravedFrame);
+    let mut badgelabel = fltk::frame::Frame::default(); // about the board
+    badgelabel.set_frame(fltk::enums::FrameType::EngravedFrame);
+    badgelabel.set_label_size(12);
+    status_row.set_size(&badgelabel, 80);
+    let mut timelabel = fltk::frame::Frame::default();
+    timelabel.set_frame(fltk::enums::FrameType::EngravedFrame);
+    timelabel.set_tooltip(tr("Time left"));
+    status_row.set_size(&timelabel, 60);
+    let setup = board::new_setup();
     let config = CONFIG.get().read().unwrap();
-    let mut scorelabel = fltk::frame::Frame::default().with_label(
-        &format!("0 • {}", config.board_highscore.separate_with_commas()),
+    let category = preset::category(
+        config.board_variant,
+        Preset::of_setup(&setup),
+        setup.generator,
+        config.board_solvable,
     );
+    let highscore = config.highscores.best(&category);
+    let mut scorelabel = fltk::frame::Frame::default()
+        .with_label(&format!("0 • {}", i18n::number(highscore)));
     scorelabel.set_frame(fltk::enums::FrameType::EngravedFrame);
     status_row.set_size(&scorelabel, 120);
     status_row.end();
     vbox.set_size(&status_row, TOOLBUTTON_SIZE);
-    (statusbar, scorelabel)
+    (statusbar, badgelabel, timelabel, scorelabel)
 }
 
 fn get_config_window_rect() -> (i32, i32, i32, i32) {
@@ -161,46 +206,30 @@ pub fn add_event_handlers(
     mainwindow: &mut fltk::window::Window,
     sender: fltk::app::Sender<Action>,
 ) {
-    // Both of these are really needed!
+    // Both of these are really needed! The callback only quits on Close:
+    // Esc (like every other key) is handled using the key bindings.
     mainwindow.set_callback(move |_| {
-        if fltk::app::event() == fltk::enums::Event::Close
-            || fltk::app::event_key() == fltk::enums::Key::Escape
-        {
+        if fltk::app::event() == fltk::enums::Event::Close {
             sender.send(Action::Quit);
         }
     });
     mainwindow.handle(move |_, event| {
-        if event == fltk::enums::Event::KeyUp
-            && fltk::app::event_key().bits() == 32
-        {
-            sender.send(Action::PressTile); // Space
-            return true;
+        if matches!(
+            event,
+            fltk::enums::Event::Unfocus | fltk::enums::Event::Hide
+        ) {
+            sender.send(Action::AutoPause);
+            return false;
         }
-        match event {
-            fltk::enums::Event::KeyUp => match fltk::app::event_key() {
-                fltk::enums::Key::Help | fltk::enums::Key::F1 => {
-                    sender.send(Action::Help);
-                    true
-                }
-                fltk::enums::Key::Up => {
-                    sender.send(Action::MoveUp);
-                    true
-                }
-                fltk::enums::Key::Down => {
-                    sender.send(Action::MoveDown);
-                    true
-                }
-                fltk::enums::Key::Left => {
-                    sender.send(Action::MoveLeft);
-                    true
-                }
-                fltk::enums::Key::Right => {
-                    sender.send(Action::MoveRight);
-                    true
-                }
-                _ => false,
-            },
-            _ => false,
+        if event == fltk::enums::Event::KeyUp {
+            let chord = Chord::from_event();
+            if let Some(command) =
+                CONFIG.get().read().unwrap().keys.command(chord)
+            {
+                sender.send(command.action());
+                return true;
+            }
         }
+        false
     });
 }
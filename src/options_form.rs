@@ -2,10 +2,21 @@
 // License: GPLv3
 
 use super::CONFIG;
+use crate::ai::Strength;
+use crate::board_util;
+use crate::engine::{Size, Specials};
+use crate::fixed::Variant;
 use crate::fixed::{
     APPNAME, BUTTON_HEIGHT, BUTTON_WIDTH, COLORS, COLORS_MIN, DELAY_MS_MAX,
-    DELAY_MS_MIN, ICON, PAD, SCALE_MAX, SCALE_MIN, SIZE_MAX, SIZE_MIN,
+    DELAY_MS_MIN, DISTANCE_MAX, DISTANCE_MIN, ICON, PAD, PREVIEW_HEIGHT,
+    SCALE_MAX, SCALE_MIN, SIZE_MAX, SIZE_MIN, SPECIALS_MAX, SPECIALS_MIN,
 };
+use crate::generator::Generator;
+use crate::i18n::{self, tr, trf, Language};
+use crate::palette::{self, Palette};
+use crate::preset::Preset;
+use crate::theme::{self, Theme, THEME};
+use crate::tileset;
 use crate::util;
 use fltk::prelude::*;
 use std::cell::RefCell;
@@ -24,6 +35,8 @@ impl Form {
             fltk::group::Flex::default().size_of_parent().column();
         vbox.set_margin(PAD);
         vbox.set_pad(PAD);
+        let choices = make_choices();
+        vbox.set_size(&choices.preview, PREVIEW_HEIGHT);
         let spinners = make_spinners();
         let (button_row, mut buttons) = make_buttons();
         vbox.set_size(&button_row, BUTTON_HEIGHT);
@@ -32,6 +45,7 @@ impl Form {
         form.make_modal(true);
         add_event_handlers(
             &mut form,
+            &choices,
             &spinners,
             &mut buttons,
             Rc::clone(&ok),
@@ -50,11 +64,34 @@ impl Drop for Form {
     }
 }
 
+struct Choices {
+    language_choice: fltk::menu::Choice,
+    variant_choice: fltk::menu::Choice,
+    strength_choice: fltk::menu::Choice,
+    palette_choice: fltk::menu::Choice,
+    symbols_checkbox: fltk::button::CheckButton,
+    reduced_motion_checkbox: fltk::button::CheckButton,
+    wrap_checkbox: fltk::button::CheckButton,
+    solvable_checkbox: fltk::button::CheckButton,
+    generator_choice: fltk::menu::Choice,
+    theme_choice: fltk::menu::Choice,
+    themes: Vec<Theme>,
+    tileset_choice: fltk::menu::Choice,
+    tilesets: Vec<String>,
+    preview: fltk::widget::Widget, // of the chosen theme
+}
+
 struct Spinners {
+    preset_choice: fltk::menu::Choice, // sets the spinners below it
     columns_spinner: fltk::misc::Spinner,
     rows_spinner: fltk::misc::Spinner,
     max_colors_spinner: fltk::misc::Spinner,
+    min_distance_spinner: fltk::misc::Spinner,
     delay_ms_spinner: fltk::misc::Spinner,
+    bombs_spinner: fltk::misc::Spinner,
+    wildcards_spinner: fltk::misc::Spinner,
+    stones_spinner: fltk::misc::Spinner,
+    multipliers_spinner: fltk::misc::Spinner,
     scale_spinner: fltk::misc::Spinner,
 }
 
@@ -66,8 +103,8 @@ struct Buttons {
 fn make_form() -> fltk::window::Window {
     let image = fltk::image::SvgImage::from_data(ICON).unwrap();
     let mut form = fltk::window::Window::default()
-        .with_size(200, 200)
-        .with_label(&format!("Options — {APPNAME}"));
+        .with_size(280, 812)
+        .with_label(&trf("Options — {0}", &[&APPNAME]));
     if let Some(window) = fltk::app::first_window() {
         form.set_pos(window.x() + 50, window.y() + 100);
     }
@@ -75,55 +112,312 @@ fn make_form() -> fltk::window::Window {
     form
 }
 
+fn make_choices() -> Choices {
+    let config = CONFIG.get().read().unwrap();
+    let names: Vec<&str> =
+        Language::ALL.iter().map(|language| language.name()).collect();
+    let index = Language::ALL
+        .iter()
+        .position(|&language| language == config.window_language)
+        .unwrap_or_default();
+    let language_choice = make_choice_row(
+        tr("&Language"),
+        &names,
+        index,
+        tr("The language of the user interface (default the system's)"),
+    );
+    let names: Vec<&str> =
+        Variant::ALL.iter().map(|variant| tr(variant.name())).collect();
+    let index = Variant::ALL
+        .iter()
+        .position(|&variant| variant == config.board_variant)
+        .unwrap_or_default();
+    let mut variant_choice = make_choice_row(
+        tr("&Game"),
+        &names,
+        index,
+        tr("Classic; Time Attack to score as much as possible against the \
+        clock; Endless with new tiles after every move; or Hot Seat for \
+        two players taking turns; or Versus Computer (default Classic)"),
+    );
+    variant_choice.take_focus().unwrap();
+    let names: Vec<&str> =
+        Strength::ALL.iter().map(|strength| tr(strength.name())).collect();
+    let index = Strength::ALL
+        .iter()
+        .position(|&strength| strength == config.board_ai_strength)
+        .unwrap_or_default();
+    let strength_choice = make_choice_row(
+        tr("Com&puter"),
+        &names,
+        index,
+        tr("How the computer plays in the Versus Computer game: Random; \
+        Greedy takes the highest scoring move; Lookahead also considers \
+        your best reply (default Greedy)"),
+    );
+    let names: Vec<&str> =
+        Palette::ALL.iter().map(|palette| tr(palette.name())).collect();
+    let index = Palette::ALL
+        .iter()
+        .position(|&palette| palette == config.board_palette)
+        .unwrap_or_default();
+    let palette_choice = make_choice_row(
+        tr("P&alette"),
+        &names,
+        index,
+        tr("The tile colors: Standard (the theme's); or one designed for \
+        deuteranopia, protanopia or tritanopia (default Standard)"),
+    );
+    let mut symbols_checkbox =
+        fltk::button::CheckButton::default().with_label(tr("S&ymbols"));
+    symbols_checkbox.set_checked(config.board_symbols);
+    symbols_checkbox.set_tooltip(tr(
+        "Draw a shape or letter on each tile so that every color can be \
+        told apart without seeing it (default off)",
+    ));
+    let mut reduced_motion_checkbox = fltk::button::CheckButton::default()
+        .with_label(tr("Reduced Motio&n"));
+    reduced_motion_checkbox.set_checked(config.board_reduced_motion);
+    reduced_motion_checkbox.set_tooltip(tr(
+        "Remove tiles at once and move tiles straight to their new places \
+        rather than animating them (default off)",
+    ));
+    let mut wrap_checkbox =
+        fltk::button::CheckButton::default().with_label(tr("&Focus Wraps"));
+    wrap_checkbox.set_checked(config.board_wrap);
+    wrap_checkbox.set_tooltip(tr(
+        "Moving the keyboard focus past the last tile in a row or column \
+        goes round to the other side (default off)",
+    ));
+    let mut solvable_checkbox = fltk::button::CheckButton::default()
+        .with_label(tr("Chec&k Boards Are Solvable"));
+    solvable_checkbox.set_checked(config.board_solvable);
+    solvable_checkbox.set_tooltip(tr(
+        "Only play boards that have been proven to be clearable, marked ✓; \
+        their high scores are kept apart (default off)",
+    ));
+    let names: Vec<&str> = Generator::ALL
+        .iter()
+        .map(|generator| tr(generator.name()))
+        .collect();
+    let index = Generator::ALL
+        .iter()
+        .position(|&generator| generator == config.board_generator)
+        .unwrap_or_default();
+    let generator_choice = make_choice_row(
+        tr("&Jumble"),
+        &names,
+        index,
+        tr("How a new board's colors are laid out: Uniform at random; \
+        Balanced with as many tiles of each color; Clustered for bigger \
+        groups; Scattered for smaller ones; or Mirror or Rotational for \
+        symmetric boards (default Uniform)"),
+    );
+    let (themes, _) = theme::all(); // errors are reported at startup
+    let names: Vec<&str> =
+        themes.iter().map(|theme| tr(theme.name.as_str())).collect();
+    let index = themes
+        .iter()
+        .position(|theme| theme.name == config.board_theme)
+        .unwrap_or_default();
+    let mut theme_choice = make_choice_row(
+        tr("T&heme"),
+        &names,
+        index,
+        &trf(
+            "The board's colors, fonts and tile style: Light, Dark, \
+            High-Contrast, or one from {0} (default Light)",
+            &[&theme::themes_dir().display()],
+        ),
+    );
+    let tilesets = tileset::names();
+    let names: Vec<&str> =
+        tilesets.iter().map(|name| tr(name.as_str())).collect();
+    let index = tilesets
+        .iter()
+        .position(|name| name == &config.board_tileset)
+        .unwrap_or_default();
+    let tileset_choice = make_choice_row(
+        tr("Til&e Set"),
+        &names,
+        index,
+        &trf(
+            "Draw the tiles as images: None for plain colored tiles, Gems, \
+            or a tile set from {0} (default None)",
+            &[&tileset::tilesets_dir().display()],
+        ),
+    );
+    let index = theme_choice.value() as usize;
+    let chosen = Rc::new(RefCell::new(themes[index].clone()));
+    let mut preview = fltk::widget::Widget::default();
+    preview.draw({
+        let chosen = Rc::clone(&chosen);
+        move |widget| {
+            board_util::draw_preview(
+                widget.x(),
+                widget.y(),
+                widget.width(),
+                widget.height(),
+                &chosen.borrow(),
+            );
+            // *MUST* restore the line style after custom drawing
+            fltk::draw::set_line_style(fltk::draw::LineStyle::Solid, 0);
+        }
+    });
+    theme_choice.set_callback({
+        let themes = themes.clone();
+        let mut preview = preview.clone();
+        move |theme_choice| {
+            if let Some(theme) = themes.get(theme_choice.value() as usize) {
+                *chosen.borrow_mut() = theme.clone();
+                preview.redraw();
+            }
+        }
+    });
+    Choices {
+        language_choice,
+        variant_choice,
+        strength_choice,
+        palette_choice,
+        symbols_checkbox,
+        reduced_motion_checkbox,
+        wrap_checkbox,
+        solvable_checkbox,
+        generator_choice,
+        theme_choice,
+        themes,
+        tileset_choice,
+        tilesets,
+        preview,
+    }
+}
+
 fn make_spinners() -> Spinners {
     let config = CONFIG.get().read().unwrap();
-    let mut columns_spinner = make_row(
-        "Co&lumns",
+    let names: Vec<&str> =
+        Preset::ALL.iter().map(|preset| tr(preset.name())).collect();
+    let preset = Preset::of(
+        Size::new(config.board_columns as i32, config.board_rows as i32),
+        config.board_maxcolors,
+        Specials {
+            bombs: config.board_bombs,
+            wildcards: config.board_wildcards,
+            stones: config.board_stones,
+            multipliers: config.board_multipliers,
+        },
+    );
+    let index = Preset::ALL
+        .iter()
+        .position(|&other| other == preset)
+        .unwrap_or_default();
+    let preset_choice = make_choice_row(
+        tr("Difficulty Le&vel"),
+        &names,
+        index,
+        tr("Set the columns, rows, max. colors and special tiles \
+        together: Beginner, Normal, Expert or Marathon; or Custom for your \
+        own. High scores and statistics are kept for each (default \
+        Normal)"),
+    );
+    let columns_spinner = make_row(
+        tr("Col&umns"),
         config.board_columns as f64,
-        "Columns (default 9)",
+        tr("Columns (default 9)"),
         SIZE_MIN as f64,
         SIZE_MAX as f64,
         1.0,
     );
-    columns_spinner.take_focus().unwrap();
     let rows_spinner = make_row(
-        "&Rows",
+        tr("&Rows"),
         config.board_rows as f64,
-        "Rows (default 9)",
+        tr("Rows (default 9)"),
         SIZE_MIN as f64,
         SIZE_MAX as f64,
         1.0,
     );
     let limit = COLORS.get().read().unwrap().len();
     let max_colors_spinner = make_row(
-        "&Max. Colors",
+        tr("Ma&x. Colors"),
         config.board_maxcolors as f64,
-        "Max. Colors (default 4)",
+        tr("Max. Colors (default 4)"),
         COLORS_MIN as f64,
         limit as f64,
         1.0,
     );
+    let min_distance_spinner = make_row(
+        tr("Color D&istance"),
+        config.board_min_distance as f64,
+        tr("The smallest acceptable difference between any two colors \
+        (CIEDE2000, where 2 is just noticeable); Options warns if there \
+        aren't enough colors this different (default 20)"),
+        DISTANCE_MIN as f64,
+        DISTANCE_MAX as f64,
+        1.0,
+    );
     let delay_ms_spinner = make_row(
-        "&Delay (ms)",
+        tr("&Delay (ms)"),
         config.board_delay_ms as f64,
-        "Delay to show tile movement (default 250 milliseconds)",
+        tr("Delay to show the tiles about to be removed (default 250 \
+        milliseconds)"),
         DELAY_MS_MIN as f64,
         DELAY_MS_MAX as f64,
         1.0,
     );
+    let bombs_spinner = make_row(
+        tr("&Bombs (%)"),
+        config.board_bombs as f64,
+        tr("Percentage of bombs, which clear the tiles around them when \
+        removed (default 0)"),
+        SPECIALS_MIN as f64,
+        SPECIALS_MAX as f64,
+        1.0,
+    );
+    let wildcards_spinner = make_row(
+        tr("&Wildcards (%)"),
+        config.board_wildcards as f64,
+        tr("Percentage of wildcards, which join any adjoining color \
+        (default 0)"),
+        SPECIALS_MIN as f64,
+        SPECIALS_MAX as f64,
+        1.0,
+    );
+    let stones_spinner = make_row(
+        tr("S&tones (%)"),
+        config.board_stones as f64,
+        tr("Percentage of stones, which never move and only bombs can \
+        remove (default 0)"),
+        SPECIALS_MIN as f64,
+        SPECIALS_MAX as f64,
+        1.0,
+    );
+    let multipliers_spinner = make_row(
+        tr("&Multipliers (%)"),
+        config.board_multipliers as f64,
+        tr("Percentage of multipliers, which double the score of the tiles \
+        removed with them (default 0)"),
+        SPECIALS_MIN as f64,
+        SPECIALS_MAX as f64,
+        1.0,
+    );
     let scale_spinner = make_row(
-        "&Scale",
+        tr("&Scale"),
         config.window_scale as f64,
-        "User interface scale (default 1.0)",
+        tr("User interface scale (default 1.0)"),
         SCALE_MIN as f64,
         SCALE_MAX as f64,
         0.1,
     );
     Spinners {
+        preset_choice,
         columns_spinner,
         rows_spinner,
         max_colors_spinner,
+        min_distance_spinner,
         delay_ms_spinner,
+        bombs_spinner,
+        wildcards_spinner,
+        stones_spinner,
+        multipliers_spinner,
         scale_spinner,
     }
 }
@@ -156,12 +450,39 @@ fn make_row(
     spinner
 }
 
+fn make_choice_row(
+    label: &str,
+    choices: &[&str],
+    index: usize,
+    tooltip: &str,
+) -> fltk::menu::Choice {
+    let row = fltk::group::Flex::default().row();
+    let mut label = fltk::button::Button::default()
+        .with_label(label)
+        .with_align(fltk::enums::Align::Inside | fltk::enums::Align::Left);
+    label.set_frame(fltk::enums::FrameType::NoBox);
+    let mut choice = fltk::menu::Choice::default();
+    for item in choices {
+        choice.add_choice(item);
+    }
+    choice.set_value(index as i32);
+    choice.set_tooltip(tooltip);
+    row.end();
+    label.set_callback({
+        let mut choice = choice.clone();
+        move |_| {
+            choice.take_focus().unwrap();
+        }
+    });
+    choice
+}
+
 fn make_buttons() -> (fltk::group::Flex, Buttons) {
     let mut row = fltk::group::Flex::default().size_of_parent().row();
     fltk::frame::Frame::default(); // pad left of buttons
-    let ok_button = fltk::button::Button::default().with_label("&OK");
+    let ok_button = fltk::button::Button::default().with_label(tr("&OK"));
     let cancel_button =
-        fltk::button::Button::default().with_label("&Cancel");
+        fltk::button::Button::default().with_label(tr("&Cancel"));
     fltk::frame::Frame::default(); // pad right of buttons
     row.set_size(&ok_button, BUTTON_WIDTH);
     row.set_size(&cancel_button, BUTTON_WIDTH);
@@ -171,31 +492,107 @@ fn make_buttons() -> (fltk::group::Flex, Buttons) {
 
 fn add_event_handlers(
     form: &mut fltk::window::Window,
+    choices: &Choices,
     spinners: &Spinners,
     buttons: &mut Buttons,
     ok: Rc<RefCell<bool>>,
 ) {
     buttons.ok_button.set_callback({
+        let language_choice = choices.language_choice.clone();
+        let variant_choice = choices.variant_choice.clone();
+        let strength_choice = choices.strength_choice.clone();
+        let palette_choice = choices.palette_choice.clone();
+        let symbols_checkbox = choices.symbols_checkbox.clone();
+        let reduced_motion_checkbox =
+            choices.reduced_motion_checkbox.clone();
+        let wrap_checkbox = choices.wrap_checkbox.clone();
+        let solvable_checkbox = choices.solvable_checkbox.clone();
+        let generator_choice = choices.generator_choice.clone();
+        let theme_choice = choices.theme_choice.clone();
+        let themes = choices.themes.clone();
+        let tileset_choice = choices.tileset_choice.clone();
+        let tilesets = choices.tilesets.clone();
         let columns_spinner = spinners.columns_spinner.clone();
         let rows_spinner = spinners.rows_spinner.clone();
         let max_colors_spinner = spinners.max_colors_spinner.clone();
+        let min_distance_spinner = spinners.min_distance_spinner.clone();
         let delay_ms_spinner = spinners.delay_ms_spinner.clone();
+        let bombs_spinner = spinners.bombs_spinner.clone();
+        let wildcards_spinner = spinners.wildcards_spinner.clone();
+        let stones_spinner = spinners.stones_spinner.clone();
+        let multipliers_spinner = spinners.multipliers_spinner.clone();
         let scale_spinner = spinners.scale_spinner.clone();
         let mut form = form.clone();
         move |_| {
             *ok.borrow_mut() = true;
             let mut config = CONFIG.get().write().unwrap();
+            if let Some(&language) =
+                Language::ALL.get(language_choice.value() as usize)
+            {
+                config.window_language = language;
+                i18n::set_language(language);
+            }
+            if let Some(&variant) =
+                Variant::ALL.get(variant_choice.value() as usize)
+            {
+                config.board_variant = variant;
+            }
+            if let Some(&strength) =
+                Strength::ALL.get(strength_choice.value() as usize)
+            {
+                config.board_ai_strength = strength;
+            }
+            if let Some(&palette) =
+                Palette::ALL.get(palette_choice.value() as usize)
+            {
+                config.board_palette = palette;
+            }
+            config.board_symbols = symbols_checkbox.is_checked();
+            config.board_reduced_motion =
+                reduced_motion_checkbox.is_checked();
+            config.board_wrap = wrap_checkbox.is_checked();
+            config.board_solvable = solvable_checkbox.is_checked();
+            if let Some(&generator) =
+                Generator::ALL.get(generator_choice.value() as usize)
+            {
+                config.board_generator = generator;
+            }
+            if let Some(theme) = themes.get(theme_choice.value() as usize) {
+                config.board_theme = theme.name.clone();
+                *THEME.get().write().unwrap() = theme.clone();
+            }
+            if let Some(name) =
+                tilesets.get(tileset_choice.value() as usize)
+            {
+                config.board_tileset = name.clone();
+            }
             config.board_columns = columns_spinner.value() as u8;
             config.board_rows = rows_spinner.value() as u8;
             config.board_maxcolors = max_colors_spinner.value() as u8;
+            config.board_min_distance = min_distance_spinner.value() as u8;
             config.board_delay_ms = delay_ms_spinner.value() as u16;
+            config.board_bombs = bombs_spinner.value() as u8;
+            config.board_wildcards = wildcards_spinner.value() as u8;
+            config.board_stones = stones_spinner.value() as u8;
+            config.board_multipliers = multipliers_spinner.value() as u8;
             let old_scale = config.window_scale;
             let new_scale = scale_spinner.value() as f32;
             config.window_scale = new_scale;
             fltk::app::set_screen_scale(0, new_scale);
+            let warning = get_color_warning(
+                config.board_palette,
+                config.board_maxcolors,
+                config.board_min_distance,
+                config.board_symbols,
+            );
+            drop(config); // the warning may wait a while
+            if let Some(message) = warning {
+                util::warning(&message);
+            }
             form.hide();
         }
     });
+    add_preset_handlers(spinners);
     buttons.cancel_button.set_callback({
         let mut form = form.clone();
         move |_| {
@@ -203,3 +600,76 @@ fn add_event_handlers(
         }
     });
 }
+
+// Choosing a preset sets its spinners and changing any of them makes the
+// preset Custom
+fn add_preset_handlers(spinners: &Spinners) {
+    let mut board_spinners = [
+        spinners.columns_spinner.clone(),
+        spinners.rows_spinner.clone(),
+        spinners.max_colors_spinner.clone(),
+        spinners.bombs_spinner.clone(),
+        spinners.wildcards_spinner.clone(),
+        spinners.stones_spinner.clone(),
+        spinners.multipliers_spinner.clone(),
+    ];
+    spinners.preset_choice.clone().set_callback({
+        let mut board_spinners = board_spinners.clone();
+        move |preset_choice| {
+            let Some((size, maxcolors, specials)) = Preset::ALL
+                .get(preset_choice.value() as usize)
+                .and_then(|preset| preset.board())
+            else {
+                return; // Custom
+            };
+            for (spinner, value) in board_spinners.iter_mut().zip([
+                size.columns as u8,
+                size.rows as u8,
+                maxcolors,
+                specials.bombs,
+                specials.wildcards,
+                specials.stones,
+                specials.multipliers,
+            ]) {
+                spinner.set_value(value as f64);
+            }
+        }
+    });
+    let custom = Preset::ALL
+        .iter()
+        .position(|&preset| preset == Preset::Custom)
+        .unwrap_or_default() as i32;
+    for spinner in &mut board_spinners {
+        spinner.set_callback({
+            let mut preset_choice = spinners.preset_choice.clone();
+            move |_| {
+                preset_choice.set_value(custom);
+            }
+        });
+    }
+}
+
+// Returns a warning if too few of the colors are at least the minimum
+// distance apart
+fn get_color_warning(
+    palette: Palette,
+    maxcolors: u8,
+    min_distance: u8,
+    symbols: bool,
+) -> Option<String> {
+    let count =
+        palette::distinct(palette, maxcolors.into(), min_distance.into());
+    if count >= maxcolors as usize {
+        return None;
+    }
+    let mut message = trf(
+        "Only {0} of the {1} colors from the {2} palette can be at least \
+        {3} apart so some will be hard to tell apart.",
+        &[&count, &maxcolors, &tr(palette.name()), &min_distance],
+    );
+    if !symbols {
+        message.push('\n');
+        message.push_str(tr("Checking Symbols may help."));
+    }
+    Some(message)
+}
@@ -0,0 +1,394 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use crate::fixed::COLORS;
+use crate::theme::THEME;
+use fltk::enums::Color;
+
+// Palettes for color vision deficiencies; based on the Okabe-Ito, IBM and
+// Tol palettes
+const DEUTERANOPIA: [u32; 10] = [
+    0x000000, 0xE69F00, 0x56B4E9, 0x009E73, 0xF0E442, 0x0072B2, 0xD55E00,
+    0xCC79A7, 0xFFFFFF, 0x999999,
+];
+const PROTANOPIA: [u32; 10] = [
+    0x000000, 0xFFB000, 0x648FFF, 0xDC267F, 0xFFFFFF, 0x785EF0, 0xFE6100,
+    0x004D80, 0x999999, 0xB3E5FC,
+];
+const TRITANOPIA: [u32; 10] = [
+    0x000000, 0xD81B60, 0x00BFA5, 0xFFFFFF, 0x1E88E5, 0x8B0000, 0xFF99CC,
+    0x004D40, 0x999999, 0xFFC107,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Palette {
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 4] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deuteranopia",
+            Palette::Protanopia => "Protanopia",
+            Palette::Tritanopia => "Tritanopia",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Palette> {
+        Palette::ALL.iter().find(|palette| palette.name() == name).copied()
+    }
+
+    pub fn colors(&self) -> Vec<Color> {
+        let hexes: &[u32] = match self {
+            Palette::Standard => {
+                let theme = THEME.get().read().unwrap();
+                return if theme.palette.is_empty() {
+                    COLORS.get().read().unwrap().clone()
+                } else {
+                    theme.palette.clone()
+                };
+            }
+            Palette::Deuteranopia => &DEUTERANOPIA,
+            Palette::Protanopia => &PROTANOPIA,
+            Palette::Tritanopia => &TRITANOPIA,
+        };
+        hexes.iter().map(|&hex| Color::from_hex(hex)).collect()
+    }
+
+    // The color vision deficiency the palette is for as a linear RGB
+    // simulation matrix (Machado, Oliveira & Fernandes, 2009)
+    fn deficiency(&self) -> Option<[[f64; 3]; 3]> {
+        match self {
+            Palette::Standard => None,
+            Palette::Deuteranopia => Some([
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ]),
+            Palette::Protanopia => Some([
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ]),
+            Palette::Tritanopia => Some([
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ]),
+        }
+    }
+
+    // Returns the color as seen by those the palette is for
+    fn to_lab(self, color: Color) -> Lab {
+        let (r, g, b) = color.to_rgb();
+        let mut rgb = [r, g, b].map(|c| to_linear(c as f64 / 255.0));
+        if let Some(matrix) = self.deficiency() {
+            rgb = matrix.map(|row| {
+                (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2])
+                    .clamp(0.0, 1.0)
+            });
+        }
+        let [r, g, b] = rgb;
+        let x = (0.4124564 * r + 0.3575761 * g + 0.1804375 * b) / 0.95047;
+        let y = 0.2126729 * r + 0.7151522 * g + 0.0721750 * b;
+        let z = (0.0193339 * r + 0.1191920 * g + 0.9503041 * b) / 1.08883;
+        let (fx, fy, fz) = (lab_f(x), lab_f(y), lab_f(z));
+        Lab {
+            l: 116.0 * fy - 16.0,
+            a: 500.0 * (fx - fy),
+            b: 200.0 * (fy - fz),
+        }
+    }
+}
+
+// Returns count colors from the palette: the most distinguishable ones
+pub fn choose(palette: Palette, count: usize) -> Vec<Color> {
+    spread(palette, count).into_iter().map(|(color, _)| color).collect()
+}
+
+// Returns how many of the count colors choose() would return are at least
+// min_distance (CIEDE2000) from one another
+pub fn distinct(
+    palette: Palette,
+    count: usize,
+    min_distance: f64,
+) -> usize {
+    spread(palette, count)
+        .iter()
+        .take_while(|(_, distance)| *distance >= min_distance)
+        .count()
+}
+
+// Returns count colors from the palette chosen one at a time so that each
+// is as different as possible from those already chosen, each with its
+// distance from the nearest of those. If the palette has too few colors,
+// standard colors make up the count.
+fn spread(palette: Palette, count: usize) -> Vec<(Color, f64)> {
+    let to_lab = |color: Color| (color, palette.to_lab(color));
+    let mut pool: Vec<(Color, Lab)> =
+        palette.colors().into_iter().map(to_lab).collect();
+    let mut extras: Vec<(Color, Lab)> = COLORS
+        .get()
+        .read()
+        .unwrap()
+        .iter()
+        .filter(|&color| !pool.iter().any(|(c, _)| c == color))
+        .map(|&color| to_lab(color))
+        .collect();
+    let mut colors = Vec::with_capacity(count);
+    let mut chosen: Vec<Lab> = Vec::with_capacity(count);
+    while colors.len() < count {
+        if pool.is_empty() {
+            if extras.is_empty() {
+                break;
+            }
+            pool = std::mem::take(&mut extras);
+        }
+        let (index, distance) = if chosen.is_empty() {
+            // Start with one of the two most different colors
+            (farthest_pair(&pool), f64::INFINITY)
+        } else {
+            pool.iter()
+                .enumerate()
+                .map(|(i, (_, lab))| {
+                    let nearest = chosen
+                        .iter()
+                        .map(|other| ciede2000(lab, other))
+                        .fold(f64::INFINITY, f64::min);
+                    (i, nearest)
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .unwrap()
+        };
+        let (color, lab) = pool.remove(index);
+        colors.push((color, distance));
+        chosen.push(lab);
+    }
+    colors
+}
+
+fn farthest_pair(pool: &[(Color, Lab)]) -> usize {
+    let mut best = (0, f64::MIN);
+    for (i, (_, a)) in pool.iter().enumerate() {
+        for (_, b) in pool.iter().skip(i + 1) {
+            let distance = ciede2000(a, b);
+            if distance > best.1 {
+                best = (i, distance);
+            }
+        }
+    }
+    best.0
+}
+
+// CIE L*a*b* (D65)
+#[derive(Copy, Clone, Debug)]
+struct Lab {
+    l: f64,
+    a: f64,
+    b: f64,
+}
+
+fn to_linear(c: f64) -> f64 {
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn lab_f(t: f64) -> f64 {
+    const EPSILON: f64 = 216.0 / 24389.0;
+    const KAPPA: f64 = 24389.0 / 27.0;
+    if t > EPSILON {
+        t.cbrt()
+    } else {
+        (KAPPA * t + 16.0) / 116.0
+    }
+}
+
+// The CIEDE2000 color difference (Sharma, Wu & Dalal, 2005)
+fn ciede2000(lab1: &Lab, lab2: &Lab) -> f64 {
+    let pow7 = |x: f64| x.powi(7);
+    let c1 = lab1.a.hypot(lab1.b);
+    let c2 = lab2.a.hypot(lab2.b);
+    let c_mean = (c1 + c2) / 2.0;
+    let g =
+        0.5 * (1.0 - (pow7(c_mean) / (pow7(c_mean) + pow7(25.0))).sqrt());
+    let a1 = (1.0 + g) * lab1.a;
+    let a2 = (1.0 + g) * lab2.a;
+    let c1 = a1.hypot(lab1.b);
+    let c2 = a2.hypot(lab2.b);
+    let hue = |b: f64, a: f64| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            b.atan2(a).to_degrees().rem_euclid(360.0)
+        }
+    };
+    let h1 = hue(lab1.b, a1);
+    let h2 = hue(lab2.b, a2);
+    let delta_l = lab2.l - lab1.l;
+    let delta_c = c2 - c1;
+    let delta_h = if c1 * c2 == 0.0 {
+        0.0
+    } else if h2 - h1 > 180.0 {
+        h2 - h1 - 360.0
+    } else if h2 - h1 < -180.0 {
+        h2 - h1 + 360.0
+    } else {
+        h2 - h1
+    };
+    let delta_h =
+        2.0 * (c1 * c2).sqrt() * (delta_h / 2.0).to_radians().sin();
+    let l_mean = (lab1.l + lab2.l) / 2.0;
+    let c_mean = (c1 + c2) / 2.0;
+    let h_mean = if c1 * c2 == 0.0 {
+        h1 + h2
+    } else if (h1 - h2).abs() <= 180.0 {
+        (h1 + h2) / 2.0
+    } else if h1 + h2 < 360.0 {
+        (h1 + h2 + 360.0) / 2.0
+    } else {
+        (h1 + h2 - 360.0) / 2.0
+    };
+    let cos = |degrees: f64| degrees.to_radians().cos();
+    let t = 1.0 - 0.17 * cos(h_mean - 30.0)
+        + 0.24 * cos(2.0 * h_mean)
+        + 0.32 * cos(3.0 * h_mean + 6.0)
+        - 0.20 * cos(4.0 * h_mean - 63.0);
+    let delta_theta = 30.0 * (-((h_mean - 275.0) / 25.0).powi(2)).exp();
+    let r_c = 2.0 * (pow7(c_mean) / (pow7(c_mean) + pow7(25.0))).sqrt();
+    let s_l = 1.0
+        + (0.015 * (l_mean - 50.0).powi(2))
+            / (20.0 + (l_mean - 50.0).powi(2)).sqrt();
+    let s_c = 1.0 + 0.045 * c_mean;
+    let s_h = 1.0 + 0.015 * c_mean * t;
+    let r_t = -(2.0 * delta_theta).to_radians().sin() * r_c;
+    let (l, c, h) = (delta_l / s_l, delta_c / s_c, delta_h / s_h);
+    (l * l + c * c + h * h + r_t * c * h).sqrt()
+}
+
+// Returns black or white, whichever shows up best on the given color
+pub fn contrasting(color: Color) -> Color {
+    let (r, g, b) = color.to_rgb();
+    let luminance = 0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64;
+    if luminance > 140.0 {
+        Color::Black
+    } else {
+        Color::White
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lab(l: f64, a: f64, b: f64) -> Lab {
+        Lab { l, a, b }
+    }
+
+    // The test data from Sharma, Wu & Dalal's paper: each pair of
+    // L*a*b* colors with their CIEDE2000 difference
+    #[rustfmt::skip]
+    const PAIRS: [[f64; 7]; 34] = [
+        [50.0, 2.6772, -79.7751, 50.0, 0.0, -82.7485, 2.0425],
+        [50.0, 3.1571, -77.2803, 50.0, 0.0, -82.7485, 2.8615],
+        [50.0, 2.8361, -74.0200, 50.0, 0.0, -82.7485, 3.4412],
+        [50.0, -1.3802, -84.2814, 50.0, 0.0, -82.7485, 1.0000],
+        [50.0, -1.1848, -84.8006, 50.0, 0.0, -82.7485, 1.0000],
+        [50.0, -0.9009, -85.5211, 50.0, 0.0, -82.7485, 1.0000],
+        [50.0, 0.0, 0.0, 50.0, -1.0, 2.0, 2.3669],
+        [50.0, -1.0, 2.0, 50.0, 0.0, 0.0, 2.3669],
+        [50.0, 2.49, -0.001, 50.0, -2.49, 0.0009, 7.1792],
+        [50.0, 2.49, -0.001, 50.0, -2.49, 0.001, 7.1792],
+        [50.0, 2.49, -0.001, 50.0, -2.49, 0.0011, 7.2195],
+        [50.0, 2.49, -0.001, 50.0, -2.49, 0.0012, 7.2195],
+        [50.0, -0.001, 2.49, 50.0, 0.0009, -2.49, 4.8045],
+        [50.0, -0.001, 2.49, 50.0, 0.001, -2.49, 4.8045],
+        [50.0, -0.001, 2.49, 50.0, 0.0011, -2.49, 4.7461],
+        [50.0, 2.5, 0.0, 50.0, 0.0, -2.5, 4.3065],
+        [50.0, 2.5, 0.0, 73.0, 25.0, -18.0, 27.1492],
+        [50.0, 2.5, 0.0, 61.0, -5.0, 29.0, 22.8977],
+        [50.0, 2.5, 0.0, 56.0, -27.0, -3.0, 31.9030],
+        [50.0, 2.5, 0.0, 58.0, 24.0, 15.0, 19.4535],
+        [50.0, 2.5, 0.0, 50.0, 3.1736, 0.5854, 1.0000],
+        [50.0, 2.5, 0.0, 50.0, 3.2972, 0.0, 1.0000],
+        [50.0, 2.5, 0.0, 50.0, 1.8634, 0.5757, 1.0000],
+        [50.0, 2.5, 0.0, 50.0, 3.2592, 0.3350, 1.0000],
+        [60.2574, -34.0099, 36.2677, 60.4626, -34.1751, 39.4387, 1.2644],
+        [63.0109, -31.0961, -5.8663, 62.8187, -29.7946, -4.0864, 1.2630],
+        [61.2901, 3.7196, -5.3901, 61.4292, 2.2480, -4.9620, 1.8731],
+        [35.0831, -44.1164, 3.7933, 35.0232, -40.0716, 1.5901, 1.8645],
+        [22.7233, 20.0904, -46.6940, 23.0331, 14.9730, -42.5619, 2.0373],
+        [36.4612, 47.8580, 18.3852, 36.2715, 50.5065, 21.2231, 1.4146],
+        [90.8027, -2.0831, 1.4410, 91.1528, -1.6435, 0.0447, 1.4441],
+        [90.9257, -0.5406, -0.9208, 88.6381, -0.8985, -0.7239, 1.5381],
+        [6.7747, -0.2908, -2.4247, 5.8714, -0.0985, -2.2286, 0.6377],
+        [2.0776, 0.0795, -1.1350, 0.9033, -0.0636, -0.5514, 0.9082],
+    ];
+
+    #[test]
+    fn ciede2000_matches_the_reference_pairs() {
+        for (i, [l1, a1, b1, l2, a2, b2, expected]) in
+            PAIRS.iter().enumerate()
+        {
+            let (lab1, lab2) = (lab(*l1, *a1, *b1), lab(*l2, *a2, *b2));
+            for difference in
+                [ciede2000(&lab1, &lab2), ciede2000(&lab2, &lab1)]
+            {
+                assert!(
+                    (difference - expected).abs() < 0.0001,
+                    "pair {}: {difference} != {expected}",
+                    i + 1
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn choose_is_deterministic_and_distinct() {
+        crate::fixed::initialize_colors();
+        for palette in &Palette::ALL[1..] {
+            // More colors than the palette has makes up with standard ones
+            for count in [1, 4, 10, 14] {
+                let colors = choose(*palette, count);
+                assert_eq!(colors.len(), count);
+                assert_eq!(colors, choose(*palette, count));
+                for (i, color) in colors.iter().enumerate() {
+                    assert!(
+                        !colors[i + 1..].contains(color),
+                        "{palette:?}"
+                    );
+                }
+                // Asking for fewer gives the first of those for more
+                assert_eq!(colors[..1], choose(*palette, 1));
+            }
+        }
+    }
+
+    #[test]
+    fn spread_puts_the_most_different_colors_first() {
+        crate::fixed::initialize_colors();
+        for palette in &Palette::ALL[1..] {
+            let spread = spread(*palette, 8);
+            assert!(spread[0].1.is_infinite());
+            for pair in spread[1..].windows(2) {
+                assert!(pair[0].1 >= pair[1].1, "{palette:?}");
+            }
+            let min_distance = spread[7].1;
+            assert_eq!(distinct(*palette, 8, min_distance), 8);
+            assert!(distinct(*palette, 8, min_distance + 0.1) < 8);
+        }
+    }
+}
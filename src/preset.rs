@@ -0,0 +1,108 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// A preset sets the board's size, colors and special tiles together. A
+// board's preset is whichever one it matches (or Custom), so high scores
+// and statistics only ever compare boards that are alike.
+
+use crate::engine::{Setup, Size, Specials};
+use crate::fixed::Variant;
+use crate::generator::Generator;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Preset {
+    Beginner,
+    Normal,
+    Expert,
+    Marathon,
+    Custom,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 5] = [
+        Preset::Beginner,
+        Preset::Normal,
+        Preset::Expert,
+        Preset::Marathon,
+        Preset::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Beginner => "Beginner",
+            Preset::Normal => "Normal",
+            Preset::Expert => "Expert",
+            Preset::Marathon => "Marathon",
+            Preset::Custom => "Custom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Preset> {
+        Preset::ALL.iter().find(|preset| preset.name() == name).copied()
+    }
+
+    // Returns the preset's size, max. colors and specials, or None for
+    // Custom
+    pub fn board(&self) -> Option<(Size, u8, Specials)> {
+        let none = Specials::default();
+        match self {
+            Preset::Beginner => Some((Size::new(7, 7), 3, none)),
+            Preset::Normal => Some((Size::new(9, 9), 4, none)),
+            Preset::Expert => Some((
+                Size::new(12, 12),
+                6,
+                Specials { bombs: 5, stones: 5, ..none },
+            )),
+            Preset::Marathon => Some((
+                Size::new(20, 20),
+                5,
+                Specials { bombs: 5, wildcards: 5, multipliers: 5, ..none },
+            )),
+            Preset::Custom => None,
+        }
+    }
+
+    // Returns the preset that matches or Custom
+    pub fn of(size: Size, maxcolors: u8, specials: Specials) -> Preset {
+        Preset::ALL
+            .iter()
+            .find(|preset| {
+                preset.board() == Some((size, maxcolors, specials))
+            })
+            .copied()
+            .unwrap_or(Preset::Custom)
+    }
+
+    pub fn of_setup(setup: &Setup) -> Preset {
+        Preset::of(setup.size, setup.maxcolors, setup.specials)
+    }
+}
+
+// Returns the high score category for the variant and preset, e.g.,
+// "Classic/Expert", "Classic/Expert/Clustered" for a board that wasn't
+// laid out uniformly, or "Classic/Expert/Solvable" for a board that was
+// chosen because it can be cleared; Custom games leave out the preset's
+// name, as all games did before there were presets
+pub fn category(
+    variant: Variant,
+    preset: Preset,
+    generator: Generator,
+    solvable: bool,
+) -> String {
+    let mut category = variant.name().to_string();
+    if preset != Preset::Custom {
+        category.push('/');
+        category.push_str(preset.name());
+    }
+    if generator != Generator::Uniform {
+        category.push('/');
+        category.push_str(generator.name());
+    }
+    if solvable {
+        category.push('/');
+        category.push_str(SOLVABLE);
+    }
+    category
+}
+
+static SOLVABLE: &str = "Solvable";
@@ -0,0 +1,232 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Each player profile has its own settings in an INI file in the profiles
+// directory (see Config::profiles_dir()) and its own high scores,
+// statistics and saved game in an INI file in the data directory (see
+// Config::profile_data_filename()), both named after the profile. The
+// window's position, size, scale and language, and which profile is
+// active, are kept in the config file itself.
+
+use crate::engine::{Game, Kind, Setup, Size, Specials, Tile};
+use crate::fixed::{
+    Variant, COLORS, COLORS_MIN, SIZE_MAX, SIZE_MIN, SPECIALS_MAX,
+    SPECIALS_MIN,
+};
+use crate::generator::Generator;
+use crate::rating;
+use std::path::Path;
+
+pub static DEFAULT_PROFILE: &str = "Default";
+pub const NAME_MAX: usize = 30; // characters
+
+// Returns the names of the profiles saved in the directory in
+// alphabetical order
+pub fn names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| {
+                    let path = entry.ok()?.path();
+                    if path.extension()? == "ini" {
+                        Some(
+                            path.file_stem()?.to_string_lossy().to_string(),
+                        )
+                    } else {
+                        None
+                    }
+                })
+                .filter(|name| is_valid_name(name))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort_by_key(|name| name.to_lowercase());
+    names
+}
+
+// Names are used for filenames so may only contain letters, digits,
+// spaces, hyphens and underscores
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.trim() == name
+        && name.chars().count() <= NAME_MAX
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+// A one-player game in progress that is carried on when its profile is
+// next used
+#[derive(Clone, Debug)]
+pub struct SavedGame {
+    pub variant: Variant,
+    pub setup: Setup, // the game's colors depend on its seed
+    pub game: Game,
+    pub elapsed_secs: u32,
+    pub time_left: f64, // seconds; only used by Time Attack
+    pub rating: u8,     // the board's difficulty in stars; 0 if unknown
+}
+
+impl SavedGame {
+    // Returns None if there's no saved game or it is damaged
+    pub fn read(properties: &ini::Properties) -> Option<SavedGame> {
+        let variant = Variant::from_name(properties.get(VARIANT_KEY)?)?;
+        let get = |key: &str, minimum: u8, maximum: u8| {
+            properties
+                .get(key)?
+                .parse::<u8>()
+                .ok()
+                .filter(|n| (minimum..=maximum).contains(n))
+        };
+        let size = Size::new(
+            get(COLUMNS_KEY, SIZE_MIN, SIZE_MAX)? as i32,
+            get(ROWS_KEY, SIZE_MIN, SIZE_MAX)? as i32,
+        );
+        // Tiles store their color in base 36
+        let colors_max = COLORS.get().read().unwrap().len().min(36) as u8;
+        let maxcolors = get(MAX_COLORS_KEY, COLORS_MIN, colors_max)?;
+        let specials = Specials {
+            bombs: get(BOMBS_KEY, SPECIALS_MIN, SPECIALS_MAX)?,
+            wildcards: get(WILDCARDS_KEY, SPECIALS_MIN, SPECIALS_MAX)?,
+            stones: get(STONES_KEY, SPECIALS_MIN, SPECIALS_MAX)?,
+            multipliers: get(MULTIPLIERS_KEY, SPECIALS_MIN, SPECIALS_MAX)?,
+        };
+        let setup = Setup {
+            seed: properties.get(SEED_KEY)?.parse().ok()?,
+            size,
+            maxcolors,
+            specials,
+            solvable: properties
+                .get(SOLVABLE_KEY)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            generator: properties
+                .get(GENERATOR_KEY)
+                .and_then(Generator::from_name)
+                .unwrap_or_default(),
+        };
+        let mut tiles = vec![];
+        for column in 0..size.columns {
+            let text =
+                properties.get(format!("{COLUMN_KEY}{}", column + 1))?;
+            let chars: Vec<char> = text.chars().collect();
+            if chars.len() != size.rows as usize * 2 {
+                return None;
+            }
+            tiles.push(
+                chars
+                    .chunks(2)
+                    .map(|code| decode_tile(code[0], code[1], maxcolors))
+                    .collect::<Option<Vec<Option<Tile>>>>()?,
+            );
+        }
+        let game = Game {
+            size,
+            maxcolors,
+            tiles,
+            specials,
+            score: properties.get(SCORE_KEY)?.parse().ok()?,
+            cleared: properties.get(CLEARED_KEY)?.parse().ok()?,
+        };
+        Some(SavedGame {
+            variant,
+            setup,
+            game,
+            elapsed_secs: properties
+                .get(ELAPSED_SECS_KEY)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            time_left: properties
+                .get(TIME_LEFT_KEY)
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+            rating: get(RATING_KEY, 0, rating::STARS_MAX)
+                .unwrap_or_default(),
+        })
+    }
+
+    pub fn write(&self, ini: &mut ini::Ini, section: &str) {
+        let specials = &self.setup.specials;
+        ini.with_section(Some(section))
+            .set(VARIANT_KEY, self.variant.name())
+            .set(SEED_KEY, self.setup.seed.to_string())
+            .set(COLUMNS_KEY, self.setup.size.columns.to_string())
+            .set(ROWS_KEY, self.setup.size.rows.to_string())
+            .set(MAX_COLORS_KEY, self.setup.maxcolors.to_string())
+            .set(BOMBS_KEY, specials.bombs.to_string())
+            .set(WILDCARDS_KEY, specials.wildcards.to_string())
+            .set(STONES_KEY, specials.stones.to_string())
+            .set(MULTIPLIERS_KEY, specials.multipliers.to_string())
+            .set(SOLVABLE_KEY, self.setup.solvable.to_string())
+            .set(GENERATOR_KEY, self.setup.generator.name())
+            .set(SCORE_KEY, self.game.score.to_string())
+            .set(CLEARED_KEY, self.game.cleared.to_string())
+            .set(ELAPSED_SECS_KEY, self.elapsed_secs.to_string())
+            .set(TIME_LEFT_KEY, format!("{:.1}", self.time_left))
+            .set(RATING_KEY, self.rating.to_string());
+        for (i, column) in self.game.tiles.iter().enumerate() {
+            let text: String = column.iter().map(encode_tile).collect();
+            ini.with_section(Some(section))
+                .set(format!("{COLUMN_KEY}{}", i + 1), text);
+        }
+    }
+}
+
+// Each tile is written as two characters, its kind and its color (in
+// base 36), or ".." if there's no tile
+fn encode_tile(tile: &Option<Tile>) -> String {
+    match tile {
+        None => "..".to_string(),
+        Some(tile) => {
+            let kind = match tile.kind {
+                Kind::Plain => 'p',
+                Kind::Bomb => 'b',
+                Kind::Wildcard => 'w',
+                Kind::Stone => 's',
+                Kind::Multiplier => 'm',
+            };
+            let color =
+                char::from_digit(tile.color as u32, 36).unwrap_or('0');
+            format!("{kind}{color}")
+        }
+    }
+}
+
+// Returns None if the code isn't valid or Some(None) if there's no tile
+fn decode_tile(
+    kind: char,
+    color: char,
+    maxcolors: u8,
+) -> Option<Option<Tile>> {
+    if kind == '.' && color == '.' {
+        return Some(None);
+    }
+    let kind = match kind {
+        'p' => Kind::Plain,
+        'b' => Kind::Bomb,
+        'w' => Kind::Wildcard,
+        's' => Kind::Stone,
+        'm' => Kind::Multiplier,
+        _ => return None,
+    };
+    let color = color.to_digit(36)? as u8;
+    (color < maxcolors).then_some(Some(Tile::new(color, kind)))
+}
+
+static VARIANT_KEY: &str = "variant";
+static SEED_KEY: &str = "seed";
+static COLUMNS_KEY: &str = "columns";
+static ROWS_KEY: &str = "rows";
+static MAX_COLORS_KEY: &str = "maxColors";
+static BOMBS_KEY: &str = "bombs";
+static WILDCARDS_KEY: &str = "wildcards";
+static STONES_KEY: &str = "stones";
+static MULTIPLIERS_KEY: &str = "multipliers";
+static SOLVABLE_KEY: &str = "solvable";
+static GENERATOR_KEY: &str = "generator";
+static SCORE_KEY: &str = "score";
+static CLEARED_KEY: &str = "cleared";
+static ELAPSED_SECS_KEY: &str = "elapsedSecs";
+static TIME_LEFT_KEY: &str = "timeLeft";
+static RATING_KEY: &str = "rating";
+static COLUMN_KEY: &str = "column";
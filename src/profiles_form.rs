@@ -0,0 +1,226 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+use super::CONFIG;
+use crate::fixed::{APPNAME, BUTTON_HEIGHT, BUTTON_WIDTH, ICON, PAD};
+use crate::i18n::{tr, trf};
+use crate::profile::{self, NAME_MAX};
+use crate::util;
+use fltk::prelude::*;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub struct Form {
+    form: fltk::window::Window,
+    pub(crate) name: Rc<RefCell<Option<String>>>, // the one to switch to
+}
+
+impl Form {
+    pub fn default() -> Self {
+        let name = Rc::from(RefCell::from(None));
+        let mut form = make_form();
+        let mut vbox =
+            fltk::group::Flex::default().size_of_parent().column();
+        vbox.set_margin(PAD);
+        vbox.set_pad(PAD);
+        let mut browser = fltk::browser::HoldBrowser::default();
+        let names = profile_names();
+        let current = CONFIG.get().read().unwrap().profile.clone();
+        populate(&mut browser, &names, &current);
+        let (button_row, mut buttons) = make_buttons();
+        vbox.set_size(&button_row, BUTTON_HEIGHT);
+        vbox.end();
+        form.end();
+        form.make_modal(true);
+        add_event_handlers(
+            &mut form,
+            &mut browser,
+            &mut buttons,
+            Rc::new(RefCell::new(names)),
+            Rc::clone(&name),
+        );
+        form.show();
+        while form.shown() {
+            fltk::app::wait();
+        }
+        Self { form, name }
+    }
+}
+
+impl Drop for Form {
+    fn drop(&mut self) {
+        fltk::app::delete_widget(self.form.clone());
+    }
+}
+
+struct Buttons {
+    add: fltk::button::Button,
+    delete: fltk::button::Button,
+    switch: fltk::button::Button,
+    cancel: fltk::button::Button,
+}
+
+fn make_form() -> fltk::window::Window {
+    let image = fltk::image::SvgImage::from_data(ICON).unwrap();
+    let mut form = fltk::window::Window::default()
+        .with_size(BUTTON_WIDTH * 4 + PAD * 7, 280)
+        .with_label(&trf("Profiles — {0}", &[&APPNAME]));
+    if let Some(window) = fltk::app::first_window() {
+        form.set_pos(window.x() + 50, window.y() + 50);
+    }
+    form.set_icon(Some(image));
+    form
+}
+
+fn make_buttons() -> (fltk::group::Flex, Buttons) {
+    let mut row = fltk::group::Flex::default().row();
+    row.set_pad(PAD);
+    let mut add = fltk::button::Button::default().with_label(tr("&New…"));
+    add.set_tooltip(tr("Add a profile"));
+    let delete = fltk::button::Button::default().with_label(tr("&Delete"));
+    let switch = fltk::button::Button::default().with_label(tr("&Switch"));
+    let cancel = fltk::button::Button::default().with_label(tr("&Cancel"));
+    for button in [&add, &delete, &switch, &cancel] {
+        row.set_size(button, BUTTON_WIDTH);
+    }
+    row.end();
+    (row, Buttons { add, delete, switch, cancel })
+}
+
+fn add_event_handlers(
+    form: &mut fltk::window::Window,
+    browser: &mut fltk::browser::HoldBrowser,
+    buttons: &mut Buttons,
+    names: Rc<RefCell<Vec<String>>>,
+    name: Rc<RefCell<Option<String>>>,
+) {
+    buttons.add.set_callback({
+        let mut browser = browser.clone();
+        let names = Rc::clone(&names);
+        move |_| {
+            if let Some(new_name) = get_new_name(&names.borrow()) {
+                let mut names = names.borrow_mut();
+                names.push(new_name.clone());
+                names.sort_by_key(|name| name.to_lowercase());
+                populate(&mut browser, &names, &new_name);
+            }
+        }
+    });
+    buttons.delete.set_callback({
+        let mut browser = browser.clone();
+        let names = Rc::clone(&names);
+        move |_| {
+            let Some(selected) = browser.text(browser.value()) else {
+                return;
+            };
+            if delete_profile(&selected) {
+                let mut names = names.borrow_mut();
+                names.retain(|name| *name != selected);
+                let current = CONFIG.get().read().unwrap().profile.clone();
+                populate(&mut browser, &names, &current);
+            }
+        }
+    });
+    buttons.switch.set_callback({
+        let browser = browser.clone();
+        let mut form = form.clone();
+        move |_| {
+            *name.borrow_mut() = browser.text(browser.value());
+            form.hide();
+        }
+    });
+    buttons.cancel.set_callback({
+        let mut form = form.clone();
+        move |_| {
+            form.hide();
+        }
+    });
+}
+
+// Returns the saved profiles' names and the active one's, which may not
+// have been saved yet
+fn profile_names() -> Vec<String> {
+    let config = CONFIG.get().read().unwrap();
+    let mut names = profile::names(&config.profiles_dir());
+    if !names.contains(&config.profile) {
+        names.push(config.profile.clone());
+        names.sort_by_key(|name| name.to_lowercase());
+    }
+    names
+}
+
+fn populate(
+    browser: &mut fltk::browser::HoldBrowser,
+    names: &[String],
+    selected: &str,
+) {
+    browser.clear();
+    for (i, name) in names.iter().enumerate() {
+        browser.add(name);
+        if name == selected {
+            browser.select(i as i32 + 1); // lines count from 1
+        }
+    }
+}
+
+// Returns the new profile's name or None if it was cancelled or isn't
+// usable
+fn get_new_name(names: &[String]) -> Option<String> {
+    fltk::dialog::message_title(&trf("Profiles — {0}", &[&APPNAME]));
+    let name = fltk::dialog::input_default(tr("New profile's name:"), "")?;
+    let name = name.trim().to_string();
+    if !profile::is_valid_name(&name) {
+        util::warning(&trf(
+            "“{0}” can't be used as a profile name: use up to {1} \
+            letters, digits, spaces, hyphens and underscores",
+            &[&name, &NAME_MAX],
+        ));
+        None
+    } else if names
+        .iter()
+        .any(|old| old.to_lowercase() == name.to_lowercase())
+    {
+        util::warning(&trf("There is already a “{0}” profile", &[&name]));
+        None
+    } else {
+        Some(name)
+    }
+}
+
+// Returns true if the profile was deleted
+fn delete_profile(name: &str) -> bool {
+    if name == CONFIG.get().read().unwrap().profile {
+        util::warning(tr("The active profile can't be deleted"));
+        return false;
+    }
+    fltk::dialog::message_title(&trf("Profiles — {0}", &[&APPNAME]));
+    if fltk::dialog::choice2_default(
+        &trf(
+            "Delete the “{0}” profile with its settings, high scores, \
+            statistics and saved game?",
+            &[&name],
+        ),
+        tr("&No"),
+        tr("&Yes"),
+        "",
+    ) != Some(1)
+    {
+        return false;
+    }
+    let filenames = {
+        let config = CONFIG.get().read().unwrap();
+        [config.profile_filename(name), config.profile_data_filename(name)]
+    };
+    for filename in filenames {
+        if filename.exists() {
+            if let Err(err) = std::fs::remove_file(&filename) {
+                util::warning(&trf(
+                    "failed to delete {0}: {1}",
+                    &[&filename.display(), &err],
+                ));
+                return false;
+            }
+        }
+    }
+    true
+}
@@ -0,0 +1,697 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// A race on the local network: every racer plays the same board (made from
+// the host's setup) and sees everyone else's progress. Joiners only talk to
+// the host, which relays each racer's progress to all of them. The protocol
+// is one space-separated message per line:
+//   HELLO name                          joiner → host
+//   WELCOME id                          host → joiner
+//   START seed columns rows maxcolors bombs wildcards stones multipliers
+//         [solvable] [generator]        host → joiners
+//   PROGRESS score remaining progress   joiner → host
+//   RACER id score remaining progress name
+//                                       host → joiners
+
+use crate::engine::{Setup, Size, Specials};
+use crate::fixed::{
+    Action, COLORS, COLORS_MIN, SIZE_MAX, SIZE_MIN, SPECIALS_MAX,
+    SPECIALS_MIN,
+};
+use crate::generator::Generator;
+use crate::i18n::{self, tr, trf};
+use std::cmp;
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+pub const RACE_PORT: u16 = 9527;
+const HOST_ID: u32 = 0;
+const ACCEPT_POLL: Duration = Duration::from_millis(100);
+// So that a racer who stops reading can't hold up everyone else
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Progress {
+    Waiting, // for the host to start the race
+    Playing,
+    Cleared,
+    Stuck,
+    Gone, // disconnected
+}
+
+impl Progress {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Progress::Waiting => "waiting",
+            Progress::Playing => "playing",
+            Progress::Cleared => "cleared",
+            Progress::Stuck => "stuck",
+            Progress::Gone => "gone",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Progress> {
+        [
+            Progress::Waiting,
+            Progress::Playing,
+            Progress::Cleared,
+            Progress::Stuck,
+            Progress::Gone,
+        ]
+        .into_iter()
+        .find(|progress| progress.name() == name)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Racer {
+    pub name: String,
+    pub score: u16,
+    pub remaining: u32,
+    pub progress: Progress,
+    pub is_me: bool,
+}
+
+impl Racer {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            score: 0,
+            remaining: 0,
+            progress: Progress::Waiting,
+            is_me: false,
+        }
+    }
+}
+
+// The state shared between the UI thread and the network threads
+#[derive(Debug, Default)]
+struct Shared {
+    my_id: Option<u32>,
+    racers: BTreeMap<u32, Racer>,
+    streams: BTreeMap<u32, TcpStream>, // the host's is at HOST_ID
+    setup: Option<Setup>, // the current race's (host) or a new one (joiner)
+    errors: Vec<String>,
+}
+
+pub struct Race {
+    is_host: bool,
+    port: u16, // the one hosted on or joined at
+    shared: Arc<Mutex<Shared>>,
+    closed: Arc<AtomicBool>,
+    started: bool,  // this racer is playing the race's board
+    pub over: bool, // set by the UI once the result has been shown
+}
+
+impl Race {
+    // Use port 0 for any free port; port() says which
+    pub fn host(
+        name: &str,
+        port: u16,
+        sender: fltk::app::Sender<Action>,
+    ) -> io::Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let mut shared =
+            Shared { my_id: Some(HOST_ID), ..Default::default() };
+        shared.racers.insert(HOST_ID, Racer::new(name));
+        let race = Self {
+            is_host: true,
+            port,
+            shared: Arc::new(Mutex::new(shared)),
+            closed: Arc::default(),
+            started: false,
+            over: false,
+        };
+        let shared = Arc::clone(&race.shared);
+        let closed = Arc::clone(&race.closed);
+        thread::spawn(move || accept(listener, shared, closed, sender));
+        Ok(race)
+    }
+
+    pub fn join(
+        address: &str,
+        name: &str,
+        sender: fltk::app::Sender<Action>,
+    ) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        stream.set_nodelay(true)?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        let port = stream.peer_addr()?.port();
+        let mut writer = stream.try_clone()?;
+        writeln!(writer, "HELLO {name}")?;
+        let mut shared = Shared::default();
+        shared.streams.insert(HOST_ID, writer);
+        let race = Self {
+            is_host: false,
+            port,
+            shared: Arc::new(Mutex::new(shared)),
+            closed: Arc::default(),
+            started: false,
+            over: false,
+        };
+        let shared = Arc::clone(&race.shared);
+        let closed = Arc::clone(&race.closed);
+        thread::spawn(move || follow_host(stream, shared, closed, sender));
+        Ok(race)
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.is_host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn is_started(&self) -> bool {
+        self.started
+    }
+
+    // A joiner whose host has gone is no longer in the race
+    pub fn is_connected(&self) -> bool {
+        self.is_host || !self.shared.lock().unwrap().streams.is_empty()
+    }
+
+    // Host only: sends the setup to every joiner (and to any who join
+    // later) so that everyone starts on the same board
+    pub fn start(&mut self, setup: Setup) {
+        self.started = true;
+        self.over = false;
+        let mut shared = self.shared.lock().unwrap();
+        shared.setup = Some(setup);
+        for racer in shared.racers.values_mut() {
+            if racer.progress != Progress::Gone {
+                racer.score = 0;
+                racer.progress = Progress::Playing;
+            }
+        }
+        broadcast(&mut shared, &start_line(&setup));
+        let ids: Vec<u32> = shared.racers.keys().copied().collect();
+        for id in ids {
+            broadcast_racer(&mut shared, id);
+        }
+    }
+
+    // Joiner only: returns the setup of a race the host has just started
+    pub fn take_setup(&mut self) -> Option<Setup> {
+        if self.is_host {
+            return None;
+        }
+        let setup = self.shared.lock().unwrap().setup.take();
+        if setup.is_some() {
+            self.started = true;
+            self.over = false;
+        }
+        setup
+    }
+
+    pub fn take_errors(&self) -> Vec<String> {
+        std::mem::take(&mut self.shared.lock().unwrap().errors)
+    }
+
+    pub fn report(&self, score: u16, remaining: u32, progress: Progress) {
+        let mut shared = self.shared.lock().unwrap();
+        if self.is_host {
+            if let Some(racer) = shared.racers.get_mut(&HOST_ID) {
+                racer.score = score;
+                racer.remaining = remaining;
+                racer.progress = progress;
+            }
+            broadcast_racer(&mut shared, HOST_ID);
+        } else if let Some(stream) = shared.streams.get_mut(&HOST_ID) {
+            // A failure means the host has gone which is reported by
+            // follow_host()
+            let _ = writeln!(
+                stream,
+                "PROGRESS {score} {remaining} {}",
+                progress.name()
+            );
+        }
+    }
+
+    pub fn racers(&self) -> Vec<Racer> {
+        let shared = self.shared.lock().unwrap();
+        shared
+            .racers
+            .iter()
+            .map(|(id, racer)| Racer {
+                is_me: shared.my_id == Some(*id),
+                ..racer.clone()
+            })
+            .collect()
+    }
+
+    // Returns the winner's name if the race is over, i.e., if someone has
+    // cleared their board or everyone still racing is stuck
+    pub fn winner(&self) -> Option<String> {
+        let racers: Vec<Racer> = self
+            .racers()
+            .into_iter()
+            .filter(|racer| racer.progress != Progress::Gone)
+            .collect();
+        if let Some(racer) = racers
+            .iter()
+            .filter(|racer| racer.progress == Progress::Cleared)
+            .max_by_key(|racer| racer.score)
+        {
+            return Some(racer.name.clone());
+        }
+        if racers.iter().any(|racer| racer.progress != Progress::Stuck) {
+            return None;
+        }
+        // Everyone is stuck so the nearest to clearing wins
+        racers
+            .iter()
+            .min_by_key(|racer| {
+                (racer.remaining, cmp::Reverse(racer.score))
+            })
+            .map(|racer| racer.name.clone())
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<body><table border=1 align=center><font size=4>
+<tr><th>{}</th><th>{}</th><th>{}</th><th></th></tr>",
+            tr("Racer"),
+            tr("Score"),
+            tr("Tiles Left")
+        );
+        for racer in self.racers() {
+            let name = if racer.is_me {
+                format!("<b>{}</b>", racer.name)
+            } else {
+                racer.name.clone()
+            };
+            html.push_str(&format!(
+                "<tr><td>{name}</td><td align=right>{}</td>\
+                <td align=right>{}</td><td>{}</td></tr>",
+                i18n::number(racer.score),
+                i18n::number(racer.remaining),
+                tr(racer.progress.name())
+            ));
+        }
+        html.push_str("</font></table></body>");
+        html
+    }
+}
+
+impl Drop for Race {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Relaxed);
+        for stream in self.shared.lock().unwrap().streams.values() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+// Host: accepts joiners until the race is dropped
+fn accept(
+    listener: TcpListener,
+    shared: Arc<Mutex<Shared>>,
+    closed: Arc<AtomicBool>,
+    sender: fltk::app::Sender<Action>,
+) {
+    let mut next_id = HOST_ID + 1;
+    while !closed.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if stream.set_nonblocking(false).is_err()
+                    || stream.set_nodelay(true).is_err()
+                    || stream
+                        .set_write_timeout(Some(WRITE_TIMEOUT))
+                        .is_err()
+                {
+                    continue;
+                }
+                if let Ok(writer) = stream.try_clone() {
+                    let id = next_id;
+                    next_id += 1;
+                    shared.lock().unwrap().streams.insert(id, writer);
+                    let shared = Arc::clone(&shared);
+                    let closed = Arc::clone(&closed);
+                    thread::spawn(move || {
+                        follow_joiner(id, stream, shared, closed, sender)
+                    });
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(ACCEPT_POLL)
+            }
+            Err(_) => thread::sleep(ACCEPT_POLL),
+        }
+    }
+}
+
+// Host: handles one joiner's messages until it disconnects
+fn follow_joiner(
+    id: u32,
+    stream: TcpStream,
+    shared: Arc<Mutex<Shared>>,
+    closed: Arc<AtomicBool>,
+    sender: fltk::app::Sender<Action>,
+) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let mut shared = shared.lock().unwrap();
+        let (command, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        match command {
+            "HELLO" => {
+                let mut racer = Racer::new(rest.trim());
+                let setup = shared.setup;
+                if let Some(stream) = shared.streams.get_mut(&id) {
+                    let _ = writeln!(stream, "WELCOME {id}");
+                    if let Some(setup) = setup {
+                        let _ = writeln!(stream, "{}", start_line(&setup));
+                        racer.progress = Progress::Playing;
+                    }
+                }
+                shared.racers.insert(id, racer);
+                let ids: Vec<u32> = shared.racers.keys().copied().collect();
+                for id in ids {
+                    broadcast_racer(&mut shared, id);
+                }
+            }
+            "PROGRESS" => {
+                let parts: Vec<&str> = rest.split_whitespace().collect();
+                if let (Some(racer), [score, remaining, progress]) =
+                    (shared.racers.get_mut(&id), parts.as_slice())
+                {
+                    racer.score = score.parse().unwrap_or(racer.score);
+                    racer.remaining =
+                        remaining.parse().unwrap_or(racer.remaining);
+                    racer.progress = Progress::from_name(progress)
+                        .unwrap_or(racer.progress);
+                }
+                broadcast_racer(&mut shared, id);
+            }
+            _ => {} // ignore anything unrecognized
+        }
+        drop(shared);
+        sender.send(Action::RaceUpdate);
+    }
+    let mut shared = shared.lock().unwrap();
+    shared.streams.remove(&id);
+    if let Some(racer) = shared.racers.get_mut(&id) {
+        racer.progress = Progress::Gone;
+        let message = trf("{0} has left the race", &[&racer.name]);
+        if !closed.load(Ordering::Relaxed) {
+            shared.errors.push(message);
+        }
+        broadcast_racer(&mut shared, id);
+    }
+    drop(shared);
+    sender.send(Action::RaceUpdate);
+}
+
+// Joiner: handles the host's messages until it disconnects
+fn follow_host(
+    stream: TcpStream,
+    shared: Arc<Mutex<Shared>>,
+    closed: Arc<AtomicBool>,
+    sender: fltk::app::Sender<Action>,
+) {
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let mut shared = shared.lock().unwrap();
+        let (command, rest) = line.split_once(' ').unwrap_or((&line, ""));
+        match command {
+            "WELCOME" => shared.my_id = rest.trim().parse().ok(),
+            "START" => {
+                if let Some(setup) = parse_setup(rest) {
+                    shared.setup = Some(setup);
+                    for racer in shared.racers.values_mut() {
+                        if racer.progress != Progress::Gone {
+                            racer.score = 0;
+                            racer.progress = Progress::Playing;
+                        }
+                    }
+                } else {
+                    shared.errors.push(
+                        tr("The host started a race on a board that \
+                           can't be played here")
+                        .to_string(),
+                    );
+                }
+            }
+            "RACER" => {
+                let parts: Vec<&str> = rest.splitn(5, ' ').collect();
+                if let [id, score, remaining, progress, name] =
+                    parts.as_slice()
+                {
+                    if let (
+                        Ok(id),
+                        Ok(score),
+                        Ok(remaining),
+                        Some(progress),
+                    ) = (
+                        id.parse(),
+                        score.parse(),
+                        remaining.parse(),
+                        Progress::from_name(progress),
+                    ) {
+                        shared.racers.insert(
+                            id,
+                            Racer {
+                                name: name.to_string(),
+                                score,
+                                remaining,
+                                progress,
+                                is_me: false,
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {} // ignore anything unrecognized
+        }
+        drop(shared);
+        sender.send(Action::RaceUpdate);
+    }
+    let mut shared = shared.lock().unwrap();
+    shared.streams.clear();
+    for racer in shared.racers.values_mut() {
+        racer.progress = Progress::Gone;
+    }
+    if !closed.load(Ordering::Relaxed) {
+        shared.errors.push(tr("The host has left the race").to_string());
+    }
+    drop(shared);
+    sender.send(Action::RaceUpdate);
+}
+
+// Host: sends the line to every joiner. A joiner that can't be written to
+// in time is disconnected, which its follow_joiner() then reports.
+fn broadcast(shared: &mut Shared, line: &str) {
+    for stream in shared.streams.values_mut() {
+        if writeln!(stream, "{line}").is_err() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+}
+
+fn broadcast_racer(shared: &mut Shared, id: u32) {
+    if let Some(racer) = shared.racers.get(&id) {
+        let line = format!(
+            "RACER {id} {} {} {} {}",
+            racer.score,
+            racer.remaining,
+            racer.progress.name(),
+            racer.name
+        );
+        broadcast(shared, &line);
+    }
+}
+
+// A solvable board's line ends with "solvable", and one not laid out
+// uniformly with its generator's name; older hosts leave both out
+fn start_line(setup: &Setup) -> String {
+    let specials = &setup.specials;
+    format!(
+        "START {} {} {} {} {} {} {} {}{}{}",
+        setup.seed,
+        setup.size.columns,
+        setup.size.rows,
+        setup.maxcolors,
+        specials.bombs,
+        specials.wildcards,
+        specials.stones,
+        specials.multipliers,
+        if setup.solvable { " solvable" } else { "" },
+        if setup.generator == Generator::Uniform {
+            String::new()
+        } else {
+            format!(" {}", setup.generator.name())
+        }
+    )
+}
+
+// Returns None if the line is malformed or any of its values are outside
+// the ranges Options allows, since they come from another machine
+fn parse_setup(text: &str) -> Option<Setup> {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    if parts.len() < 8 {
+        return None;
+    }
+    // seed columns rows maxcolors bombs wildcards stones multipliers
+    let (values, rest) = parts.split_at(8);
+    let colors_max = COLORS.get().read().unwrap().len() as u8;
+    let number = |i: usize, minimum: u8, maximum: u8| {
+        values[i]
+            .parse::<u8>()
+            .ok()
+            .filter(|n| (minimum..=maximum).contains(n))
+    };
+    let mut solvable = false;
+    let mut generator = Generator::Uniform;
+    for &token in rest {
+        match token {
+            "solvable" if !solvable => solvable = true,
+            _ if generator == Generator::Uniform => {
+                generator = Generator::from_name(token)?
+            }
+            _ => return None,
+        }
+    }
+    Some(Setup {
+        seed: values[0].parse().ok()?,
+        size: Size::new(
+            number(1, SIZE_MIN, SIZE_MAX)? as i32,
+            number(2, SIZE_MIN, SIZE_MAX)? as i32,
+        ),
+        maxcolors: number(3, COLORS_MIN, colors_max)?,
+        specials: Specials {
+            bombs: number(4, SPECIALS_MIN, SPECIALS_MAX)?,
+            wildcards: number(5, SPECIALS_MIN, SPECIALS_MAX)?,
+            stones: number(6, SPECIALS_MIN, SPECIALS_MAX)?,
+            multipliers: number(7, SPECIALS_MIN, SPECIALS_MAX)?,
+        },
+        solvable,
+        generator,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixed::initialize_colors;
+    use std::time::Instant;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    fn setup(solvable: bool, generator: Generator) -> Setup {
+        Setup {
+            seed: 1234567890123,
+            size: Size::new(12, 9),
+            maxcolors: 5,
+            specials: Specials {
+                bombs: 5,
+                wildcards: 4,
+                stones: 3,
+                multipliers: 2,
+            },
+            solvable,
+            generator,
+        }
+    }
+
+    // Returns true as soon as done() does or false if it doesn't in time
+    fn wait_until(done: impl Fn() -> bool) -> bool {
+        let end = Instant::now() + WAIT;
+        while Instant::now() < end {
+            if done() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    #[test]
+    fn start_line_round_trips() {
+        initialize_colors();
+        for solvable in [false, true] {
+            for generator in Generator::ALL {
+                let setup = setup(solvable, generator);
+                let line = start_line(&setup);
+                let text = line.strip_prefix("START ").unwrap();
+                assert_eq!(parse_setup(text), Some(setup), "{line}");
+            }
+        }
+    }
+
+    #[test]
+    fn parse_setup_refuses_bad_values() {
+        initialize_colors();
+        for text in [
+            "1 9 9 4 0 0 0",     // too short
+            "1 9 9 0 0 0 0 0",   // no colors
+            "1 9 9 1 0 0 0 0",   // too few colors
+            "1 9 9 250 0 0 0 0", // more colors than there are
+            "1 0 9 4 0 0 0 0",   // too few columns
+            "1 9 200 4 0 0 0 0", // too many rows
+            "1 -9 9 4 0 0 0 0",  // negative columns
+            "1 9 9 4 90 0 0 0",  // too many bombs
+            "1 9 9 4 0 0 0 0 solvable solvable",
+            "1 9 9 4 0 0 0 0 Clustered Mirror",
+            "1 9 9 4 0 0 0 0 Nonesuch",
+        ] {
+            assert_eq!(parse_setup(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn host_and_join_on_localhost() {
+        initialize_colors();
+        let (sender, _) = fltk::app::channel::<Action>();
+        let mut host = Race::host("Ann", 0, sender).unwrap();
+        assert_ne!(host.port(), 0);
+        let address = format!("127.0.0.1:{}", host.port());
+        let mut joiner = Race::join(&address, "Bob", sender).unwrap();
+        // HELLO and WELCOME
+        assert!(wait_until(|| host.racers().len() == 2));
+        assert!(wait_until(|| joiner
+            .shared
+            .lock()
+            .unwrap()
+            .my_id
+            .is_some()));
+        assert!(wait_until(|| joiner.racers().len() == 2));
+        // START
+        let setup = setup(true, Generator::Clustered);
+        host.start(setup);
+        assert!(wait_until(|| joiner
+            .shared
+            .lock()
+            .unwrap()
+            .setup
+            .is_some()));
+        assert_eq!(joiner.take_setup(), Some(setup));
+        assert!(joiner.is_started());
+        // PROGRESS, relayed back to the joiner as RACER
+        joiner.report(42, 17, Progress::Playing);
+        let reported = |race: &Race| {
+            race.racers().iter().any(|racer| {
+                racer.name == "Bob"
+                    && racer.score == 42
+                    && racer.remaining == 17
+                    && racer.progress == Progress::Playing
+            })
+        };
+        assert!(wait_until(|| reported(&host)));
+        assert!(wait_until(|| reported(&joiner)));
+        assert!(joiner.is_connected());
+        drop(joiner);
+        assert!(wait_until(|| {
+            host.racers().iter().any(|racer| {
+                racer.name == "Bob" && racer.progress == Progress::Gone
+            })
+        }));
+    }
+}
@@ -0,0 +1,79 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Estimates how hard a board is by playing it out many times, some moves
+// chosen at random and some greedily, and seeing how often it is cleared,
+// how many tiles are left when it isn't, and how much the scores vary.
+
+use crate::engine::{Game, Setup};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::time::{Duration, Instant};
+
+pub const STARS_MAX: u8 = 5;
+const ROLLOUTS: usize = 24;
+const GREEDINESS: f64 = 0.5; // the chance a rollout's move is greedy
+const BUDGET: Duration = Duration::from_millis(500);
+
+// Returns the board's difficulty from 1 (easiest) to STARS_MAX; the same
+// setup always gets the same rating unless time runs short
+pub fn rate(setup: &Setup) -> u8 {
+    let mut rng = StdRng::seed_from_u64(setup.seed);
+    let game = setup.new_game(&mut rng);
+    let total = game.remaining().max(1) as f64;
+    let end = Instant::now() + BUDGET;
+    let mut cleared = 0;
+    let mut left = 0.0;
+    let mut scores = vec![];
+    for i in 0..ROLLOUTS {
+        // The first rollout is wholly greedy
+        let greediness = if i == 0 { 1.0 } else { GREEDINESS };
+        let game = rollout(&game, greediness, &mut rng);
+        if game.remaining() == 0 {
+            cleared += 1;
+        }
+        left += game.remaining() as f64 / total;
+        scores.push(game.score as f64);
+        if Instant::now() >= end {
+            break;
+        }
+    }
+    let count = scores.len() as f64;
+    let clear_rate = cleared as f64 / count;
+    let left = left / count;
+    let best = scores.iter().copied().fold(0.0, f64::max);
+    let worst = scores.iter().copied().fold(best, f64::min);
+    // A wide spread means that the board punishes poor moves
+    let spread = if best > 0.0 { (best - worst) / best } else { 1.0 };
+    let difficulty =
+        (0.5 * (1.0 - clear_rate) + 0.3 * left + 0.2 * spread).min(1.0);
+    1 + ((difficulty * STARS_MAX as f64) as u8).min(STARS_MAX - 1)
+}
+
+// Returns the stars as text, e.g., "★★★☆☆"
+pub fn stars(rating: u8) -> String {
+    (1..=STARS_MAX).map(|i| if i <= rating { '★' } else { '☆' }).collect()
+}
+
+// Plays until there are no moves left, choosing the highest scoring move
+// or (with the remaining chance) a random one
+pub fn rollout<R: Rng>(game: &Game, greediness: f64, rng: &mut R) -> Game {
+    let mut game = game.clone();
+    loop {
+        let moves = game.moves();
+        let pos = if rng.gen_bool(greediness) {
+            moves
+                .iter()
+                .max_by_key(|&&pos| game.score_for(&game.removal(pos)))
+                .copied()
+        } else {
+            moves.choose(rng).copied()
+        };
+        let Some(pos) = pos else {
+            return game;
+        };
+        game.remove(&game.removal(pos));
+        game.close_up();
+    }
+}
@@ -0,0 +1,190 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Finds boards that are proven clearable by generating them from random
+// seeds and searching each one's moves until a way to clear it (stones
+// aside) is found or its share of the time runs out.
+
+use crate::engine::{Game, Setup};
+use crate::util::Pos;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::time::{Duration, Instant};
+
+const SEARCH_BUDGET: Duration = Duration::from_millis(2000);
+const BOARD_BUDGET: Duration = Duration::from_millis(250);
+
+// Returns the first seed for the setup's size, colors and specials
+// (starting with its own) that gives a board that can be cleared, or None
+// if none is found within the time budget
+pub fn find_solvable_seed(setup: &Setup) -> Option<u64> {
+    let end = Instant::now() + SEARCH_BUDGET;
+    let mut seed = setup.seed;
+    while Instant::now() < end {
+        let game = setup.new_game(&mut StdRng::seed_from_u64(seed));
+        let deadline = end.min(Instant::now() + BOARD_BUDGET);
+        if is_clearable(&game, deadline, &mut vec![]) == Some(true) {
+            return Some(seed);
+        }
+        seed = rand::random();
+    }
+    None
+}
+
+// Returns Some(true) if the game can be cleared (with the moves that do
+// it in moves), Some(false) if it can't, or None if that wasn't settled
+// by the deadline
+pub fn is_clearable(
+    game: &Game,
+    deadline: Instant,
+    moves: &mut Vec<Pos>,
+) -> Option<bool> {
+    let mut seen = HashSet::new();
+    moves.clear();
+    search(game, deadline, &mut seen, moves)
+}
+
+// Depth-first, trying the likeliest moves first and skipping positions
+// already found to be dead ends
+fn search(
+    game: &Game,
+    deadline: Instant,
+    seen: &mut HashSet<u64>,
+    moves: &mut Vec<Pos>,
+) -> Option<bool> {
+    let (won, can_move) = game.check_tiles();
+    if won {
+        return Some(true);
+    }
+    if !can_move || !seen.insert(fingerprint(game)) {
+        return Some(false);
+    }
+    if Instant::now() >= deadline {
+        return None;
+    }
+    for pos in ordered_moves(game) {
+        let mut next = game.clone();
+        next.remove(&next.removal(pos));
+        next.close_up();
+        moves.push(pos);
+        match search(&next, deadline, seen, moves) {
+            Some(false) => moves.pop(),
+            result => return result,
+        };
+    }
+    Some(false)
+}
+
+// Moves that remove the last of a color come first since they leave
+// fewer colors to clear, then the largest groups
+fn ordered_moves(game: &Game) -> Vec<Pos> {
+    let mut count_for_color = HashMap::<u8, usize>::new();
+    for tile in game.tiles.iter().flatten().flatten() {
+        *count_for_color.entry(tile.color).or_default() += 1;
+    }
+    let mut moves: Vec<(Pos, bool, usize)> = game
+        .moves()
+        .into_iter()
+        .map(|pos| {
+            let size = game.removal(pos).len();
+            let color = game.tile(pos).map(|tile| tile.color);
+            let last = color.and_then(|color| count_for_color.get(&color))
+                == Some(&size);
+            (pos, last, size)
+        })
+        .collect();
+    moves.sort_by_key(|&(_, last, size)| (!last, std::cmp::Reverse(size)));
+    moves.into_iter().map(|(pos, _, _)| pos).collect()
+}
+
+fn fingerprint(game: &Game) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    game.tiles.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Kind, Size, Specials, Tile};
+
+    const DEADLINE: Duration = Duration::from_secs(5);
+
+    // Replays the moves on a new game from the setup and seed and returns
+    // true if they are all legal and clear it
+    fn clears(setup: &Setup, seed: u64, moves: &[Pos]) -> bool {
+        let mut game = setup.new_game(&mut StdRng::seed_from_u64(seed));
+        for &pos in moves {
+            if !game.is_legal(pos) {
+                return false;
+            }
+            game.remove(&game.removal(pos));
+            game.close_up();
+        }
+        game.check_tiles().0
+    }
+
+    fn setup(specials: Specials) -> Setup {
+        Setup {
+            size: Size::new(6, 6),
+            maxcolors: 3,
+            specials,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn the_moves_found_clear_the_board() {
+        let mut solved = 0;
+        for specials in [
+            Specials::default(),
+            Specials { bombs: 2, wildcards: 2, ..Default::default() },
+            Specials { stones: 2, ..Default::default() },
+        ] {
+            let setup = setup(specials);
+            for seed in 0..10 {
+                let game = setup.new_game(&mut StdRng::seed_from_u64(seed));
+                let deadline = Instant::now() + DEADLINE;
+                let mut moves = vec![];
+                if is_clearable(&game, deadline, &mut moves) == Some(true) {
+                    assert!(clears(&setup, seed, &moves), "seed {seed}");
+                    solved += 1;
+                }
+            }
+        }
+        assert!(solved > 0);
+    }
+
+    #[test]
+    fn a_solvable_seed_gives_a_board_that_can_be_cleared() {
+        let setup = setup(Specials::default());
+        let seed = find_solvable_seed(&setup).unwrap();
+        let game = setup.new_game(&mut StdRng::seed_from_u64(seed));
+        let mut moves = vec![];
+        let deadline = Instant::now() + DEADLINE;
+        assert_eq!(is_clearable(&game, deadline, &mut moves), Some(true));
+        assert!(clears(&setup, seed, &moves));
+    }
+
+    #[test]
+    fn a_board_with_no_moves_is_not_clearable() {
+        let game = Game {
+            size: Size::new(2, 1),
+            maxcolors: 2,
+            tiles: vec![
+                vec![Some(Tile::new(0, Kind::Plain))],
+                vec![Some(Tile::new(1, Kind::Plain))],
+            ],
+            specials: Specials::default(),
+            score: 0,
+            cleared: 0,
+        };
+        let deadline = Instant::now() + DEADLINE;
+        let mut moves = vec![];
+        assert_eq!(is_clearable(&game, deadline, &mut moves), Some(false));
+        assert!(moves.is_empty());
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Settings (the config file and each profile's settings) are kept in the
+// platform's config directory, e.g., ~/.config, and game data (each
+// profile's high scores, statistics and saved game) in its data
+// directory, e.g., ~/.local/share/gravitate. In portable mode, e.g., when
+// run from a USB stick, everything is kept beside the executable instead:
+// this is used if the GRAVITATE_PORTABLE environment variable is set (to
+// anything but 0) or if there's a file called portable beside the
+// executable.
+
+use crate::fixed::APPNAME;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+pub fn config_filename() -> PathBuf {
+    // to_lowercase is for backwards compatability
+    let name = format!("{}.ini", APPNAME.to_lowercase());
+    if let Some(dir) = portable_dir() {
+        return dir.join(name);
+    }
+    if let Some(dir) = dirs::config_dir() {
+        dir.join(name)
+    } else if let Some(dir) = dirs::home_dir() {
+        if std::env::consts::FAMILY == "unix" {
+            dir.join(format!(".{name}"))
+        } else {
+            dir.join(name)
+        }
+    } else {
+        PathBuf::new()
+    }
+}
+
+// Falls back to a directory beside the config file if the platform has
+// no data directory
+pub fn data_dir(config_filename: &Path) -> PathBuf {
+    let name = APPNAME.to_lowercase();
+    if let Some(dir) = portable_dir() {
+        dir.join(format!("{name}-data"))
+    } else if let Some(dir) = dirs::data_dir() {
+        dir.join(name)
+    } else {
+        config_filename.with_file_name(format!("{name}-data"))
+    }
+}
+
+// Writes to a temporary file beside the file and then renames it over
+// the file so that a crash part way through never leaves it half written
+pub fn write_ini(ini: &ini::Ini, filename: &Path) -> std::io::Result<()> {
+    if let Some(dir) = filename.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let temp = filename.with_extension("ini.tmp");
+    let result = std::fs::File::create(&temp).and_then(|mut file| {
+        ini.write_to(&mut file)?;
+        file.flush()?;
+        file.sync_all()
+    });
+    if let Err(err) = result.and_then(|_| std::fs::rename(&temp, filename))
+    {
+        let _ = std::fs::remove_file(&temp);
+        return Err(err);
+    }
+    Ok(())
+}
+
+// Returns the executable's directory in portable mode
+fn portable_dir() -> Option<PathBuf> {
+    let dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+    let env = std::env::var(PORTABLE_ENV).ok();
+    is_portable(env.as_deref(), &dir).then_some(dir)
+}
+
+// Portable mode is on if the environment variable's value (if set) isn't
+// empty or 0 or if there's a portable file in the directory
+fn is_portable(env: Option<&str>, dir: &Path) -> bool {
+    env.is_some_and(|value| !value.is_empty() && value != "0")
+        || dir.join(PORTABLE_FILE).exists()
+}
+
+static PORTABLE_ENV: &str = "GRAVITATE_PORTABLE";
+static PORTABLE_FILE: &str = "portable";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Returns a new empty directory for the test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("gravitate-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn write_ini_replaces_the_file_whole() {
+        let dir = test_dir("write");
+        let filename = dir.join("profiles").join("Default.ini");
+        for (i, value) in ["first", "second"].iter().enumerate() {
+            let mut ini = ini::Ini::new();
+            ini.with_section(Some("Board")).set("theme", *value);
+            if i == 0 {
+                ini.with_section(Some("Old")).set("key", "value");
+            }
+            write_ini(&ini, &filename).unwrap();
+            let read = ini::Ini::load_from_file(&filename).unwrap();
+            assert_eq!(read.get_from(Some("Board"), "theme"), Some(*value));
+            assert_eq!(read.section(Some("Old")).is_some(), i == 0);
+            assert!(!filename.with_extension("ini.tmp").exists());
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_ini_failure_leaves_the_file_alone() {
+        let dir = test_dir("write-failure");
+        let filename = dir.join("Default.ini");
+        std::fs::create_dir(&filename).unwrap(); // can't be renamed over
+        let mut ini = ini::Ini::new();
+        ini.with_section(Some("Board")).set("theme", "Default");
+        assert!(write_ini(&ini, &filename).is_err());
+        assert!(filename.is_dir());
+        assert!(!filename.with_extension("ini.tmp").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn portable_mode_by_environment_or_file() {
+        let dir = test_dir("portable");
+        assert!(!is_portable(None, &dir));
+        assert!(!is_portable(Some(""), &dir));
+        assert!(!is_portable(Some("0"), &dir));
+        assert!(is_portable(Some("1"), &dir));
+        assert!(is_portable(Some("yes"), &dir));
+        std::fs::write(dir.join(PORTABLE_FILE), "").unwrap();
+        assert!(is_portable(None, &dir));
+        assert!(is_portable(Some("0"), &dir)); // the file wins
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Strategies: each is shown the board and picks a move, so they can be
+// played against each other on the same boards (see tournament.rs); some
+// also serve as the computer opponent (see ai::Strength).
+
+use crate::engine::{Game, Kind};
+use crate::rating;
+use crate::util::Pos;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+const LOOKAHEAD_BREADTH: usize = 12; // the best greedy moves to look into
+const MONTE_CARLO_BREADTH: usize = 10; // the largest groups to try
+const MONTE_CARLO_ROLLOUTS: usize = 8; // random playouts per move
+
+pub trait Strategy {
+    // Returns the position to click or None to stop; the game is only
+    // looked at, never changed
+    fn choose(&mut self, game: &Game) -> Option<Pos>;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Bot {
+    Random,
+    Greedy,
+    Lookahead,
+    LargestGroup,
+    SmallestGroup,
+    ColorMinimizing,
+    MonteCarlo,
+}
+
+impl Bot {
+    pub const ALL: [Bot; 7] = [
+        Bot::Random,
+        Bot::Greedy,
+        Bot::Lookahead,
+        Bot::LargestGroup,
+        Bot::SmallestGroup,
+        Bot::ColorMinimizing,
+        Bot::MonteCarlo,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Bot::Random => "Random",
+            Bot::Greedy => "Greedy",
+            Bot::Lookahead => "Lookahead",
+            Bot::LargestGroup => "LargestGroup",
+            Bot::SmallestGroup => "SmallestGroup",
+            Bot::ColorMinimizing => "ColorMinimizing",
+            Bot::MonteCarlo => "MonteCarlo",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Bot> {
+        Bot::ALL.iter().find(|bot| bot.name() == name).copied()
+    }
+
+    // Returns the bot's strategy; those that use chance are seeded so that
+    // the same seed always plays the same way
+    pub fn strategy(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            Bot::Random => Box::new(RandomBot(StdRng::seed_from_u64(seed))),
+            Bot::Greedy => Box::new(GreedyBot),
+            Bot::Lookahead => Box::new(LookaheadBot),
+            Bot::LargestGroup => Box::new(LargestGroupBot),
+            Bot::SmallestGroup => Box::new(SmallestGroupBot),
+            Bot::ColorMinimizing => Box::new(ColorMinimizingBot),
+            Bot::MonteCarlo => {
+                Box::new(MonteCarloBot(StdRng::seed_from_u64(seed)))
+            }
+        }
+    }
+}
+
+// Any legal move
+pub struct RandomBot(StdRng);
+
+impl Strategy for RandomBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        game.moves().choose(&mut self.0).copied()
+    }
+}
+
+// The move that scores the most points
+pub struct GreedyBot;
+
+impl Strategy for GreedyBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        ranked(game).first().map(|&(pos, _)| pos)
+    }
+}
+
+// The move that best outscores the best reply, i.e., the next move, which
+// in a two-player game is the opponent's
+pub struct LookaheadBot;
+
+impl Strategy for LookaheadBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        let mut best: Option<(Pos, i32)> = None;
+        for (pos, points) in
+            ranked(game).into_iter().take(LOOKAHEAD_BREADTH)
+        {
+            let mut next = game.clone();
+            next.remove(&next.removal(pos));
+            next.close_up();
+            let reply =
+                ranked(&next).first().map_or(0, |&(_, points)| points);
+            let value = points as i32 - reply as i32;
+            if !matches!(best, Some((_, best_value)) if best_value >= value)
+            {
+                best = Some((pos, value));
+            }
+        }
+        best.map(|(pos, _)| pos)
+    }
+}
+
+// The move that removes the most tiles
+pub struct LargestGroupBot;
+
+impl Strategy for LargestGroupBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        game.moves().into_iter().max_by_key(|&pos| game.removal(pos).len())
+    }
+}
+
+// The move that removes the fewest tiles, keeping the big groups to grow
+// bigger still
+pub struct SmallestGroupBot;
+
+impl Strategy for SmallestGroupBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        game.moves().into_iter().min_by_key(|&pos| game.removal(pos).len())
+    }
+}
+
+// The move that leaves the fewest colors, then the fewest tiles of the
+// color removed, then removes the most tiles
+pub struct ColorMinimizingBot;
+
+impl Strategy for ColorMinimizingBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        let mut count_for_color = HashMap::<u8, usize>::new();
+        for tile in game
+            .tiles
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|tile| tile.kind != Kind::Stone)
+        {
+            *count_for_color.entry(tile.color).or_default() += 1;
+        }
+        game.moves().into_iter().min_by_key(|&pos| {
+            let size = game.removal(pos).len();
+            let left = game
+                .tile(pos)
+                .and_then(|tile| count_for_color.get(&tile.color))
+                .map_or(0, |&count| count.saturating_sub(size));
+            let colors = count_for_color
+                .len()
+                .saturating_sub(usize::from(left == 0));
+            (colors, left, Reverse(size))
+        })
+    }
+}
+
+// Of the moves that remove the most tiles, the one whose random playouts
+// score best on average
+pub struct MonteCarloBot(StdRng);
+
+impl Strategy for MonteCarloBot {
+    fn choose(&mut self, game: &Game) -> Option<Pos> {
+        let rng = &mut self.0;
+        let mut moves = game.moves();
+        moves.sort_by_key(|&pos| Reverse(game.removal(pos).len()));
+        moves
+            .into_iter()
+            .take(MONTE_CARLO_BREADTH)
+            .map(|pos| {
+                let mut next = game.clone();
+                next.remove(&next.removal(pos));
+                next.close_up();
+                let total: u32 = (0..MONTE_CARLO_ROLLOUTS)
+                    .map(|_| rating::rollout(&next, 0.0, rng).score as u32)
+                    .sum();
+                (pos, total)
+            })
+            .max_by_key(|&(_, total)| total)
+            .map(|(pos, _)| pos)
+    }
+}
+
+// Returns the moves with the points each scores, best first
+fn ranked(game: &Game) -> Vec<(Pos, u16)> {
+    let mut ranked: Vec<(Pos, u16)> = game
+        .moves()
+        .into_iter()
+        .map(|pos| (pos, game.score_for(&game.removal(pos))))
+        .collect();
+    ranked.sort_by_key(|&(_, points)| Reverse(points));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Setup, Size, Specials};
+
+    fn setups() -> Vec<Setup> {
+        [
+            Specials::default(),
+            Specials { bombs: 2, wildcards: 2, stones: 2, multipliers: 2 },
+        ]
+        .into_iter()
+        .flat_map(|specials| {
+            (0..5).map(move |seed| Setup {
+                size: Size::new(6, 5),
+                maxcolors: 3,
+                specials,
+                seed,
+                ..Default::default()
+            })
+        })
+        .collect()
+    }
+
+    #[test]
+    fn every_bot_plays_legal_moves_until_there_are_none() {
+        for bot in Bot::ALL {
+            for setup in setups() {
+                let mut game =
+                    setup.new_game(&mut StdRng::seed_from_u64(setup.seed));
+                let mut strategy = bot.strategy(setup.seed);
+                let mut moves = 0;
+                while let Some(pos) = strategy.choose(&game) {
+                    assert!(game.is_legal(pos), "{bot:?} {pos:?}");
+                    game.remove(&game.removal(pos));
+                    game.close_up();
+                    moves += 1;
+                    assert!(moves <= 30, "{bot:?} never stops");
+                }
+                // Only stopping when there's no move left
+                assert!(!game.check_tiles().1, "{bot:?} stopped early");
+            }
+        }
+    }
+}
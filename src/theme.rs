@@ -0,0 +1,308 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// A theme file is an INI file in the themes directory (see themes_dir())
+// with a [Theme] section; every key is optional, defaulting to the Light
+// theme's value, e.g.:
+//   [Theme]
+//   name = Sunset
+//   palette = #FF7F50 #FFD700 #8B008B #4169E1 #2E8B57
+//   background = #2B1B17
+//   stone = #5C4033
+//   focusInner = #FFFFFF
+//   focusOuter = #000000
+//   overlayShadow = #000000
+//   gameOver = #FFD700
+//   won = #FF7F50
+//   timeUp = #4169E1
+//   overlayFont = HelveticaBold
+//   overlaySize = 40
+//   tileFrame = round
+
+use super::CONFIG;
+use crate::i18n::trf;
+use fltk::enums::{Color, Font, FrameType};
+use std::path::{Path, PathBuf};
+use std::sync;
+
+pub static THEME: state::Storage<sync::RwLock<Theme>> =
+    state::Storage::new();
+
+pub const LIGHT: &str = "Light";
+pub const DARK: &str = "Dark";
+pub const HIGH_CONTRAST: &str = "High-Contrast";
+
+const FONTS: [(&str, Font); 12] = [
+    ("Helvetica", Font::Helvetica),
+    ("HelveticaBold", Font::HelveticaBold),
+    ("HelveticaItalic", Font::HelveticaItalic),
+    ("HelveticaBoldItalic", Font::HelveticaBoldItalic),
+    ("Courier", Font::Courier),
+    ("CourierBold", Font::CourierBold),
+    ("CourierItalic", Font::CourierItalic),
+    ("CourierBoldItalic", Font::CourierBoldItalic),
+    ("Times", Font::Times),
+    ("TimesBold", Font::TimesBold),
+    ("TimesItalic", Font::TimesItalic),
+    ("TimesBoldItalic", Font::TimesBoldItalic),
+];
+
+const FRAMES: [(&str, FrameType); 8] = [
+    ("up", FrameType::UpBox),
+    ("thin", FrameType::ThinUpBox),
+    ("round", FrameType::RoundUpBox),
+    ("plastic", FrameType::PlasticUpBox),
+    ("gtk", FrameType::GtkUpBox),
+    ("gleam", FrameType::GleamUpBox),
+    ("border", FrameType::BorderBox),
+    ("flat", FrameType::FlatBox),
+];
+
+#[derive(Clone, Debug)]
+pub struct Theme {
+    pub name: String,
+    pub palette: Vec<Color>, // empty means the standard colors
+    pub background: Color,
+    pub stone: Color,
+    pub focus_inner: Color,
+    pub focus_outer: Color,
+    pub overlay_shadow: Color,
+    pub game_over: Color,
+    pub won: Color, // also used for the winner in two-player games
+    pub time_up: Color,
+    pub overlay_font: Font,
+    pub overlay_size: i32,
+    pub tile_frame: FrameType,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            name: LIGHT.to_string(),
+            palette: vec![],
+            background: Color::BackGround,
+            stone: Color::Dark2,
+            focus_inner: Color::White,
+            focus_outer: Color::Black,
+            overlay_shadow: Color::White,
+            game_over: Color::Green,
+            won: Color::Red,
+            time_up: Color::Blue,
+            overlay_font: Font::TimesBoldItalic,
+            overlay_size: 48,
+            tile_frame: FrameType::UpBox,
+        }
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            name: DARK.to_string(),
+            background: Color::from_hex(0x202020),
+            stone: Color::from_hex(0x505050),
+            focus_inner: Color::Black,
+            focus_outer: Color::White,
+            overlay_shadow: Color::Black,
+            game_over: Color::from_hex(0x3CB44B),
+            won: Color::from_hex(0xF58231),
+            time_up: Color::from_hex(0x42D4F4),
+            overlay_font: Font::HelveticaBold,
+            overlay_size: 44,
+            tile_frame: FrameType::GtkUpBox,
+            ..Default::default()
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            name: HIGH_CONTRAST.to_string(),
+            palette: [
+                0xFFFFFF, 0xFFFF00, 0x00FFFF, 0xFF00FF, 0xFF0000, 0x00FF00,
+                0x0000FF, 0xFF8000, 0x808080, 0x8000FF,
+            ]
+            .iter()
+            .map(|&hex| Color::from_hex(hex))
+            .collect(),
+            background: Color::Black,
+            stone: Color::from_hex(0x404040),
+            focus_inner: Color::Black,
+            focus_outer: Color::White,
+            overlay_shadow: Color::Black,
+            game_over: Color::Yellow,
+            won: Color::Cyan,
+            time_up: Color::Magenta,
+            overlay_font: Font::HelveticaBold,
+            overlay_size: 48,
+            tile_frame: FrameType::FlatBox,
+        }
+    }
+
+    pub fn builtins() -> Vec<Theme> {
+        vec![Theme::default(), Theme::dark(), Theme::high_contrast()]
+    }
+
+    // Reads a theme file; any missing value is the Light theme's
+    pub fn read(filename: &Path) -> Result<Theme, String> {
+        let ini = ini::Ini::load_from_file(filename)
+            .map_err(|err| format!("{}: {err}", filename.display()))?;
+        let properties =
+            ini.section(Some(THEME_SECTION)).ok_or_else(|| {
+                trf(
+                    "{0}: no [{1}] section",
+                    &[&filename.display(), &THEME_SECTION],
+                )
+            })?;
+        let invalid = |key: &str, value: &str| {
+            trf(
+                "{0}: invalid {1} “{2}”",
+                &[&filename.display(), &key, &value],
+            )
+        };
+        let color = |key: &str, default: Color| match properties.get(key) {
+            Some(value) => {
+                parse_color(value).ok_or_else(|| invalid(key, value))
+            }
+            None => Ok(default),
+        };
+        let light = Theme::default();
+        let name = match properties.get(NAME_KEY) {
+            Some(name) => name.trim().to_string(),
+            None => filename
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        let palette = match properties.get(PALETTE_KEY) {
+            Some(value) => value
+                .split_whitespace()
+                .map(|hex| {
+                    parse_color(hex)
+                        .ok_or_else(|| invalid(PALETTE_KEY, hex))
+                })
+                .collect::<Result<Vec<Color>, String>>()?,
+            None => vec![],
+        };
+        let overlay_font = match properties.get(OVERLAY_FONT_KEY) {
+            Some(value) => FONTS
+                .iter()
+                .find(|(name, _)| name == &value.trim())
+                .map(|(_, font)| *font)
+                .ok_or_else(|| invalid(OVERLAY_FONT_KEY, value))?,
+            None => light.overlay_font,
+        };
+        let overlay_size = match properties.get(OVERLAY_SIZE_KEY) {
+            Some(value) => value
+                .trim()
+                .parse()
+                .ok()
+                .filter(|size| (8..=144).contains(size))
+                .ok_or_else(|| invalid(OVERLAY_SIZE_KEY, value))?,
+            None => light.overlay_size,
+        };
+        let tile_frame = match properties.get(TILE_FRAME_KEY) {
+            Some(value) => FRAMES
+                .iter()
+                .find(|(name, _)| name == &value.trim())
+                .map(|(_, frame)| *frame)
+                .ok_or_else(|| invalid(TILE_FRAME_KEY, value))?,
+            None => light.tile_frame,
+        };
+        Ok(Theme {
+            name,
+            palette,
+            background: color(BACKGROUND_KEY, light.background)?,
+            stone: color(STONE_KEY, light.stone)?,
+            focus_inner: color(FOCUS_INNER_KEY, light.focus_inner)?,
+            focus_outer: color(FOCUS_OUTER_KEY, light.focus_outer)?,
+            overlay_shadow: color(
+                OVERLAY_SHADOW_KEY,
+                light.overlay_shadow,
+            )?,
+            game_over: color(GAME_OVER_KEY, light.game_over)?,
+            won: color(WON_KEY, light.won)?,
+            time_up: color(TIME_UP_KEY, light.time_up)?,
+            overlay_font,
+            overlay_size,
+            tile_frame,
+        })
+    }
+}
+
+pub fn themes_dir() -> PathBuf {
+    CONFIG.get().read().unwrap().sibling_dir("themes")
+}
+
+// Returns the built-in themes followed by those in the themes directory
+// along with an error message for every theme file that couldn't be read
+pub fn all() -> (Vec<Theme>, Vec<String>) {
+    let mut themes = Theme::builtins();
+    let mut errors = vec![];
+    if let Ok(entries) = std::fs::read_dir(themes_dir()) {
+        let mut filenames: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().is_some_and(|suffix| suffix == "ini")
+            })
+            .collect();
+        filenames.sort();
+        for filename in filenames {
+            match Theme::read(&filename) {
+                Ok(theme) => {
+                    // A theme file may replace a built-in theme
+                    themes.retain(|other| other.name != theme.name);
+                    themes.push(theme);
+                }
+                Err(err) => errors.push(err),
+            }
+        }
+    }
+    (themes, errors)
+}
+
+// Makes the configured theme (or Light if it isn't found) the current one;
+// returns any errors from reading theme files
+pub fn initialize() -> Vec<String> {
+    let name = CONFIG.get().read().unwrap().board_theme.clone();
+    let (themes, errors) = all();
+    let theme = themes
+        .into_iter()
+        .find(|theme| theme.name == name)
+        .unwrap_or_default();
+    THEME.set(sync::RwLock::new(theme));
+    errors
+}
+
+// Makes the configured theme current, e.g., after switching profiles
+pub fn reload() {
+    let name = CONFIG.get().read().unwrap().board_theme.clone();
+    let (themes, _) = all(); // errors are reported at startup
+    *THEME.get().write().unwrap() = themes
+        .into_iter()
+        .find(|theme| theme.name == name)
+        .unwrap_or_default();
+}
+
+fn parse_color(text: &str) -> Option<Color> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok().map(Color::from_hex)
+}
+
+static THEME_SECTION: &str = "Theme";
+static NAME_KEY: &str = "name";
+static PALETTE_KEY: &str = "palette";
+static BACKGROUND_KEY: &str = "background";
+static STONE_KEY: &str = "stone";
+static FOCUS_INNER_KEY: &str = "focusInner";
+static FOCUS_OUTER_KEY: &str = "focusOuter";
+static OVERLAY_SHADOW_KEY: &str = "overlayShadow";
+static GAME_OVER_KEY: &str = "gameOver";
+static WON_KEY: &str = "won";
+static TIME_UP_KEY: &str = "timeUp";
+static OVERLAY_FONT_KEY: &str = "overlayFont";
+static OVERLAY_SIZE_KEY: &str = "overlaySize";
+static TILE_FRAME_KEY: &str = "tileFrame";
@@ -0,0 +1,275 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// A tile set draws tiles as images rather than as colored boxes. Besides
+// the built-in Gems, every subdirectory of the tile sets directory (see
+// tilesets_dir()) that has a manifest.ini is a tile set, e.g.:
+//   [TileSet]
+//   name = Fruit
+//   0 = apple.svg
+//   1 = cherry.png
+// where each key is a palette index (i.e., a tile's color) and each value
+// is an SVG or PNG file in the same directory. Tiles whose color has no
+// image are drawn as colored boxes.
+
+use super::CONFIG;
+use crate::i18n::trf;
+use fltk::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub const NONE: &str = "None";
+pub const GEMS: &str = "Gems";
+const MANIFEST: &str = "manifest.ini";
+const MARGIN: i32 = 1;
+
+// Each gem is a distinctly shaped SVG element with a distinct color
+const GEM_SHAPES: [(&str, &str); 8] = [
+    ("#E0115F", r#"<circle cx="50" cy="50" r="44""#),
+    (
+        "#50C878",
+        r#"<polygon points="25,8 75,8 92,25 92,75 75,92 25,92 8,75 8,25""#,
+    ),
+    ("#0F52BA", r#"<polygon points="50,6 94,50 50,94 6,50""#),
+    ("#FFC87C", r#"<polygon points="50,6 94,90 6,90""#),
+    ("#9966CC", r#"<polygon points="27,8 73,8 95,50 73,92 27,92 5,50""#),
+    (
+        "#B9F2FF",
+        concat!(
+            r#"<polygon points="50,4 61,37 96,37 68,58 79,93 50,72 21,93"#,
+            r#" 32,58 4,37 39,37""#
+        ),
+    ),
+    ("#E4D00A", r#"<polygon points="50,6 94,38 77,92 23,92 6,38""#),
+    (
+        "#A52A2A",
+        concat!(
+            r#"<path d="M50,90 C20,66 5,50 5,32 A22,22 0 0 1 50,24"#,
+            r#" A22,22 0 0 1 95,32 C95,50 80,66 50,90 Z""#
+        ),
+    ),
+];
+
+#[derive(Clone)]
+enum Image {
+    Svg(fltk::image::SvgImage),
+    Png(fltk::image::PngImage),
+}
+
+impl Image {
+    fn load(filename: &Path) -> Result<Image, String> {
+        let suffix = filename
+            .extension()
+            .map(|suffix| suffix.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let image = match suffix.as_str() {
+            "svg" => fltk::image::SvgImage::load(filename).map(Image::Svg),
+            "png" => fltk::image::PngImage::load(filename).map(Image::Png),
+            _ => {
+                return Err(trf(
+                    "{0}: not an SVG or PNG image",
+                    &[&filename.display()],
+                ))
+            }
+        };
+        image.map_err(|err| format!("{}: {err}", filename.display()))
+    }
+
+    fn scaled(&self, width: i32, height: i32, dimmed: bool) -> Image {
+        match self {
+            Image::Svg(image) => {
+                Image::Svg(scaled(image, width, height, dimmed))
+            }
+            Image::Png(image) => {
+                Image::Png(scaled(image, width, height, dimmed))
+            }
+        }
+    }
+
+    fn draw(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        match self {
+            Image::Svg(image) => image.draw(x, y, width, height),
+            Image::Png(image) => image.draw(x, y, width, height),
+        }
+    }
+}
+
+fn scaled<I: ImageExt>(
+    image: &I,
+    width: i32,
+    height: i32,
+    dimmed: bool,
+) -> I {
+    let mut image = image.copy();
+    image.scale(width, height, true, true);
+    if dimmed {
+        image.inactive();
+    }
+    image
+}
+
+// The images scaled for one tile size (keyed by palette index and whether
+// dimmed) so that they're only scaled again when the tile size changes
+#[derive(Default)]
+struct Cache {
+    width: i32,
+    height: i32,
+    images: HashMap<(u8, bool), Image>,
+}
+
+pub struct TileSet {
+    pub name: String,
+    images: Vec<Option<Image>>, // indexed by palette index
+    cache: RefCell<Cache>,
+}
+
+impl TileSet {
+    pub fn gems() -> Self {
+        let images = GEM_SHAPES
+            .iter()
+            .map(|(color, shape)| {
+                let svg = format!(
+                    r##"<svg xmlns="http://www.w3.org/2000/svg"
+width="100" height="100" viewBox="0 0 100 100">
+{shape} fill="{color}" stroke="#202020" stroke-width="4"/>
+<ellipse cx="38" cy="34" rx="12" ry="7" fill="#FFFFFF" fill-opacity="0.55"/>
+</svg>"##
+                );
+                fltk::image::SvgImage::from_data(&svg).ok().map(Image::Svg)
+            })
+            .collect();
+        Self { name: GEMS.to_string(), images, cache: RefCell::default() }
+    }
+
+    // Reads the tile set whose manifest is in the given directory
+    pub fn read(dir: &Path) -> Result<Self, String> {
+        let filename = dir.join(MANIFEST);
+        let ini = ini::Ini::load_from_file(&filename)
+            .map_err(|err| format!("{}: {err}", filename.display()))?;
+        let properties =
+            ini.section(Some(TILE_SET_SECTION)).ok_or_else(|| {
+                trf(
+                    "{0}: no [{1}] section",
+                    &[&filename.display(), &TILE_SET_SECTION],
+                )
+            })?;
+        let mut images = vec![];
+        for (key, value) in properties.iter() {
+            if key == NAME_KEY {
+                continue;
+            }
+            let index: usize = key.parse().map_err(|_| {
+                trf(
+                    "{0}: “{1}” isn't a palette index",
+                    &[&filename.display(), &key],
+                )
+            })?;
+            if images.len() <= index {
+                images.resize(index + 1, None);
+            }
+            images[index] = Some(Image::load(&dir.join(value.trim()))?);
+        }
+        Ok(Self {
+            name: manifest_name(dir, &ini),
+            images,
+            cache: RefCell::default(),
+        })
+    }
+
+    // Draws the image for the palette index scaled to fit; returns false
+    // if there isn't one
+    pub fn draw(
+        &self,
+        index: u8,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        dimmed: bool,
+    ) -> bool {
+        let Some(Some(source)) = self.images.get(index as usize) else {
+            return false;
+        };
+        let width = width - 2 * MARGIN;
+        let height = height - 2 * MARGIN;
+        let mut cache = self.cache.borrow_mut();
+        if cache.width != width || cache.height != height {
+            *cache = Cache { width, height, images: HashMap::new() };
+        }
+        cache
+            .images
+            .entry((index, dimmed))
+            .or_insert_with(|| source.scaled(width, height, dimmed))
+            .draw(x + MARGIN, y + MARGIN, width, height);
+        true
+    }
+}
+
+pub fn tilesets_dir() -> PathBuf {
+    CONFIG.get().read().unwrap().sibling_dir("tilesets")
+}
+
+// Returns the names of every tile set starting with None (i.e., colored
+// boxes) and Gems
+pub fn names() -> Vec<String> {
+    let mut names = vec![NONE.to_string(), GEMS.to_string()];
+    for dir in user_dirs() {
+        if let Ok(ini) = ini::Ini::load_from_file(dir.join(MANIFEST)) {
+            names.push(manifest_name(&dir, &ini));
+        }
+    }
+    names
+}
+
+// Returns the named tile set or None for colored boxes
+pub fn load(name: &str) -> Result<Option<TileSet>, String> {
+    match name {
+        NONE => Ok(None),
+        GEMS => Ok(Some(TileSet::gems())),
+        _ => {
+            for dir in user_dirs() {
+                if let Ok(ini) =
+                    ini::Ini::load_from_file(dir.join(MANIFEST))
+                {
+                    if manifest_name(&dir, &ini) == name {
+                        return TileSet::read(&dir).map(Some);
+                    }
+                }
+            }
+            Err(trf(
+                "there is no “{0}” tile set in {1}",
+                &[&name, &tilesets_dir().display()],
+            ))
+        }
+    }
+}
+
+fn user_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(tilesets_dir())
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.join(MANIFEST).is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
+
+// The manifest's name if it has one, otherwise the directory's
+fn manifest_name(dir: &Path, ini: &ini::Ini) -> String {
+    ini.section(Some(TILE_SET_SECTION))
+        .and_then(|properties| properties.get(NAME_KEY))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|| {
+            dir.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+}
+
+static TILE_SET_SECTION: &str = "TileSet";
+static NAME_KEY: &str = "name";
@@ -0,0 +1,188 @@
+// Copyright © 2021-23 Mark Summerfield. All rights reserved.
+// License: GPLv3
+
+// Plays every bot on the same boards (the same seeds for each preset's
+// board) with no GUI, spread over all the CPUs, then prints how the bots
+// rank. Run with: gravitate --tournament [GAMES] [BOT ...]
+
+use crate::engine::Setup;
+use crate::preset::Preset;
+use crate::strategy::Bot;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub const FLAG: &str = "--tournament";
+const GAMES: u64 = 10; // per preset
+
+// Returns the process's exit code
+pub fn run(args: &[String]) -> i32 {
+    let (games, names) = match args.split_first() {
+        Some((first, rest)) if first.parse::<u64>().is_ok() => {
+            (first.parse().unwrap(), rest)
+        }
+        _ => (GAMES, args),
+    };
+    let Some(mut bots) = names
+        .iter()
+        .map(|name| Bot::from_name(name))
+        .collect::<Option<Vec<Bot>>>()
+    else {
+        return usage();
+    };
+    if games == 0 {
+        return usage();
+    }
+    if bots.is_empty() {
+        bots = Bot::ALL.to_vec();
+    }
+    let mut jobs = vec![];
+    for preset in Preset::ALL {
+        if let Some((size, maxcolors, specials)) = preset.board() {
+            for seed in 0..games {
+                let setup = Setup {
+                    seed,
+                    size,
+                    maxcolors,
+                    specials,
+                    ..Default::default()
+                };
+                jobs.extend(bots.iter().map(|&bot| (bot, setup)));
+            }
+        }
+    }
+    let count = jobs.len();
+    let jobs = Mutex::new(jobs);
+    let outcomes = Mutex::new(Vec::with_capacity(count));
+    let workers =
+        thread::available_parallelism().map_or(1, |count| count.get());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let job = jobs.lock().unwrap().pop();
+                let Some((bot, setup)) = job else {
+                    break;
+                };
+                let outcome = play(bot, &setup);
+                outcomes.lock().unwrap().push(outcome);
+            });
+        }
+    });
+    print_ranking(games, &bots, &outcomes.into_inner().unwrap());
+    0
+}
+
+fn usage() -> i32 {
+    let names: Vec<&str> = Bot::ALL.iter().map(|bot| bot.name()).collect();
+    eprintln!("usage: gravitate {FLAG} [GAMES] [BOT ...]");
+    eprintln!("plays GAMES boards of each preset (default {GAMES})");
+    eprintln!("with each BOT (default all): {}", names.join(" "));
+    2
+}
+
+struct Outcome {
+    bot: Bot,
+    score: u16,
+    won: bool,
+    moves: u32,
+    elapsed: Duration, // choosing moves
+}
+
+fn play(bot: Bot, setup: &Setup) -> Outcome {
+    let mut game = setup.new_game(&mut StdRng::seed_from_u64(setup.seed));
+    let mut strategy = bot.strategy(setup.seed);
+    let mut moves = 0;
+    let mut elapsed = Duration::ZERO;
+    loop {
+        let start = Instant::now();
+        let pos = strategy.choose(&game);
+        elapsed += start.elapsed();
+        // An illegal move ends the game as if there were none left
+        match pos {
+            Some(pos) if game.is_legal(pos) => {
+                game.remove(&game.removal(pos));
+                game.close_up();
+                moves += 1;
+            }
+            _ => break,
+        }
+    }
+    let (won, _) = game.check_tiles();
+    Outcome { bot, score: game.score, won, moves, elapsed }
+}
+
+fn print_ranking(games: u64, bots: &[Bot], outcomes: &[Outcome]) {
+    let mut rows = vec![];
+    for &bot in bots {
+        let played: Vec<&Outcome> =
+            outcomes.iter().filter(|outcome| outcome.bot == bot).collect();
+        let count = played.len().max(1) as f64;
+        let score =
+            played.iter().map(|outcome| outcome.score as f64).sum::<f64>()
+                / count;
+        let won = played.iter().filter(|outcome| outcome.won).count();
+        let moves: u32 = played.iter().map(|outcome| outcome.moves).sum();
+        let elapsed: Duration =
+            played.iter().map(|outcome| outcome.elapsed).sum();
+        let ms_per_move =
+            elapsed.as_secs_f64() * 1000.0 / moves.max(1) as f64;
+        rows.push((bot, score, won as f64 * 100.0 / count, ms_per_move));
+    }
+    rows.sort_by(|a, b| b.1.total_cmp(&a.1));
+    let boards = Preset::ALL
+        .iter()
+        .filter(|preset| preset.board().is_some())
+        .map(|preset| preset.name())
+        .collect::<Vec<&str>>()
+        .join(", ");
+    println!("{games} boards each of {boards}");
+    println!(
+        "{:>4}  {:<16}{:>12}{:>12}{:>12}",
+        "#", "Bot", "Mean Score", "Clear Rate", "ms/Move"
+    );
+    for (i, (bot, score, clear_rate, ms_per_move)) in
+        rows.iter().enumerate()
+    {
+        println!(
+            "{:>4}  {:<16}{:>12.1}{:>11.1}%{:>12.3}",
+            i + 1,
+            bot.name(),
+            score,
+            clear_rate,
+            ms_per_move
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{Size, Specials};
+
+    #[test]
+    fn the_same_seed_plays_the_same_game() {
+        let specials =
+            Specials { bombs: 3, wildcards: 3, stones: 3, multipliers: 3 };
+        for seed in 0..3 {
+            let setup = Setup {
+                size: Size::new(8, 6),
+                maxcolors: 4,
+                specials,
+                seed,
+                ..Default::default()
+            };
+            for bot in Bot::ALL {
+                let first = play(bot, &setup);
+                let second = play(bot, &setup);
+                assert_eq!(
+                    (first.score, first.won, first.moves),
+                    (second.score, second.won, second.moves),
+                    "{bot:?} {seed}"
+                );
+                assert!(first.moves > 0, "{bot:?} {seed}");
+            }
+        }
+    }
+}
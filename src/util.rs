@@ -2,6 +2,7 @@
 // License: GPLv3
 
 use crate::fixed::APPNAME;
+use crate::i18n::trf;
 use std::{cmp, fmt, str};
 
 pub fn x() -> i32 {
@@ -66,6 +67,6 @@ impl std::fmt::Display for Pos {
 }
 
 pub(crate) fn warning(message: &str) {
-    fltk::dialog::message_title(&format!("Warning — {APPNAME}"));
+    fltk::dialog::message_title(&trf("Warning — {0}", &[&APPNAME]));
     fltk::dialog::message(x() - 200, y() - 100, message);
 }